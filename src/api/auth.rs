@@ -0,0 +1,363 @@
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::model::AccessLevel;
+
+/// A logged-in user. The token identifies the session to server functions that need an access level.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub user: String,
+    pub level: AccessLevel,
+    pub token: String,
+}
+
+/// The access level of `session`, or level 1 when nobody is logged in.
+pub fn access_level(session: &Option<Session>) -> AccessLevel {
+    session
+        .as_ref()
+        .map_or(AccessLevel::Public, |session| session.level)
+}
+
+/// The token of `session` to pass to server functions, empty when nobody is logged in.
+pub fn session_token(session: &Option<Session>) -> String {
+    session
+        .as_ref()
+        .map(|session| session.token.clone())
+        .unwrap_or_default()
+}
+
+/// Checks a user name and password against the credentials stored on the server and starts a session. A name that
+/// failed too often in a row is refused for a while without checking the password.
+#[server]
+pub async fn login(name: String, password: String) -> Result<Session, ServerFnError> {
+    let name = name.trim();
+    if let Some(wait) = throttle::with(|throttle| throttle.wait(name, std::time::Instant::now())) {
+        return Err(ServerFnError::new(format!(
+            "Too many failed logins; try again in {} s",
+            wait.as_secs() + 1
+        )));
+    }
+    let Some(level) = credentials::verify(name, &password)? else {
+        throttle::with(|throttle| throttle.failed(name, std::time::Instant::now()));
+        return Err(ServerFnError::new("Wrong user name or password"));
+    };
+    throttle::with(|throttle| throttle.succeeded(name));
+    sessions::start(name, level)
+}
+
+/// Ends a session. Ending a session that has already expired is not an error.
+#[server]
+pub async fn logout(token: String) -> Result<(), ServerFnError> {
+    sessions::end(&token);
+    Ok(())
+}
+
+/// Fails unless `token` belongs to a live session with at least the `required` access level.
+#[cfg(feature = "server")]
+pub(super) fn require_level(token: &str, required: AccessLevel) -> Result<Session, ServerFnError> {
+    let session = sessions::get(token)
+        .ok_or_else(|| ServerFnError::new("Not logged in, or the session has expired"))?;
+    if !session.level.allows(required) {
+        return Err(ServerFnError::new(format!(
+            "This needs access {}",
+            required
+        )));
+    }
+    Ok(session)
+}
+
+/// User names with salted, hashed passwords, kept in a JSON file on the server.
+///
+/// The file defaults to `credentials.json` in the working directory and can be moved with the `CREDENTIALS_FILE`
+/// environment variable. If it does not exist, it is created with the accounts `operator` (level 2) and `engineer`
+/// (level 3), each with a random password that is printed to the server console once and cannot be shown again.
+#[cfg(feature = "server")]
+mod credentials {
+    use std::fs;
+    use std::io::{ErrorKind, Write};
+    use std::path::PathBuf;
+
+    use dioxus::prelude::ServerFnError;
+    use serde::{Deserialize, Serialize};
+    use sha2::Sha256;
+
+    use crate::model::AccessLevel;
+
+    /// PBKDF2-HMAC-SHA256 iterations for new passwords, as OWASP recommends. Each credential keeps its own count, so
+    /// raising this later does not lock out existing accounts.
+    const HASH_ROUNDS: u32 = 600_000;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Credential {
+        name: String,
+        level: AccessLevel,
+        salt: String,
+        rounds: u32,
+        hash: String,
+    }
+
+    impl Credential {
+        fn new(name: &str, level: AccessLevel, password: &str) -> Result<Self, ServerFnError> {
+            let salt = super::random_hex(16)?;
+            let hash = hash_password(&salt, password, HASH_ROUNDS);
+            Ok(Self {
+                name: name.to_string(),
+                level,
+                salt,
+                rounds: HASH_ROUNDS,
+                hash,
+            })
+        }
+    }
+
+    fn credentials_path() -> PathBuf {
+        std::env::var_os("CREDENTIALS_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("credentials.json"))
+    }
+
+    pub fn hash_password(salt: &str, password: &str, rounds: u32) -> String {
+        let mut hash = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt.as_bytes(), rounds, &mut hash);
+        super::to_hex(&hash)
+    }
+
+    // Compares every byte so the time taken does not tell how much of a hash matched
+    fn same_hash(a: &str, b: &str) -> bool {
+        a.len() == b.len()
+            && a.bytes()
+                .zip(b.bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+
+    fn load() -> Result<Vec<Credential>, ServerFnError> {
+        let path = credentials_path();
+        match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).map_err(|err| {
+                ServerFnError::new(format!("The credentials file is damaged: {}", err))
+            }),
+            Err(err) if err.kind() == ErrorKind::NotFound => create(&path),
+            Err(err) => Err(ServerFnError::new(format!(
+                "The credentials file cannot be read: {}",
+                err
+            ))),
+        }
+    }
+
+    /// Creates the credentials file with the first accounts and prints their passwords, which are stored only hashed.
+    fn create(path: &PathBuf) -> Result<Vec<Credential>, ServerFnError> {
+        let mut accounts = Vec::new();
+        let mut credentials = Vec::new();
+        for (name, level) in [
+            ("operator", AccessLevel::Operator),
+            ("engineer", AccessLevel::Engineer),
+        ] {
+            let password = super::random_hex(8)?;
+            credentials.push(Credential::new(name, level, &password)?);
+            accounts.push(format!("  {} ({}): {}", name, level, password));
+        }
+        // Another request may have created the file in the meantime; never overwrite it
+        let mut file = match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+        {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => return load(),
+            Err(err) => return Err(err.into()),
+        };
+        file.write_all(serde_json::to_string_pretty(&credentials)?.as_bytes())?;
+        eprintln!(
+            "Created {} with these accounts. Note the passwords now; they are not shown again:\n{}",
+            path.display(),
+            accounts.join("\n")
+        );
+        Ok(credentials)
+    }
+
+    /// The access level of `name` if `password` is right.
+    pub fn verify(name: &str, password: &str) -> Result<Option<AccessLevel>, ServerFnError> {
+        let credentials = load()?;
+        let Some(credential) = credentials
+            .iter()
+            .find(|credential| credential.name == name)
+        else {
+            // Hash anyway so unknown names take as long as wrong passwords
+            hash_password("", password, HASH_ROUNDS);
+            return Ok(None);
+        };
+        let hash = hash_password(&credential.salt, password, credential.rounds);
+        Ok(same_hash(&hash, &credential.hash).then_some(credential.level))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_hashes_depend_on_salt_and_password() {
+            let hash = hash_password("salt", "2222", 1_000);
+            assert_eq!(hash.len(), 64);
+            assert_eq!(hash, hash_password("salt", "2222", 1_000));
+            assert_ne!(hash, hash_password("pepper", "2222", 1_000));
+            assert_ne!(hash, hash_password("salt", "2223", 1_000));
+            assert_ne!(hash, hash_password("salt", "2222", 1_001));
+            assert!(same_hash(&hash, &hash_password("salt", "2222", 1_000)));
+            assert!(!same_hash(&hash, &hash[1..]));
+        }
+
+        #[test]
+        fn test_hashes_are_pbkdf2_hmac_sha256() {
+            // RFC 7914, section 11
+            assert_eq!(
+                hash_password("salt", "passwd", 1),
+                "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc"
+            );
+        }
+    }
+}
+
+/// Failed logins per user name, kept in memory. After [`MAX_FAILURES`](throttle::MAX_FAILURES) in a row a name has to
+/// wait [`LOCKOUT`](throttle::LOCKOUT) before it can try again, which makes guessing a password slow.
+#[cfg(feature = "server")]
+mod throttle {
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    pub const MAX_FAILURES: u32 = 5;
+    pub const LOCKOUT: Duration = Duration::from_secs(60);
+
+    #[derive(Debug)]
+    pub struct Throttle {
+        // Failures in a row and when the last one happened
+        failures: BTreeMap<String, (u32, Instant)>,
+    }
+
+    impl Throttle {
+        pub const fn new() -> Self {
+            Self {
+                failures: BTreeMap::new(),
+            }
+        }
+
+        /// How long `name` still has to wait, if it is locked out.
+        pub fn wait(&mut self, name: &str, now: Instant) -> Option<Duration> {
+            self.failures
+                .retain(|_, (_, last)| now.duration_since(*last) < LOCKOUT);
+            let (count, last) = self.failures.get(name)?;
+            (*count >= MAX_FAILURES).then(|| LOCKOUT - now.duration_since(*last))
+        }
+
+        pub fn failed(&mut self, name: &str, now: Instant) {
+            let entry = self.failures.entry(name.to_string()).or_insert((0, now));
+            *entry = (entry.0 + 1, now);
+        }
+
+        pub fn succeeded(&mut self, name: &str) {
+            self.failures.remove(name);
+        }
+    }
+
+    static THROTTLE: Mutex<Throttle> = Mutex::new(Throttle::new());
+
+    /// Runs `action` on the shared throttle.
+    pub fn with<T>(action: impl FnOnce(&mut Throttle) -> T) -> T {
+        let mut throttle = THROTTLE
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        action(&mut throttle)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_locks_out_after_repeated_failures() {
+            let mut throttle = Throttle::new();
+            let start = Instant::now();
+            for _ in 1..MAX_FAILURES {
+                throttle.failed("operator", start);
+            }
+            assert_eq!(throttle.wait("operator", start), None);
+
+            throttle.failed("operator", start);
+            assert_eq!(throttle.wait("operator", start), Some(LOCKOUT));
+            assert_eq!(throttle.wait("engineer", start), None);
+            let later = start + Duration::from_secs(20);
+            assert_eq!(
+                throttle.wait("operator", later),
+                Some(LOCKOUT - Duration::from_secs(20))
+            );
+            assert_eq!(throttle.wait("operator", start + LOCKOUT), None);
+
+            throttle.failed("engineer", start);
+            throttle.succeeded("engineer");
+            assert!(throttle.failures.is_empty());
+        }
+    }
+}
+
+/// Live sessions, kept in memory so a server restart logs everybody out.
+///
+/// A session ends after [`SESSION_TIMEOUT`](sessions::SESSION_TIMEOUT) without a request that checked it.
+#[cfg(feature = "server")]
+mod sessions {
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    use dioxus::prelude::ServerFnError;
+
+    use super::Session;
+    use crate::model::AccessLevel;
+
+    pub const SESSION_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+    static SESSIONS: Mutex<BTreeMap<String, (Session, Instant)>> = Mutex::new(BTreeMap::new());
+
+    fn lock() -> std::sync::MutexGuard<'static, BTreeMap<String, (Session, Instant)>> {
+        SESSIONS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn start(user: &str, level: AccessLevel) -> Result<Session, ServerFnError> {
+        let session = Session {
+            user: user.to_string(),
+            level,
+            token: super::random_hex(32)?,
+        };
+        lock().insert(session.token.clone(), (session.clone(), Instant::now()));
+        Ok(session)
+    }
+
+    pub fn end(token: &str) {
+        lock().remove(token);
+    }
+
+    /// The session of `token` if it has not expired. Checking a session keeps it alive.
+    pub fn get(token: &str) -> Option<Session> {
+        let mut sessions = lock();
+        let now = Instant::now();
+        sessions.retain(|_, (_, last_used)| now.duration_since(*last_used) < SESSION_TIMEOUT);
+        let (session, last_used) = sessions.get_mut(token)?;
+        *last_used = now;
+        Some(session.clone())
+    }
+}
+
+#[cfg(feature = "server")]
+fn random_hex(bytes: usize) -> Result<String, ServerFnError> {
+    let mut buffer = vec![0u8; bytes];
+    getrandom::getrandom(&mut buffer)
+        .map_err(|err| ServerFnError::new(format!("No random numbers available: {}", err)))?;
+    Ok(to_hex(&buffer))
+}
+
+#[cfg(feature = "server")]
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
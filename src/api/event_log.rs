@@ -0,0 +1,111 @@
+use dioxus::prelude::*;
+
+use crate::model::LogEntry;
+
+/// Loads every entry of the event log stored on the server, oldest first. Needs an access level 2 session.
+#[server]
+pub async fn load_server_log(token: String) -> Result<Vec<LogEntry>, ServerFnError> {
+    super::auth::require_level(&token, crate::model::AccessLevel::Operator)?;
+    storage::read()
+}
+
+/// Appends entries to the event log stored on the server and returns them as stored. Needs an access level 2 session.
+///
+/// The server numbers the entries after the last one it has, so several clients can write to the same log, and records
+/// the user of the session as their operator; the sequence numbers and operators sent are ignored.
+#[server]
+pub async fn append_server_log(
+    token: String,
+    entries: Vec<LogEntry>,
+) -> Result<Vec<LogEntry>, ServerFnError> {
+    let session = super::auth::require_level(&token, crate::model::AccessLevel::Operator)?;
+    let entries = entries
+        .into_iter()
+        .map(|entry| LogEntry {
+            operator: session.user.clone(),
+            ..entry
+        })
+        .collect();
+    storage::append(entries)
+}
+
+/// The event log on the server's local disk, one JSON [`LogEntry`] per line. The file is only ever appended to.
+///
+/// The file defaults to `logs/event_log.jsonl` in the working directory; the directory can be moved with the `LOGS_DIR`
+/// environment variable.
+#[cfg(feature = "server")]
+mod storage {
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    use dioxus::prelude::ServerFnError;
+
+    use crate::model::LogEntry;
+
+    // The last stored sequence number, read from the file on first use. Two appends must not interleave.
+    static LAST_SEQUENCE: Mutex<Option<u64>> = Mutex::new(None);
+
+    fn log_path() -> PathBuf {
+        std::env::var_os("LOGS_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("logs"))
+            .join("event_log.jsonl")
+    }
+
+    fn read_unlocked() -> Result<Vec<LogEntry>, ServerFnError> {
+        let contents = match fs::read_to_string(log_path()) {
+            Ok(contents) => contents,
+            // Nothing has been logged yet
+            Err(_) => return Ok(Vec::new()),
+        };
+        contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .enumerate()
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|err| {
+                    ServerFnError::new(format!("Event log entry {} is damaged: {}", index + 1, err))
+                })
+            })
+            .collect()
+    }
+
+    pub fn read() -> Result<Vec<LogEntry>, ServerFnError> {
+        let _guard = LAST_SEQUENCE
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        read_unlocked()
+    }
+
+    pub fn append(mut entries: Vec<LogEntry>) -> Result<Vec<LogEntry>, ServerFnError> {
+        let mut last_sequence = LAST_SEQUENCE
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let last = match *last_sequence {
+            Some(last) => last,
+            None => read_unlocked()?
+                .last()
+                .map(|entry| entry.sequence)
+                .unwrap_or(0),
+        };
+        if entries.is_empty() {
+            return Ok(entries);
+        }
+        let mut lines = String::new();
+        for (entry, sequence) in entries.iter_mut().zip(last + 1..) {
+            entry.sequence = sequence;
+            lines.push_str(&serde_json::to_string(entry)?);
+            lines.push('\n');
+        }
+        let path = log_path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(lines.as_bytes())?;
+        *last_sequence = Some(last + entries.len() as u64);
+        Ok(entries)
+    }
+}
@@ -0,0 +1,20 @@
+//! The api module contains the fullstack server functions our views call. Each server function runs on the server when
+//! called from the client; the request and response types are shared by both sides.
+//!
+//! Server-only helpers (file access and the like) live behind the `server` feature so they are not compiled into the
+//! web bundle.
+
+mod auth;
+pub use auth::{access_level, login, logout, session_token, Session};
+
+mod projects;
+pub use projects::{
+    create_server_project, delete_server_project, list_server_projects, load_server_project,
+    save_server_project, validate_project_name,
+};
+
+mod reports;
+pub use reports::generate_server_report;
+
+mod event_log;
+pub use event_log::{append_server_log, load_server_log};
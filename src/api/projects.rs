@@ -0,0 +1,319 @@
+use std::collections::BTreeMap;
+
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::model::{Timestamp, UnitKind, UnitStore};
+
+/// What the project list shows about a project stored on the server.
+///
+/// The counts are kept in a small file next to the project and the rest comes from the file system, so listing does
+/// not open the project files.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectSummary {
+    pub name: String,
+    /// Size of the project file in bytes.
+    pub size: u64,
+    /// When the project was last saved, if the file system records it.
+    pub modified: Option<Timestamp>,
+    /// How many units of each kind the project held when it was last saved.
+    pub counts: BTreeMap<UnitKind, usize>,
+}
+
+impl ProjectSummary {
+    pub fn unit_count(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// The kinds the project has units of with their counts, such as "3 Detectors, 1 Output".
+    pub fn counts_label(&self) -> String {
+        let parts: Vec<String> = self
+            .counts
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(kind, count)| match count {
+                1 => format!("1 {}", kind.label()),
+                count => format!("{} {}", count, kind.plural_label()),
+            })
+            .collect();
+        if parts.is_empty() {
+            "No units".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+
+    /// The file size in B, kB or MB.
+    pub fn size_label(&self) -> String {
+        match self.size {
+            size if size < 1_000 => format!("{} B", size),
+            size if size < 1_000_000 => format!("{:.1} kB", size as f64 / 1_000.0),
+            size => format!("{:.1} MB", size as f64 / 1_000_000.0),
+        }
+    }
+}
+
+/// Checks that `name` can be used as a project name.
+///
+/// Names become file names on the server, so only letters, digits, spaces, `-` and `_` are allowed.
+pub fn validate_project_name(name: &str) -> Result<(), String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("Project name must not be empty".to_string());
+    }
+    if trimmed.len() > 64 {
+        return Err("Project name must be at most 64 characters".to_string());
+    }
+    if let Some(invalid) = trimmed
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '_')))
+    {
+        return Err(format!("Project name must not contain '{}'", invalid));
+    }
+    Ok(())
+}
+
+/// Lists every project stored on the server, sorted by name. Needs the access level 2 session that opening one needs.
+///
+/// Only names, unit counts and file details are sent; the projects themselves are opened with [`load_server_project`].
+#[server]
+pub async fn list_server_projects(token: String) -> Result<Vec<ProjectSummary>, ServerFnError> {
+    super::auth::require_level(&token, crate::model::AccessLevel::Operator)?;
+    storage::list()
+}
+
+/// Creates an empty project. Fails if a project with the same name already exists. Needs an access level 3 session.
+#[server]
+pub async fn create_server_project(
+    token: String,
+    name: String,
+) -> Result<ProjectSummary, ServerFnError> {
+    super::auth::require_level(&token, crate::model::AccessLevel::Engineer)?;
+    validate_project_name(&name).map_err(ServerFnError::new)?;
+    let name = name.trim();
+    if storage::exists(name) {
+        return Err(ServerFnError::new(format!(
+            "Project '{}' already exists",
+            name
+        )));
+    }
+    storage::write(name, &UnitStore::new())?;
+    storage::summary(name)
+}
+
+/// Loads a project. The stored file is validated the same way as a file opened in the browser. Needs an access level 2
+/// session.
+#[server]
+pub async fn load_server_project(token: String, name: String) -> Result<UnitStore, ServerFnError> {
+    super::auth::require_level(&token, crate::model::AccessLevel::Operator)?;
+    validate_project_name(&name).map_err(ServerFnError::new)?;
+    storage::read(name.trim())
+}
+
+/// Saves a project, creating it if needed. Needs an access level 3 session.
+#[server]
+pub async fn save_server_project(
+    token: String,
+    name: String,
+    store: UnitStore,
+) -> Result<ProjectSummary, ServerFnError> {
+    super::auth::require_level(&token, crate::model::AccessLevel::Engineer)?;
+    validate_project_name(&name).map_err(ServerFnError::new)?;
+    crate::model::project::validate_store(&store)
+        .map_err(|err| ServerFnError::new(err.to_string()))?;
+    let name = name.trim();
+    storage::write(name, &store)?;
+    storage::summary(name)
+}
+
+/// Deletes a project. Needs an access level 3 session.
+#[server]
+pub async fn delete_server_project(token: String, name: String) -> Result<(), ServerFnError> {
+    super::auth::require_level(&token, crate::model::AccessLevel::Engineer)?;
+    validate_project_name(&name).map_err(ServerFnError::new)?;
+    storage::remove(name.trim())
+}
+
+/// Project files on the server's local disk, one `<name>.json` per project in the project file format, with its unit
+/// counts in `<name>.counts` next to it.
+///
+/// The directory defaults to `projects` in the working directory and can be moved with the `PROJECTS_DIR` environment
+/// variable.
+#[cfg(feature = "server")]
+mod storage {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::time::UNIX_EPOCH;
+
+    use dioxus::prelude::ServerFnError;
+
+    use super::ProjectSummary;
+    use crate::model::{load_project, save_project, Timestamp, UnitKind, UnitStore};
+
+    fn projects_dir() -> PathBuf {
+        std::env::var_os("PROJECTS_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("projects"))
+    }
+
+    fn project_path(name: &str) -> PathBuf {
+        projects_dir().join(format!("{}.json", name))
+    }
+
+    fn counts_path(name: &str) -> PathBuf {
+        projects_dir().join(format!("{}.counts", name))
+    }
+
+    /// The counts saved with the project. Projects saved before counts were kept are read once to count them.
+    fn counts(name: &str) -> BTreeMap<UnitKind, usize> {
+        let saved = fs::read_to_string(counts_path(name))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok());
+        if let Some(counts) = saved {
+            return counts;
+        }
+        let Ok(store) = read(name) else {
+            // Damaged projects are reported when they are opened
+            return BTreeMap::new();
+        };
+        let counts = store.counts_by_kind();
+        let _ = write_counts(name, &counts);
+        counts
+    }
+
+    fn write_counts(name: &str, counts: &BTreeMap<UnitKind, usize>) -> Result<(), ServerFnError> {
+        let contents = serde_json::to_string(counts)?;
+        fs::write(counts_path(name), contents)?;
+        Ok(())
+    }
+
+    pub fn exists(name: &str) -> bool {
+        project_path(name).exists()
+    }
+
+    pub fn read(name: &str) -> Result<UnitStore, ServerFnError> {
+        let contents = fs::read_to_string(project_path(name))
+            .map_err(|_| ServerFnError::new(format!("Project '{}' does not exist", name)))?;
+        load_project(&contents)
+            .map_err(|err| ServerFnError::new(format!("Project '{}' is damaged: {}", name, err)))
+    }
+
+    pub fn write(name: &str, store: &UnitStore) -> Result<(), ServerFnError> {
+        fs::create_dir_all(projects_dir())?;
+        // Write next to the target and rename, so a crash never leaves a half-written project behind
+        let path = project_path(name);
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, save_project(store))?;
+        fs::rename(&temp, &path)?;
+        write_counts(name, &store.counts_by_kind())
+    }
+
+    pub fn remove(name: &str) -> Result<(), ServerFnError> {
+        fs::remove_file(project_path(name))
+            .map_err(|_| ServerFnError::new(format!("Project '{}' does not exist", name)))?;
+        let _ = fs::remove_file(counts_path(name));
+        Ok(())
+    }
+
+    pub fn summary(name: &str) -> Result<ProjectSummary, ServerFnError> {
+        summary_of(name, &project_path(name))
+            .ok_or_else(|| ServerFnError::new(format!("Project '{}' does not exist", name)))
+    }
+
+    fn summary_of(name: &str, path: &Path) -> Option<ProjectSummary> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|elapsed| Timestamp(elapsed.as_millis() as u64));
+        Some(ProjectSummary {
+            name: name.to_string(),
+            size: metadata.len(),
+            modified,
+            counts: counts(name),
+        })
+    }
+
+    pub fn list() -> Result<Vec<ProjectSummary>, ServerFnError> {
+        let entries = match fs::read_dir(projects_dir()) {
+            Ok(entries) => entries,
+            // No project has been saved yet
+            Err(_) => return Ok(Vec::new()),
+        };
+
+        let mut summaries = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            // Files that could not have been saved under their name are skipped; damaged ones are reported when opened
+            if super::validate_project_name(name).is_err() {
+                continue;
+            }
+            if let Some(summary) = summary_of(name, &path) {
+                summaries.push(summary);
+            }
+        }
+        summaries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(summaries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_project_name_accepts_simple_names() {
+        assert_eq!(validate_project_name("Site A - Building 2"), Ok(()));
+        assert_eq!(validate_project_name("hospital_east"), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_project_name_rejects_paths_and_empty_names() {
+        assert!(validate_project_name("").is_err());
+        assert!(validate_project_name("   ").is_err());
+        assert!(validate_project_name("../etc/passwd").is_err());
+        assert!(validate_project_name("a/b").is_err());
+        assert!(validate_project_name(&"x".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_size_label_picks_a_unit() {
+        let summary = |size| ProjectSummary {
+            name: "Site".to_string(),
+            size,
+            modified: None,
+            counts: BTreeMap::new(),
+        };
+
+        assert_eq!(summary(512).size_label(), "512 B");
+        assert_eq!(summary(1_500).size_label(), "1.5 kB");
+        assert_eq!(summary(2_340_000).size_label(), "2.3 MB");
+    }
+
+    #[test]
+    fn test_counts_label_names_the_kinds_present() {
+        let mut store = UnitStore::new();
+        store.add_unit(UnitKind::Detector, "Detector 1");
+        store.add_unit(UnitKind::Detector, "Detector 2");
+        store.add_unit(UnitKind::Output, "Output 1");
+        let mut summary = ProjectSummary {
+            name: "Site".to_string(),
+            size: 0,
+            modified: None,
+            counts: store.counts_by_kind(),
+        };
+
+        assert_eq!(summary.unit_count(), 3);
+        assert_eq!(summary.counts_label(), "2 Detectors, 1 Output");
+        summary.counts = UnitStore::new().counts_by_kind();
+        assert_eq!(summary.counts_label(), "No units");
+    }
+}
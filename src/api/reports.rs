@@ -0,0 +1,15 @@
+use dioxus::prelude::*;
+
+use crate::model::ReportFormat;
+
+/// Renders the device schedule of a project stored on the server. Needs an access level 2 session, like opening it.
+#[server]
+pub async fn generate_server_report(
+    token: String,
+    name: String,
+    format: ReportFormat,
+) -> Result<String, ServerFnError> {
+    let store = super::load_server_project(token, name.clone()).await?;
+    let title = format!("Device Schedule – {}", name.trim());
+    Ok(crate::model::Report::new(&title, &store).render(format))
+}
@@ -0,0 +1,72 @@
+use crate::api::{access_level, login, Session};
+use crate::model::{AccessLevel, EventLog};
+use dioxus::prelude::*;
+
+/// A user name and password form that starts a session on the server.
+///
+/// On success the session is stored in the shared session context and the event log records the user as the operator.
+/// `on_login` is called afterwards, e.g. to close a popup.
+#[component]
+pub fn LoginForm(#[props(default)] on_login: EventHandler<()>) -> Element {
+    let mut session = use_context::<Signal<Option<Session>>>();
+    let mut event_log = use_context::<Signal<EventLog>>();
+    let mut name = use_signal(String::new);
+    let mut password = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let submit = move |evt: FormEvent| async move {
+        evt.prevent_default();
+        match login(name(), password()).await {
+            Ok(started) => {
+                event_log.write().set_operator(&started.user);
+                session.set(Some(started));
+                password.set(String::new());
+                error.set(None);
+                on_login.call(());
+            }
+            Err(err) => error.set(Some(err.to_string())),
+        }
+    };
+
+    rsx! {
+        form { class: "login-form", onsubmit: submit,
+            input {
+                placeholder: "User",
+                autocomplete: "username",
+                value: "{name}",
+                oninput: move |evt| name.set(evt.value()),
+            }
+            input {
+                r#type: "password",
+                placeholder: "Password",
+                autocomplete: "current-password",
+                value: "{password}",
+                oninput: move |evt| password.set(evt.value()),
+            }
+            button { r#type: "submit", "Log in" }
+            if let Some(err) = error() {
+                span { class: "login-error", "{err}" }
+            }
+        }
+    }
+}
+
+/// Renders `children` only when the current session has at least the `required` access level, and a login form otherwise.
+///
+/// The guard only hides the page; server functions that change anything check the session again themselves.
+#[component]
+pub fn AccessGuard(required: AccessLevel, children: Element) -> Element {
+    let session = use_context::<Signal<Option<Session>>>();
+    let level = access_level(&session.read());
+
+    if level.allows(required) {
+        return children;
+    }
+    rsx! {
+        div { class: "access-denied",
+            h2 { "Access {required} needed" }
+            p { "You have access {level}. Log in with an account that has access {required} to continue." }
+            LoginForm {}
+        }
+    }
+}
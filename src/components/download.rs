@@ -0,0 +1,24 @@
+use dioxus::prelude::*;
+
+/// Offers `contents` to the user as a file download named `file_name`.
+///
+/// The browser has no API for writing files from wasm directly, so a temporary object URL is created and clicked from
+/// JavaScript.
+pub fn download_file(file_name: &str, mime_type: &str, contents: &str) {
+    // JSON string literals are valid JavaScript string literals, which takes care of escaping
+    let quote = |value: &str| serde_json::to_string(value).unwrap_or_default();
+    let script = format!(
+        r#"
+        const blob = new Blob([{contents}], {{ type: {mime_type} }});
+        const link = document.createElement("a");
+        link.href = URL.createObjectURL(blob);
+        link.download = {file_name};
+        link.click();
+        URL.revokeObjectURL(link.href);
+        "#,
+        contents = quote(contents),
+        mime_type = quote(mime_type),
+        file_name = quote(file_name),
+    );
+    document::eval(&script);
+}
@@ -1,8 +1,16 @@
 //! The components module contains all shared components for our app. Components are the building blocks of dioxus apps.
-//! They can be used to defined common UI elements like buttons, forms, and modals. In this template, we define a Hero
-//! component and an Echo component for fullstack apps to be used in our app.
+//! They can be used to defined common UI elements like buttons, forms, and modals. Here we define a helper for offering
+//! files as downloads, a panel for projects stored on the server, a list that only renders the rows in view and the login
+//! form and access guard.
 
-mod hero;
-pub use hero::Hero;
+mod download;
+pub use download::download_file;
 
-mod echo;
+mod server_projects;
+pub use server_projects::ServerProjects;
+
+mod access;
+pub use access::{AccessGuard, LoginForm};
+
+mod virtual_list;
+pub use virtual_list::VirtualList;
@@ -0,0 +1,176 @@
+use super::download_file;
+use crate::api::{
+    create_server_project, delete_server_project, generate_server_report, list_server_projects,
+    load_server_project, save_server_project, session_token, validate_project_name, Session,
+};
+use crate::model::{ReportFormat, UnitStore};
+use dioxus::prelude::*;
+
+/// Lists the projects stored on the server and lets the user save the current unit store to, or open one from, the server.
+///
+/// Opening a project hands the loaded store to `on_open`; the caller decides how to apply it. Every action uses the
+/// token of the current session, which the server checks for access level 2 to list or open projects or download a
+/// report and access level 3 for the rest.
+#[component]
+pub fn ServerProjects(on_open: EventHandler<(String, UnitStore)>) -> Element {
+    let unit_store = use_context::<Signal<UnitStore>>();
+    let session = use_context::<Signal<Option<Session>>>();
+    let mut project_name = use_signal(String::new);
+    let mut status = use_signal(|| None::<Result<String, String>>);
+    // Re-runs when the session changes and whenever it is restarted after a change on the server
+    let mut projects = use_resource(move || {
+        let token = session_token(&session.read());
+        list_server_projects(token)
+    });
+
+    let save = move |_| async move {
+        let name = project_name();
+        if let Err(err) = validate_project_name(&name) {
+            status.set(Some(Err(err)));
+            return;
+        }
+        let store = unit_store.read().clone();
+        let token = session_token(&session.read());
+        match save_server_project(token, name, store).await {
+            Ok(summary) => {
+                status.set(Some(Ok(format!(
+                    "Saved '{}' ({} units, {})",
+                    summary.name,
+                    summary.unit_count(),
+                    summary.size_label()
+                ))));
+                projects.restart();
+            }
+            Err(err) => status.set(Some(Err(err.to_string()))),
+        }
+    };
+
+    let create = move |_| async move {
+        let name = project_name();
+        if let Err(err) = validate_project_name(&name) {
+            status.set(Some(Err(err)));
+            return;
+        }
+        let token = session_token(&session.read());
+        match create_server_project(token, name).await {
+            Ok(summary) => {
+                status.set(Some(Ok(format!("Created '{}'", summary.name))));
+                projects.restart();
+            }
+            Err(err) => status.set(Some(Err(err.to_string()))),
+        }
+    };
+
+    rsx! {
+        div { id: "server-projects",
+            h3 { "Server Projects" }
+            div { id: "server-project-controls",
+                input {
+                    placeholder: "Project name",
+                    value: "{project_name}",
+                    oninput: move |evt| project_name.set(evt.value()),
+                }
+                button { onclick: save, "Save to Server" }
+                button { onclick: create, "New" }
+            }
+            match status() {
+                Some(Ok(message)) => rsx! { p { class: "server-project-status", "{message}" } },
+                Some(Err(message)) => rsx! { p { class: "server-project-status error", "{message}" } },
+                None => rsx! {},
+            }
+            match &*projects.read() {
+                Some(Ok(list)) if list.is_empty() => rsx! {
+                    p { class: "server-project-status", "No projects on the server" }
+                },
+                Some(Ok(list)) => rsx! {
+                    table { id: "server-project-table",
+                        tbody {
+                            for summary in list.iter().cloned() {
+                                tr { key: "{summary.name}",
+                                    td { "{summary.name}" }
+                                    td { title: "{summary.counts_label()}", "{summary.unit_count()} units" }
+                                    td { "{summary.size_label()}" }
+                                    td {
+                                        if let Some(modified) = summary.modified {
+                                            "saved {modified}"
+                                        }
+                                    }
+                                    td {
+                                        button {
+                                            onclick: {
+                                                let name = summary.name.clone();
+                                                move |_| {
+                                                    let name = name.clone();
+                                                    async move {
+                                                        let token = session_token(&session.read());
+                                                        match load_server_project(token, name.clone()).await {
+                                                            Ok(store) => {
+                                                                project_name.set(name.clone());
+                                                                status.set(Some(Ok(format!("Opened '{}'", name))));
+                                                                on_open.call((name, store));
+                                                            }
+                                                            Err(err) => status.set(Some(Err(err.to_string()))),
+                                                        }
+                                                    }
+                                                }
+                                            },
+                                            "Open"
+                                        }
+                                    }
+                                    td {
+                                        button {
+                                            title: "Download the device schedule",
+                                            onclick: {
+                                                let name = summary.name.clone();
+                                                move |_| {
+                                                    let name = name.clone();
+                                                    async move {
+                                                        let format = ReportFormat::Html;
+                                                        let token = session_token(&session.read());
+                                                        match generate_server_report(token, name.clone(), format).await {
+                                                            Ok(report) => download_file(
+                                                                &format!("{} schedule.{}", name, format.extension()),
+                                                                format.mime(),
+                                                                &report,
+                                                            ),
+                                                            Err(err) => status.set(Some(Err(err.to_string()))),
+                                                        }
+                                                    }
+                                                }
+                                            },
+                                            "Report"
+                                        }
+                                    }
+                                    td {
+                                        button {
+                                            class: "delete",
+                                            onclick: {
+                                                let name = summary.name.clone();
+                                                move |_| {
+                                                    let name = name.clone();
+                                                    async move {
+                                                        let token = session_token(&session.read());
+                                                        match delete_server_project(token, name.clone()).await {
+                                                            Ok(()) => {
+                                                                status.set(Some(Ok(format!("Deleted '{}'", name))));
+                                                                projects.restart();
+                                                            }
+                                                            Err(err) => status.set(Some(Err(err.to_string()))),
+                                                        }
+                                                    }
+                                                }
+                                            },
+                                            "Delete"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+                Some(Err(err)) => rsx! { p { class: "server-project-status error", "Could not list projects: {err}" } },
+                None => rsx! { p { class: "server-project-status", "Loading projects…" } },
+            }
+        }
+    }
+}
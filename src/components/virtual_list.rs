@@ -0,0 +1,51 @@
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+
+/// A scrolling list that only renders the rows in view.
+///
+/// Every row must be `row_height` pixels tall. `render_row` is called with the index of each visible row, plus `overscan`
+/// rows either side so fast scrolling does not show gaps.
+#[component]
+pub fn VirtualList(
+    row_count: usize,
+    row_height: f64,
+    height: f64,
+    #[props(default = 5)] overscan: usize,
+    render_row: Callback<usize, Element>,
+) -> Element {
+    let mut container = use_signal(|| None::<Rc<MountedData>>);
+    let mut scroll_top = use_signal(|| 0.0);
+
+    let on_scroll = move |_| async move {
+        let Some(element) = container() else {
+            return;
+        };
+        if let Ok(offset) = element.get_scroll_offset().await {
+            scroll_top.set(offset.y);
+        }
+    };
+
+    // A list that shrank under the scroll position shows its last rows rather than nothing
+    let visible = (height / row_height).ceil() as usize;
+    let first = ((scroll_top() / row_height).floor() as usize)
+        .min(row_count.saturating_sub(visible))
+        .saturating_sub(overscan);
+    let last = (first + visible + 2 * overscan).min(row_count);
+
+    rsx! {
+        div {
+            class: "virtual-list",
+            style: "height: {height}px; overflow-y: auto;",
+            onmounted: move |evt| container.set(Some(evt.data())),
+            onscroll: on_scroll,
+            div { style: "height: {row_count as f64 * row_height}px; position: relative;",
+                div { style: "position: absolute; top: {first as f64 * row_height}px; left: 0; right: 0;",
+                    for index in first..last {
+                        {render_row.call(index)}
+                    }
+                }
+            }
+        }
+    }
+}
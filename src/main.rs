@@ -2,13 +2,17 @@
 // need dioxus
 use dioxus::prelude::*;
 
-use views::{Blog, Config, Navbar};
+use model::{EventLog, History, PanelFilter, PanelStatus, Simulation, Timestamp, UnitStore};
+use views::{Blog, CauseEffect, Config, Log, Navbar, Simulator, Status};
 
+/// Define an api module that contains the server functions used by our views.
+mod api;
 /// Define a components module that contains all shared components for our app.
 mod components;
 /// Define a views module that contains the UI for all Layouts and Routes for our app.
 mod views;
 
+/// Define a model module that contains the domain types (units and the unit store) edited by the views.
 mod model;
 
 /// The Route enum is used to define the structure of internal routes in our app. All route enums need to derive
@@ -26,6 +30,14 @@ enum Route {
         // the component for that route will be rendered. The component name that is rendered defaults to the variant name.
         #[route("/")]
         Config {},
+        #[route("/cause-effect")]
+        CauseEffect {},
+        #[route("/simulator")]
+        Simulator {},
+        #[route("/status")]
+        Status {},
+        #[route("/log")]
+        Log {},
         // The route attribute can include dynamic parameters that implement [`std::str::FromStr`] and [`std::fmt::Display`] with the `:` syntax.
         // In this case, id will match any integer like `/blog/123` or `/blog/-456`.
         #[route("/blog/:id")]
@@ -53,6 +65,78 @@ fn main() {
 /// Components should be annotated with `#[component]` to support props, better error messages, and autocomplete
 #[component]
 fn App() -> Element {
+    // The unit store is shared by every route, so it is provided as context here instead of living in one view.
+    let unit_store = use_context_provider(|| Signal::new(UnitStore::new()));
+    // Undo history for the unit store, shared so it survives switching between routes
+    let mut history = use_context_provider(|| Signal::new(History::new()));
+    // The panel picked in the navbar, which narrows what the pages show
+    let mut panel_filter = use_context_provider(|| Signal::new(PanelFilter::All));
+    // A panel that was removed, or is not in a newly opened project, shows everything again
+    use_effect(move || {
+        if let Some(panel) = panel_filter().panel() {
+            if unit_store.read().network().get_panel(panel).is_none() {
+                panel_filter.set(PanelFilter::All);
+            }
+        }
+    });
+    // The simulation and the runtime state it drives are shared by the Simulator and Status pages
+    let mut simulation = use_context_provider(|| Signal::new(Simulation::new()));
+    let mut panel_status = use_context_provider(|| Signal::new(PanelStatus::new()));
+    // After a configuration change the simulation follows the new rules and zones, and removed units are forgotten.
+    // Opening another project clears both instead, as ids are reused.
+    use_effect(move || {
+        let store = unit_store.read();
+        simulation.write().refresh(&store);
+        panel_status.write().retain(&store);
+    });
+    use_effect(move || {
+        panel_status
+            .write()
+            .follow_simulation(&unit_store.read(), &simulation.read());
+    });
+
+    // The logged-in user, if any. It decides the access level of every page.
+    let session = use_context_provider(|| Signal::new(None::<api::Session>));
+
+    // Configuration and state changes are queued by the history and the panel status and written to the event log here
+    let mut event_log = use_context_provider(|| Signal::new(EventLog::new()));
+    use_effect(move || {
+        if !history.read().has_changes() && !panel_status.read().has_changes() {
+            return;
+        }
+        let mut records = history.write().take_changes();
+        records.extend(panel_status.write().take_changes());
+        event_log
+            .write()
+            .append(&unit_store.peek(), Timestamp::now(), records);
+    });
+    // The log saved on the server is restored first; until then nothing is sent, so entries are never stored twice. The
+    // server log needs an access level 2 session, so it waits for one. One request runs at a time, and one that failed is
+    // tried again when the next entry is written or someone logs in.
+    let mut log_request = use_signal(|| false);
+    use_effect(move || {
+        if !api::access_level(&session.read()).can_operate() {
+            return;
+        }
+        let token = api::session_token(&session.read());
+        let restored = event_log.read().is_restored();
+        let batch = event_log.read().unsynced().to_vec();
+        if *log_request.peek() || (restored && batch.is_empty()) {
+            return;
+        }
+        log_request.set(true);
+        spawn(async move {
+            if !restored {
+                if let Ok(saved) = api::load_server_log(token).await {
+                    event_log.write().restore(saved);
+                }
+            } else if let Ok(stored) = api::append_server_log(token, batch).await {
+                event_log.write().mark_synced(stored);
+            }
+            log_request.set(false);
+        });
+    });
+
     // The `rsx!` macro lets us define HTML inside of rust. It expands to an Element with all of our HTML inside.
     rsx! {
         // In addition to element and text (which we will see later), rsx can contain other components. In this case,
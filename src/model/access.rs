@@ -0,0 +1,65 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// The EN 54-2 access levels of a control panel.
+///
+/// Each level includes everything the levels below it may do.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize,
+)]
+pub enum AccessLevel {
+    /// Level 1: anyone may look at the panel.
+    #[default]
+    Public = 1,
+    /// Level 2: acknowledge, silence and reset, disable and test.
+    Operator = 2,
+    /// Level 3: change the configuration.
+    Engineer = 3,
+}
+
+impl AccessLevel {
+    /// The level number from EN 54-2.
+    pub fn number(&self) -> u8 {
+        *self as u8
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AccessLevel::Public => "Public",
+            AccessLevel::Operator => "Operator",
+            AccessLevel::Engineer => "Engineer",
+        }
+    }
+
+    /// Whether this level includes `required`.
+    pub fn allows(&self, required: AccessLevel) -> bool {
+        *self >= required
+    }
+
+    /// Whether the panel may be acknowledged, silenced and reset, and units disabled and tested.
+    pub fn can_operate(&self) -> bool {
+        self.allows(AccessLevel::Operator)
+    }
+}
+
+impl fmt::Display for AccessLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Level {} ({})", self.number(), self.label())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_include_the_ones_below() {
+        assert!(!AccessLevel::Public.can_operate());
+        assert!(AccessLevel::Operator.can_operate());
+        assert!(!AccessLevel::Operator.allows(AccessLevel::Engineer));
+        assert!(AccessLevel::Engineer.can_operate());
+        assert!(AccessLevel::Engineer.allows(AccessLevel::Public));
+        assert_eq!(AccessLevel::Operator.to_string(), "Level 2 (Operator)");
+    }
+}
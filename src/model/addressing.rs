@@ -0,0 +1,119 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::UnitKind;
+
+/// The highest address a device can have on a loop.
+pub const MAX_LOOP_ADDRESS: u16 = 250;
+
+/// The capacity given to loops that are created without one.
+pub const DEFAULT_LOOP_CAPACITY: u16 = MAX_LOOP_ADDRESS;
+
+/// Where an addressable device sits: a loop number and an address from 1 to the loop capacity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct LoopAddress {
+    pub loop_number: u16,
+    pub address: u16,
+}
+
+impl LoopAddress {
+    pub fn new(loop_number: u16, address: u16) -> Self {
+        Self {
+            loop_number,
+            address,
+        }
+    }
+}
+
+impl fmt::Display for LoopAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "L{}.{:03}", self.loop_number, self.address)
+    }
+}
+
+/// A detection loop of the panel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Loop {
+    number: u16,
+    capacity: u16,
+}
+
+impl Loop {
+    pub fn new(number: u16, capacity: u16) -> Self {
+        Self { number, capacity }
+    }
+
+    pub fn number(&self) -> u16 {
+        self.number
+    }
+
+    /// The number of addresses available on the loop, starting at 1.
+    pub fn capacity(&self) -> u16 {
+        self.capacity
+    }
+
+    pub(super) fn set_capacity(&mut self, capacity: u16) {
+        self.capacity = capacity;
+    }
+}
+
+/// How full a loop is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoopUtilisation {
+    pub loop_number: u16,
+    pub used: usize,
+    pub capacity: u16,
+}
+
+impl LoopUtilisation {
+    /// Used addresses as a percentage of the capacity.
+    pub fn percent(&self) -> u32 {
+        if self.capacity == 0 {
+            return 0;
+        }
+        (self.used as u32 * 100) / self.capacity as u32
+    }
+}
+
+/// Reasons an addressing change is refused.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AddressError {
+    UnitNotFound(u64),
+    NotAddressable(UnitKind),
+    LoopNotFound(u16),
+    AddressOutOfRange { address: u16, capacity: u16 },
+    AddressInUse { address: LoopAddress, other: u64 },
+    LoopFull(u16),
+    InvalidCapacity(u16),
+    CapacityBelowUsage { capacity: u16, highest: u16 },
+}
+
+impl fmt::Display for AddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressError::UnitNotFound(id) => write!(f, "unit {} does not exist", id),
+            AddressError::NotAddressable(kind) => write!(f, "a {} has no loop address", kind),
+            AddressError::LoopNotFound(number) => write!(f, "loop {} does not exist", number),
+            AddressError::AddressOutOfRange { address, capacity } => {
+                write!(f, "address {} is outside 1 to {}", address, capacity)
+            }
+            AddressError::AddressInUse { address, other } => {
+                write!(f, "address {} is already used by unit {}", address, other)
+            }
+            AddressError::LoopFull(number) => write!(f, "loop {} has no free address", number),
+            AddressError::InvalidCapacity(capacity) => write!(
+                f,
+                "capacity {} is outside 1 to {}",
+                capacity, MAX_LOOP_ADDRESS
+            ),
+            AddressError::CapacityBelowUsage { capacity, highest } => write!(
+                f,
+                "capacity {} is below the highest address in use ({})",
+                capacity, highest
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AddressError {}
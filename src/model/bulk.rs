@@ -0,0 +1,555 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use super::{
+    AddressError, MembershipError, NameError, NetworkError, UnitKind, UnitStore, MAX_NAME_LENGTH,
+};
+
+/// Placeholder in a rename pattern for the position of the unit in the edit, counting from 1.
+pub const NUMBER_PLACEHOLDER: &str = "{n}";
+/// Placeholder in a rename pattern for the current name of the unit.
+pub const NAME_PLACEHOLDER: &str = "{name}";
+
+/// What a [`BulkEdit`] does to each of its units.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulkAction {
+    /// Removes the units and their subtrees.
+    Delete,
+    /// Renames the units from a pattern such as "Detector {n} – Floor 2", see [`NUMBER_PLACEHOLDER`] and
+    /// [`NAME_PLACEHOLDER`].
+    Rename { pattern: String },
+    /// Puts the units in a zone, or takes them out of their zones.
+    MoveToZone { zone: Option<u64> },
+    /// Gives the units the first free addresses on a loop, or takes them off their loops. Units already on the loop
+    /// keep their address.
+    AssignToLoop { loop_number: Option<u16> },
+    /// Turns the units into units of another kind, see [`UnitStore::set_kind`].
+    ChangeKind { kind: UnitKind },
+    /// Gives the units and their subtrees to another panel.
+    MoveToPanel { panel: u16 },
+}
+
+/// Why a bulk edit was refused. Nothing is changed when it is.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulkError {
+    NoUnits,
+    UnitNotFound(u64),
+    RootIsFixed,
+    Name {
+        unit: u64,
+        error: NameError,
+    },
+    /// Two units of the edit would end up with the same name and kind.
+    SameName {
+        name: String,
+    },
+    Membership(MembershipError),
+    Address {
+        unit: u64,
+        error: AddressError,
+    },
+    KindNotConfigurable(UnitKind),
+    Network(NetworkError),
+}
+
+impl fmt::Display for BulkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BulkError::NoUnits => write!(f, "no units are selected"),
+            BulkError::UnitNotFound(id) => write!(f, "unit {} does not exist", id),
+            BulkError::RootIsFixed => write!(f, "the Root unit cannot be edited"),
+            BulkError::Name { unit, error } => write!(f, "unit {}: {}", unit, error),
+            BulkError::SameName { name } => {
+                write!(f, "more than one unit would be called '{}'", name)
+            }
+            BulkError::Membership(error) => write!(f, "{}", error),
+            BulkError::Address { unit, error } => write!(f, "unit {}: {}", unit, error),
+            BulkError::KindNotConfigurable(kind) => write!(f, "units cannot be made {}", kind),
+            BulkError::Network(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for BulkError {}
+
+/// One action applied to several units at once, all or nothing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BulkEdit {
+    units: Vec<u64>,
+    action: BulkAction,
+}
+
+impl BulkEdit {
+    /// An edit of `units` in the given order, which is the order rename patterns number them in. Repeated ids are
+    /// dropped.
+    pub fn new(units: impl IntoIterator<Item = u64>, action: BulkAction) -> Self {
+        let mut seen = HashSet::new();
+        Self {
+            units: units.into_iter().filter(|id| seen.insert(*id)).collect(),
+            action,
+        }
+    }
+
+    pub fn action(&self) -> &BulkAction {
+        &self.action
+    }
+
+    /// Checks whether the edit would apply to `store` without changing it.
+    pub fn check(&self, store: &UnitStore) -> Result<(), BulkError> {
+        self.apply_to(&mut store.clone())
+    }
+
+    /// Applies the edit to every unit, or to none of them if any one fails.
+    pub fn apply(&self, store: &mut UnitStore) -> Result<(), BulkError> {
+        let mut draft = store.clone();
+        self.apply_to(&mut draft)?;
+        *store = draft;
+        Ok(())
+    }
+
+    // May leave `store` half edited on error; callers hand in a copy
+    fn apply_to(&self, store: &mut UnitStore) -> Result<(), BulkError> {
+        if self.units.is_empty() {
+            return Err(BulkError::NoUnits);
+        }
+        for id in &self.units {
+            if *id == 0 {
+                return Err(BulkError::RootIsFixed);
+            }
+            store.get_unit(*id).ok_or(BulkError::UnitNotFound(*id))?;
+        }
+
+        match &self.action {
+            BulkAction::Delete => {
+                // A unit may already be gone with the subtree of another selected unit
+                for id in &self.units {
+                    store.remove_unit(*id);
+                }
+            }
+            BulkAction::Rename { pattern } => {
+                let names: Vec<String> = self
+                    .units
+                    .iter()
+                    .enumerate()
+                    .map(|(index, id)| {
+                        rename(pattern, index + 1, store.get_unit(*id).unwrap().name())
+                    })
+                    .collect();
+                let kinds: Vec<UnitKind> = self
+                    .units
+                    .iter()
+                    .map(|id| store.get_unit(*id).unwrap().kind())
+                    .collect();
+                check_names(store, &self.units, &names, &kinds)?;
+                for (id, name) in self.units.iter().zip(&names) {
+                    store.get_unit_mut(*id).unwrap().set_name(name);
+                }
+            }
+            BulkAction::MoveToZone { zone: Some(zone) } => {
+                for id in &self.units {
+                    store
+                        .assign_to_zone(*id, *zone)
+                        .map_err(BulkError::Membership)?;
+                }
+            }
+            BulkAction::MoveToZone { zone: None } => {
+                for id in &self.units {
+                    store.unassign_from_zone(*id);
+                }
+            }
+            BulkAction::AssignToLoop { loop_number } => {
+                for id in &self.units {
+                    let unit = store.get_unit(*id).unwrap();
+                    if !unit.kind().is_addressable() {
+                        return Err(BulkError::Address {
+                            unit: *id,
+                            error: AddressError::NotAddressable(unit.kind()),
+                        });
+                    }
+                    let current = unit.address().map(|address| address.loop_number);
+                    let address = match loop_number {
+                        Some(number) if current == Some(*number) => continue,
+                        Some(number) => Some(store.first_free_address(*number)),
+                        None => None,
+                    };
+                    let address = address
+                        .transpose()
+                        .map_err(|error| BulkError::Address { unit: *id, error })?;
+                    store
+                        .set_address(*id, address)
+                        .map_err(|error| BulkError::Address { unit: *id, error })?;
+                }
+            }
+            BulkAction::ChangeKind { kind } => {
+                if !UnitKind::CONFIGURABLE.contains(kind) {
+                    return Err(BulkError::KindNotConfigurable(*kind));
+                }
+                let names: Vec<String> = self
+                    .units
+                    .iter()
+                    .map(|id| store.get_unit(*id).unwrap().name().to_string())
+                    .collect();
+                check_names(store, &self.units, &names, &vec![*kind; self.units.len()])?;
+                for id in &self.units {
+                    store.set_kind(*id, *kind);
+                }
+            }
+            BulkAction::MoveToPanel { panel } => {
+                for id in &self.units {
+                    // Units mounted below another selected unit go with it
+                    if store
+                        .ancestors(*id)
+                        .iter()
+                        .any(|ancestor| self.units.contains(ancestor))
+                    {
+                        continue;
+                    }
+                    store.set_panel(*id, *panel).map_err(BulkError::Network)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Describes the edit for the history list.
+    pub fn describe(&self, store: &UnitStore) -> String {
+        let count = match self.units.len() {
+            1 => "1 unit".to_string(),
+            count => format!("{} units", count),
+        };
+        match &self.action {
+            BulkAction::Delete => format!("Remove {}", count),
+            BulkAction::Rename { pattern } => format!("Rename {} to '{}'", count, pattern),
+            BulkAction::MoveToZone { zone: Some(zone) } => format!(
+                "Move {} to {}",
+                count,
+                store
+                    .get_unit(*zone)
+                    .map(|zone| zone.name().to_string())
+                    .unwrap_or_else(|| format!("zone {}", zone))
+            ),
+            BulkAction::MoveToZone { zone: None } => format!("Take {} out of their zones", count),
+            BulkAction::AssignToLoop {
+                loop_number: Some(number),
+            } => format!("Put {} on loop {}", count, number),
+            BulkAction::AssignToLoop { loop_number: None } => {
+                format!("Take {} off their loops", count)
+            }
+            BulkAction::ChangeKind { kind } => format!("Change {} to {}", count, kind),
+            BulkAction::MoveToPanel { panel } => format!(
+                "Move {} to {}",
+                count,
+                store
+                    .network()
+                    .get_panel(*panel)
+                    .map(|panel| panel.name().to_string())
+                    .unwrap_or_else(|| format!("panel {}", panel))
+            ),
+        }
+    }
+}
+
+/// Fills in a rename pattern for the unit at `number` (counting from 1) currently called `name`.
+pub fn rename(pattern: &str, number: usize, name: &str) -> String {
+    pattern
+        .replace(NUMBER_PLACEHOLDER, &number.to_string())
+        .replace(NAME_PLACEHOLDER, name)
+}
+
+// Checks the names `units` would have as `kinds` against each other and against every unit outside the edit
+fn check_names(
+    store: &UnitStore,
+    units: &[u64],
+    names: &[String],
+    kinds: &[UnitKind],
+) -> Result<(), BulkError> {
+    let mut taken: BTreeMap<(UnitKind, &str), u64> = store
+        .get_all_units()
+        .iter()
+        .filter(|unit| !units.contains(&unit.id()))
+        .map(|unit| ((unit.kind(), unit.name()), unit.id()))
+        .collect();
+    for ((id, name), kind) in units.iter().zip(names).zip(kinds) {
+        if name.trim().is_empty() {
+            return Err(BulkError::Name {
+                unit: *id,
+                error: NameError::Empty,
+            });
+        }
+        if name.chars().count() > MAX_NAME_LENGTH {
+            return Err(BulkError::Name {
+                unit: *id,
+                error: NameError::TooLong {
+                    max: MAX_NAME_LENGTH,
+                },
+            });
+        }
+        if let Some(other) = taken.insert((*kind, name.as_str()), *id) {
+            return Err(if units.contains(&other) {
+                BulkError::SameName { name: name.clone() }
+            } else {
+                BulkError::Name {
+                    unit: *id,
+                    error: NameError::Duplicate { other },
+                }
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::LoopAddress;
+
+    fn names(store: &UnitStore, ids: &[u64]) -> Vec<String> {
+        ids.iter()
+            .map(|id| store.get_unit(*id).unwrap().name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_rename_with_pattern() {
+        let mut store = UnitStore::new();
+        let ids: Vec<u64> = (1..=3)
+            .map(|n| store.add_unit(UnitKind::Detector, &format!("Det {}", n)))
+            .collect();
+        let edit = BulkEdit::new(
+            [ids[2], ids[0]],
+            BulkAction::Rename {
+                pattern: "Detector {n} – Floor 2 ({name})".to_string(),
+            },
+        );
+
+        edit.apply(&mut store).unwrap();
+        assert_eq!(
+            names(&store, &ids),
+            vec![
+                "Detector 2 – Floor 2 (Det 1)",
+                "Det 2",
+                "Detector 1 – Floor 2 (Det 3)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rename_refuses_clashes_and_changes_nothing() {
+        let mut store = UnitStore::new();
+        let a = store.add_unit(UnitKind::Detector, "A");
+        let b = store.add_unit(UnitKind::Detector, "B");
+        store.add_unit(UnitKind::Detector, "Hall");
+        let before = store.clone();
+
+        let same = BulkEdit::new(
+            [a, b],
+            BulkAction::Rename {
+                pattern: "Kitchen".to_string(),
+            },
+        );
+        assert_eq!(
+            same.apply(&mut store),
+            Err(BulkError::SameName {
+                name: "Kitchen".to_string()
+            })
+        );
+        let taken = BulkEdit::new(
+            [a],
+            BulkAction::Rename {
+                pattern: "Hall".to_string(),
+            },
+        );
+        assert!(matches!(
+            taken.apply(&mut store),
+            Err(BulkError::Name {
+                error: NameError::Duplicate { .. },
+                ..
+            })
+        ));
+        assert_eq!(store, before);
+
+        // Swapping names within the edit is fine
+        let swap = BulkEdit::new(
+            [b, a],
+            BulkAction::Rename {
+                pattern: "{n}".to_string(),
+            },
+        );
+        swap.apply(&mut store).unwrap();
+        assert_eq!(names(&store, &[a, b]), vec!["2", "1"]);
+    }
+
+    #[test]
+    fn test_move_to_zone_is_all_or_nothing() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let mcp = store.add_unit(UnitKind::ManualCallPoint, "MCP 1");
+        let out = store.add_unit(UnitKind::Output, "Output 1");
+        let zone = store.add_unit(UnitKind::DetectionZone, "Zone 1");
+
+        let edit = BulkEdit::new([det, mcp, out], BulkAction::MoveToZone { zone: Some(zone) });
+        assert!(matches!(
+            edit.check(&store),
+            Err(BulkError::Membership(MembershipError::NotAssignable { .. }))
+        ));
+        assert_eq!(store.zone_of(det), None);
+
+        BulkEdit::new([det, mcp], BulkAction::MoveToZone { zone: Some(zone) })
+            .apply(&mut store)
+            .unwrap();
+        assert_eq!(store.zone_of(mcp), Some(zone));
+        BulkEdit::new([det], BulkAction::MoveToZone { zone: None })
+            .apply(&mut store)
+            .unwrap();
+        assert_eq!(store.zone_of(det), None);
+    }
+
+    #[test]
+    fn test_move_to_panel() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let out = store.add_unit(UnitKind::Output, "Output 1");
+        let sounder = store
+            .add_child(det, UnitKind::FireAlarmDevice, "Base Sounder")
+            .unwrap();
+        let annex = store.add_panel("Annex").unwrap();
+
+        let edit = BulkEdit::new(
+            [sounder, det, out],
+            BulkAction::MoveToPanel { panel: annex },
+        );
+        assert_eq!(edit.describe(&store), "Move 3 units to Annex");
+        edit.apply(&mut store).unwrap();
+        assert_eq!(store.panel_of(det), Some(annex));
+        assert_eq!(store.panel_of(sounder), Some(annex));
+        assert_eq!(store.panel_of(out), Some(annex));
+        assert_eq!(
+            BulkEdit::new([sounder], BulkAction::MoveToPanel { panel: 1 }).apply(&mut store),
+            Err(BulkError::Network(NetworkError::Mounted {
+                unit: sounder,
+                parent: det
+            }))
+        );
+        assert_eq!(
+            BulkEdit::new([det], BulkAction::MoveToPanel { panel: 9 }).apply(&mut store),
+            Err(BulkError::Network(NetworkError::PanelNotFound(9)))
+        );
+    }
+
+    #[test]
+    fn test_assign_to_loop_fills_free_addresses() {
+        let mut store = UnitStore::new();
+        let number = store.add_loop(2).unwrap();
+        let ids: Vec<u64> = (1..=3)
+            .map(|n| store.add_unit(UnitKind::Detector, &format!("Detector {}", n)))
+            .collect();
+        store.auto_address(ids[1]).unwrap();
+        let before = store.clone();
+
+        let all = BulkEdit::new(
+            ids.clone(),
+            BulkAction::AssignToLoop {
+                loop_number: Some(number),
+            },
+        );
+        assert_eq!(
+            all.apply(&mut store),
+            Err(BulkError::Address {
+                unit: ids[2],
+                error: AddressError::LoopFull(number)
+            })
+        );
+        assert_eq!(store, before);
+
+        BulkEdit::new(
+            [ids[0], ids[1]],
+            BulkAction::AssignToLoop {
+                loop_number: Some(number),
+            },
+        )
+        .apply(&mut store)
+        .unwrap();
+        assert_eq!(
+            store.get_unit(ids[0]).unwrap().address(),
+            Some(LoopAddress::new(number, 2))
+        );
+        assert_eq!(
+            store.get_unit(ids[1]).unwrap().address(),
+            Some(LoopAddress::new(number, 1))
+        );
+    }
+
+    #[test]
+    fn test_change_kind_drops_what_no_longer_fits() {
+        let mut store = UnitStore::new();
+        store.add_loop(10).unwrap();
+        let det = store.add_unit(UnitKind::Detector, "Kitchen");
+        let zone = store.add_unit(UnitKind::DetectionZone, "Zone 1");
+        store.assign_to_zone(det, zone).unwrap();
+        store.auto_address(det).unwrap();
+        let rule = store
+            .cause_effect_mut()
+            .add_rule("Rule 1", crate::model::InputLogic::Any)
+            .unwrap();
+        store.set_rule_input(rule, det, true).unwrap();
+
+        BulkEdit::new(
+            [det],
+            BulkAction::ChangeKind {
+                kind: UnitKind::ManualCallPoint,
+            },
+        )
+        .apply(&mut store)
+        .unwrap();
+        assert_eq!(store.zone_of(det), Some(zone));
+        assert!(store.get_unit(det).unwrap().address().is_some());
+        assert!(store
+            .cause_effect()
+            .get_rule(rule)
+            .unwrap()
+            .inputs()
+            .contains(&det));
+
+        BulkEdit::new(
+            [det],
+            BulkAction::ChangeKind {
+                kind: UnitKind::AlarmZone,
+            },
+        )
+        .apply(&mut store)
+        .unwrap();
+        let unit = store.get_unit(det).unwrap();
+        assert_eq!(unit.kind(), UnitKind::AlarmZone);
+        assert_eq!(unit.address(), None);
+        assert_eq!(store.zone_of(det), None);
+        assert!(store
+            .cause_effect()
+            .get_rule(rule)
+            .unwrap()
+            .inputs()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_delete_and_root() {
+        let mut store = UnitStore::new();
+        let parent = store.add_unit(UnitKind::Detector, "Detector 1");
+        let child = store
+            .add_child(parent, UnitKind::Output, "Base Sounder")
+            .unwrap();
+        let other = store.add_unit(UnitKind::Output, "Output 1");
+
+        assert_eq!(
+            BulkEdit::new([other, 0], BulkAction::Delete).apply(&mut store),
+            Err(BulkError::RootIsFixed)
+        );
+        assert_eq!(
+            BulkEdit::new([], BulkAction::Delete).check(&store),
+            Err(BulkError::NoUnits)
+        );
+        BulkEdit::new([parent, child, other], BulkAction::Delete)
+            .apply(&mut store)
+            .unwrap();
+        assert_eq!(store.count(), 0);
+    }
+}
@@ -0,0 +1,444 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::UnitKind;
+
+/// How the active inputs of a rule are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InputLogic {
+    /// Any single active input triggers the rule.
+    Any,
+    /// Every input must be active.
+    All,
+    /// At least this many inputs must be active at the same time.
+    Coincidence(usize),
+}
+
+impl fmt::Display for InputLogic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputLogic::Any => write!(f, "Any"),
+            InputLogic::All => write!(f, "All"),
+            InputLogic::Coincidence(n) => write!(f, "{} of N", n),
+        }
+    }
+}
+
+/// Reasons a rule cannot be edited.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CauseEffectError {
+    RuleNotFound(u64),
+    UnitNotFound(u64),
+    NotAnInput(UnitKind),
+    NotAnOutput(UnitKind),
+    InvalidCoincidence,
+}
+
+impl fmt::Display for CauseEffectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CauseEffectError::RuleNotFound(id) => write!(f, "rule {} does not exist", id),
+            CauseEffectError::UnitNotFound(id) => write!(f, "unit {} does not exist", id),
+            CauseEffectError::NotAnInput(kind) => write!(f, "a {} cannot be a rule input", kind),
+            CauseEffectError::NotAnOutput(kind) => {
+                write!(f, "a {} cannot be a rule output", kind)
+            }
+            CauseEffectError::InvalidCoincidence => {
+                write!(f, "coincidence must require at least one input")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CauseEffectError {}
+
+/// A single cause-and-effect rule: when its inputs satisfy `logic`, all of its outputs activate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    id: u64,
+    name: String,
+    logic: InputLogic,
+    inputs: BTreeSet<u64>,
+    outputs: BTreeSet<u64>,
+}
+
+impl Rule {
+    pub fn new(id: u64, name: &str, logic: InputLogic) -> Self {
+        Self {
+            id,
+            name: name.to_string(),
+            logic,
+            inputs: BTreeSet::new(),
+            outputs: BTreeSet::new(),
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
+    pub fn logic(&self) -> InputLogic {
+        self.logic
+    }
+
+    pub fn inputs(&self) -> &BTreeSet<u64> {
+        &self.inputs
+    }
+
+    pub fn outputs(&self) -> &BTreeSet<u64> {
+        &self.outputs
+    }
+
+    /// Returns true if the rule fires for the given set of active unit ids.
+    pub fn is_triggered(&self, active_inputs: &BTreeSet<u64>) -> bool {
+        let active = self
+            .inputs
+            .iter()
+            .filter(|input| active_inputs.contains(input))
+            .count();
+        match self.logic {
+            InputLogic::Any => active > 0,
+            InputLogic::All => !self.inputs.is_empty() && active == self.inputs.len(),
+            InputLogic::Coincidence(required) => required > 0 && active >= required,
+        }
+    }
+}
+
+/// All cause-and-effect rules of a configuration.
+///
+/// The matrix only stores unit ids; checking that an id refers to a unit of a suitable kind is done
+/// by [`super::UnitStore`], which owns both the units and the matrix.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CauseEffectMatrix {
+    rules: Vec<Rule>,
+    next_id: u64,
+}
+
+impl CauseEffectMatrix {
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Adds a rule and returns its id, or refuses a coincidence that needs no inputs.
+    pub fn add_rule(&mut self, name: &str, logic: InputLogic) -> Result<u64, CauseEffectError> {
+        if logic == InputLogic::Coincidence(0) {
+            return Err(CauseEffectError::InvalidCoincidence);
+        }
+        let id = self.next_id;
+        self.rules.push(Rule::new(id, name, logic));
+        self.next_id += 1;
+        Ok(id)
+    }
+
+    pub fn remove_rule(&mut self, id: u64) -> bool {
+        let before = self.rules.len();
+        self.rules.retain(|rule| rule.id() != id);
+        self.rules.len() != before
+    }
+
+    pub fn get_rule(&self, id: u64) -> Option<&Rule> {
+        self.rules.iter().find(|rule| rule.id() == id)
+    }
+
+    pub fn get_rule_mut(&mut self, id: u64) -> Option<&mut Rule> {
+        self.rules.iter_mut().find(|rule| rule.id() == id)
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// The id the next added rule gets.
+    pub fn next_id(&self) -> u64 {
+        self.next_id
+    }
+
+    pub fn set_logic(&mut self, id: u64, logic: InputLogic) -> Result<(), CauseEffectError> {
+        if logic == InputLogic::Coincidence(0) {
+            return Err(CauseEffectError::InvalidCoincidence);
+        }
+        let rule = self
+            .get_rule_mut(id)
+            .ok_or(CauseEffectError::RuleNotFound(id))?;
+        rule.logic = logic;
+        Ok(())
+    }
+
+    /// Adds or removes `unit` as an input of rule `id`.
+    pub(super) fn set_input(
+        &mut self,
+        id: u64,
+        unit: u64,
+        enabled: bool,
+    ) -> Result<(), CauseEffectError> {
+        let rule = self
+            .get_rule_mut(id)
+            .ok_or(CauseEffectError::RuleNotFound(id))?;
+        if enabled {
+            rule.inputs.insert(unit);
+        } else {
+            rule.inputs.remove(&unit);
+        }
+        Ok(())
+    }
+
+    /// Adds or removes `unit` as an output of rule `id`.
+    pub(super) fn set_output(
+        &mut self,
+        id: u64,
+        unit: u64,
+        enabled: bool,
+    ) -> Result<(), CauseEffectError> {
+        let rule = self
+            .get_rule_mut(id)
+            .ok_or(CauseEffectError::RuleNotFound(id))?;
+        if enabled {
+            rule.outputs.insert(unit);
+        } else {
+            rule.outputs.remove(&unit);
+        }
+        Ok(())
+    }
+
+    /// Returns the outputs to activate for the given set of active inputs.
+    ///
+    /// A rule fires when its active inputs satisfy its logic, and then activates each of its outputs that
+    /// `reaches(input, output)` allows for one of its active inputs.
+    pub fn evaluate(
+        &self,
+        active_inputs: &BTreeSet<u64>,
+        reaches: impl Fn(u64, u64) -> bool,
+    ) -> BTreeSet<u64> {
+        let mut effects = BTreeSet::new();
+        for rule in self
+            .rules
+            .iter()
+            .filter(|rule| rule.is_triggered(active_inputs))
+        {
+            let inputs: Vec<u64> = rule.inputs.intersection(active_inputs).copied().collect();
+            effects.extend(
+                rule.outputs
+                    .iter()
+                    .filter(|output| inputs.iter().any(|input| reaches(*input, **output))),
+            );
+        }
+        effects
+    }
+
+    /// Drops every reference to a unit that no longer exists. Rules themselves are kept.
+    pub fn forget_unit(&mut self, unit: u64) {
+        for rule in &mut self.rules {
+            rule.inputs.remove(&unit);
+            rule.outputs.remove(&unit);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.rules.clear();
+        self.next_id = 1;
+    }
+}
+
+impl Default for CauseEffectMatrix {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn active(ids: &[u64]) -> BTreeSet<u64> {
+        ids.iter().copied().collect()
+    }
+
+    fn everywhere(_input: u64, _output: u64) -> bool {
+        true
+    }
+
+    #[test]
+    fn test_add_rule_increments_id() {
+        let mut matrix = CauseEffectMatrix::new();
+
+        assert_eq!(matrix.add_rule("Rule 1", InputLogic::Any), Ok(1));
+        assert_eq!(matrix.add_rule("Rule 2", InputLogic::All), Ok(2));
+        assert_eq!(matrix.rules().len(), 2);
+    }
+
+    #[test]
+    fn test_any_logic_fires_on_single_input() {
+        let mut matrix = CauseEffectMatrix::new();
+        let rule = matrix.add_rule("Zone 2 alarm", InputLogic::Any).unwrap();
+        matrix.set_input(rule, 10, true).unwrap();
+        matrix.set_input(rule, 11, true).unwrap();
+        matrix.set_output(rule, 20, true).unwrap();
+        matrix.set_output(rule, 21, true).unwrap();
+
+        assert_eq!(
+            matrix.evaluate(&active(&[11]), everywhere),
+            active(&[20, 21])
+        );
+        assert!(matrix.evaluate(&active(&[12]), everywhere).is_empty());
+    }
+
+    #[test]
+    fn test_all_logic_needs_every_input() {
+        let mut matrix = CauseEffectMatrix::new();
+        let rule = matrix.add_rule("Both", InputLogic::All).unwrap();
+        matrix.set_input(rule, 10, true).unwrap();
+        matrix.set_input(rule, 11, true).unwrap();
+        matrix.set_output(rule, 20, true).unwrap();
+
+        assert!(matrix.evaluate(&active(&[10]), everywhere).is_empty());
+        assert_eq!(
+            matrix.evaluate(&active(&[10, 11]), everywhere),
+            active(&[20])
+        );
+    }
+
+    #[test]
+    fn test_all_logic_without_inputs_never_fires() {
+        let mut matrix = CauseEffectMatrix::new();
+        let rule = matrix.add_rule("Empty", InputLogic::All).unwrap();
+        matrix.set_output(rule, 20, true).unwrap();
+
+        assert!(matrix.evaluate(&active(&[10]), everywhere).is_empty());
+    }
+
+    #[test]
+    fn test_coincidence_logic_counts_inputs() {
+        let mut matrix = CauseEffectMatrix::new();
+        let rule = matrix
+            .add_rule("Two of three", InputLogic::Coincidence(2))
+            .unwrap();
+        for input in [10, 11, 12] {
+            matrix.set_input(rule, input, true).unwrap();
+        }
+        matrix.set_output(rule, 20, true).unwrap();
+
+        assert!(matrix.evaluate(&active(&[10]), everywhere).is_empty());
+        assert_eq!(
+            matrix.evaluate(&active(&[10, 12]), everywhere),
+            active(&[20])
+        );
+        assert_eq!(
+            matrix.evaluate(&active(&[10, 11, 12]), everywhere),
+            active(&[20])
+        );
+    }
+
+    #[test]
+    fn test_outputs_of_several_rules_are_merged() {
+        let mut matrix = CauseEffectMatrix::new();
+        let first = matrix.add_rule("First", InputLogic::Any).unwrap();
+        let second = matrix.add_rule("Second", InputLogic::Any).unwrap();
+        matrix.set_input(first, 10, true).unwrap();
+        matrix.set_output(first, 20, true).unwrap();
+        matrix.set_input(second, 10, true).unwrap();
+        matrix.set_output(second, 20, true).unwrap();
+        matrix.set_output(second, 21, true).unwrap();
+
+        assert_eq!(
+            matrix.evaluate(&active(&[10]), everywhere),
+            active(&[20, 21])
+        );
+    }
+
+    #[test]
+    fn test_outputs_must_be_reachable_from_an_active_input() {
+        let mut matrix = CauseEffectMatrix::new();
+        let rule = matrix.add_rule("Rule", InputLogic::Any).unwrap();
+        matrix.set_input(rule, 10, true).unwrap();
+        matrix.set_input(rule, 11, true).unwrap();
+        matrix.set_output(rule, 20, true).unwrap();
+        matrix.set_output(rule, 21, true).unwrap();
+        let reaches = |input, output| (input, output) != (10, 21);
+
+        assert_eq!(matrix.evaluate(&active(&[10]), reaches), active(&[20]));
+        assert_eq!(
+            matrix.evaluate(&active(&[10, 11]), reaches),
+            active(&[20, 21])
+        );
+    }
+
+    #[test]
+    fn test_add_rule_rejects_zero_coincidence() {
+        let mut matrix = CauseEffectMatrix::new();
+
+        assert_eq!(
+            matrix.add_rule("Rule", InputLogic::Coincidence(0)),
+            Err(CauseEffectError::InvalidCoincidence)
+        );
+        assert!(matrix.rules().is_empty());
+        assert_eq!(matrix.next_id(), 1);
+    }
+
+    #[test]
+    fn test_set_logic_rejects_zero_coincidence() {
+        let mut matrix = CauseEffectMatrix::new();
+        let rule = matrix.add_rule("Rule", InputLogic::Any).unwrap();
+
+        assert_eq!(
+            matrix.set_logic(rule, InputLogic::Coincidence(0)),
+            Err(CauseEffectError::InvalidCoincidence)
+        );
+        assert_eq!(
+            matrix.set_logic(99, InputLogic::All),
+            Err(CauseEffectError::RuleNotFound(99))
+        );
+        assert_eq!(matrix.set_logic(rule, InputLogic::Coincidence(3)), Ok(()));
+        assert_eq!(
+            matrix.get_rule(rule).unwrap().logic(),
+            InputLogic::Coincidence(3)
+        );
+    }
+
+    #[test]
+    fn test_set_input_false_removes_input() {
+        let mut matrix = CauseEffectMatrix::new();
+        let rule = matrix.add_rule("Rule", InputLogic::Any).unwrap();
+        matrix.set_input(rule, 10, true).unwrap();
+        matrix.set_input(rule, 10, false).unwrap();
+
+        assert!(matrix.get_rule(rule).unwrap().inputs().is_empty());
+    }
+
+    #[test]
+    fn test_forget_unit_removes_references() {
+        let mut matrix = CauseEffectMatrix::new();
+        let rule = matrix.add_rule("Rule", InputLogic::Any).unwrap();
+        matrix.set_input(rule, 10, true).unwrap();
+        matrix.set_output(rule, 10, true).unwrap();
+        matrix.set_output(rule, 20, true).unwrap();
+
+        matrix.forget_unit(10);
+
+        let rule = matrix.get_rule(rule).unwrap();
+        assert!(rule.inputs().is_empty());
+        assert_eq!(rule.outputs(), &active(&[20]));
+    }
+
+    #[test]
+    fn test_remove_rule() {
+        let mut matrix = CauseEffectMatrix::new();
+        let rule = matrix.add_rule("Rule", InputLogic::Any).unwrap();
+
+        assert!(matrix.remove_rule(rule));
+        assert!(!matrix.remove_rule(rule));
+        assert!(matrix.rules().is_empty());
+    }
+}
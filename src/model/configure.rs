@@ -0,0 +1,298 @@
+use std::collections::HashSet;
+
+use super::{AddressError, UnitKind, UnitStore};
+
+/// The changes needed to bring a store to the requested number of units of each kind.
+///
+/// Units that already exist keep their ids and names. Missing units are added with the next free "<Kind> <n>" name, and
+/// surplus units are taken from the most recently added units of that kind. Units below a removed unit are kept and move
+/// up to its parent. Added devices that sit on a loop get the first free loop address.
+///
+/// A plan for one panel only counts, adds and removes units of that panel; otherwise added units go to the main panel.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigurePlan {
+    additions: Vec<(UnitKind, String)>,
+    removals: Vec<u64>,
+    panel: Option<u16>,
+}
+
+impl ConfigurePlan {
+    /// Works out the plan for `counts` on `panel`, or on the whole store when `panel` is `None`. Kinds that are not listed
+    /// are left alone.
+    pub fn for_panel(store: &UnitStore, panel: Option<u16>, counts: &[(UnitKind, usize)]) -> Self {
+        let mut taken: HashSet<String> = store
+            .get_all_units()
+            .iter()
+            .map(|unit| unit.name().to_string())
+            .collect();
+        let mut plan = Self {
+            panel,
+            ..Self::default()
+        };
+
+        for (kind, requested) in counts {
+            let existing: Vec<u64> = store
+                .units_of_kind(*kind)
+                .filter(|unit| panel.is_none() || store.panel_of(unit.id()) == panel)
+                .map(|unit| unit.id())
+                .collect();
+
+            if existing.len() > *requested {
+                plan.removals
+                    .extend(existing[*requested..].iter().rev().copied());
+                continue;
+            }
+
+            let mut number = existing.len();
+            for _ in existing.len()..*requested {
+                let name = loop {
+                    number += 1;
+                    let candidate = format!("{} {}", kind, number);
+                    if !taken.contains(&candidate) {
+                        break candidate;
+                    }
+                };
+                taken.insert(name.clone());
+                plan.additions.push((*kind, name));
+            }
+        }
+
+        plan
+    }
+
+    /// Units that will be created, in the order they will be added.
+    pub fn additions(&self) -> &[(UnitKind, String)] {
+        &self.additions
+    }
+
+    /// Ids of the units that will be removed.
+    pub fn removals(&self) -> &[u64] {
+        &self.removals
+    }
+
+    /// The panel the plan is for, or `None` for the whole store.
+    pub fn panel(&self) -> Option<u16> {
+        self.panel
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.additions.is_empty() && self.removals.is_empty()
+    }
+
+    /// Units below the removed units, which move up to the parent of the removed unit instead of going with it.
+    pub fn lifted(&self, store: &UnitStore) -> Vec<u64> {
+        store.units_below(&self.removals)
+    }
+
+    /// Checks that the plan applies to `store` without changing it.
+    pub fn check(&self, store: &UnitStore) -> Result<(), AddressError> {
+        self.apply_to(&mut store.clone())
+    }
+
+    /// Applies the plan, removals first and then additions, or changes nothing if a new device cannot be addressed.
+    pub fn apply(&self, store: &mut UnitStore) -> Result<(), AddressError> {
+        let mut draft = store.clone();
+        self.apply_to(&mut draft)?;
+        *store = draft;
+        Ok(())
+    }
+
+    // May leave `store` half configured on error; callers hand in a copy
+    fn apply_to(&self, store: &mut UnitStore) -> Result<(), AddressError> {
+        for id in &self.removals {
+            let Some(parent) = store.get_unit(*id).and_then(|unit| unit.parent()) else {
+                continue;
+            };
+            // Only the surplus units go; whatever hangs below them stays, one level up
+            let children: Vec<u64> = store.children_of(*id).map(|child| child.id()).collect();
+            for child in children {
+                let _ = store.move_unit(child, parent);
+            }
+            store.remove_unit(*id);
+        }
+        for (kind, name) in &self.additions {
+            let id = store.add_unit(*kind, name);
+            if let Some(panel) = self.panel {
+                // A panel removed since the plan was made leaves the unit on the main panel
+                let _ = store.set_panel(id, panel);
+            }
+            if kind.is_addressable() {
+                store.auto_address(id)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_for_empty_store_adds_everything() {
+        let store = UnitStore::new();
+
+        let plan = ConfigurePlan::for_panel(
+            &store,
+            None,
+            &[(UnitKind::Detector, 2), (UnitKind::Output, 1)],
+        );
+
+        assert_eq!(
+            plan.additions(),
+            &[
+                (UnitKind::Detector, "Detector 1".to_string()),
+                (UnitKind::Detector, "Detector 2".to_string()),
+                (UnitKind::Output, "Output 1".to_string()),
+            ]
+        );
+        assert!(plan.removals().is_empty());
+    }
+
+    #[test]
+    fn test_growing_keeps_existing_units() {
+        let mut store = UnitStore::new();
+        ConfigurePlan::for_panel(&store, None, &[(UnitKind::Detector, 10)])
+            .apply(&mut store)
+            .unwrap();
+        store.get_unit_mut(3).unwrap().set_name("Kitchen");
+
+        let plan = ConfigurePlan::for_panel(&store, None, &[(UnitKind::Detector, 12)]);
+        plan.apply(&mut store).unwrap();
+
+        assert_eq!(store.count_of_kind(UnitKind::Detector), 12);
+        assert_eq!(store.get_unit(3).unwrap().name(), "Kitchen");
+        assert_eq!(store.get_unit(11).unwrap().name(), "Detector 11");
+        assert_eq!(store.get_unit(12).unwrap().name(), "Detector 12");
+    }
+
+    #[test]
+    fn test_shrinking_removes_most_recent_units() {
+        let mut store = UnitStore::new();
+        ConfigurePlan::for_panel(&store, None, &[(UnitKind::Output, 4)])
+            .apply(&mut store)
+            .unwrap();
+
+        let plan = ConfigurePlan::for_panel(&store, None, &[(UnitKind::Output, 2)]);
+
+        assert_eq!(plan.removals(), &[4, 3]);
+        assert!(plan.additions().is_empty());
+        plan.apply(&mut store).unwrap();
+        let names: Vec<&str> = store
+            .units_of_kind(UnitKind::Output)
+            .map(|unit| unit.name())
+            .collect();
+        assert_eq!(names, vec!["Output 1", "Output 2"]);
+    }
+
+    #[test]
+    fn test_shrinking_keeps_units_below_removed_units() {
+        let mut store = UnitStore::new();
+        ConfigurePlan::for_panel(&store, None, &[(UnitKind::Detector, 2)])
+            .apply(&mut store)
+            .unwrap();
+        let sounder = store
+            .add_child(2, UnitKind::FireAlarmDevice, "Base Sounder")
+            .unwrap();
+
+        let plan = ConfigurePlan::for_panel(&store, None, &[(UnitKind::Detector, 1)]);
+        assert_eq!(plan.lifted(&store), vec![sounder]);
+        plan.apply(&mut store).unwrap();
+
+        assert!(store.get_unit(2).is_none());
+        assert_eq!(store.get_unit(sounder).unwrap().parent(), Some(0));
+    }
+
+    #[test]
+    fn test_new_names_skip_names_in_use() {
+        let mut store = UnitStore::new();
+        store.add_unit(UnitKind::Detector, "Detector 2");
+
+        let plan = ConfigurePlan::for_panel(&store, None, &[(UnitKind::Detector, 3)]);
+
+        let names: Vec<&str> = plan
+            .additions()
+            .iter()
+            .map(|(_, name)| name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Detector 3", "Detector 4"]);
+    }
+
+    #[test]
+    fn test_unlisted_kinds_and_matching_counts_are_untouched() {
+        let mut store = UnitStore::new();
+        store.add_unit(UnitKind::AlarmZone, "Alarm Zone 1");
+        store.add_unit(UnitKind::Detector, "Detector 1");
+
+        let plan = ConfigurePlan::for_panel(&store, None, &[(UnitKind::Detector, 1)]);
+
+        assert!(plan.is_empty());
+    }
+
+    #[test]
+    fn test_apply_addresses_new_devices() {
+        let mut store = UnitStore::new();
+        store.add_loop(2).unwrap();
+
+        ConfigurePlan::for_panel(
+            &store,
+            None,
+            &[(UnitKind::Detector, 3), (UnitKind::AlarmZone, 1)],
+        )
+        .apply(&mut store)
+        .unwrap();
+
+        let addresses: Vec<String> = store
+            .units_of_kind(UnitKind::Detector)
+            .map(|unit| unit.address().unwrap().to_string())
+            .collect();
+        assert_eq!(addresses, vec!["L1.001", "L1.002", "L2.001"]);
+        assert_eq!(
+            store
+                .units_of_kind(UnitKind::AlarmZone)
+                .next()
+                .unwrap()
+                .address(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ids_are_not_reused_after_shrink_and_grow() {
+        let mut store = UnitStore::new();
+        ConfigurePlan::for_panel(&store, None, &[(UnitKind::Detector, 3)])
+            .apply(&mut store)
+            .unwrap();
+        ConfigurePlan::for_panel(&store, None, &[(UnitKind::Detector, 2)])
+            .apply(&mut store)
+            .unwrap();
+
+        ConfigurePlan::for_panel(&store, None, &[(UnitKind::Detector, 3)])
+            .apply(&mut store)
+            .unwrap();
+
+        assert!(store.get_unit(3).is_none());
+        assert_eq!(store.get_unit(4).unwrap().name(), "Detector 3");
+    }
+
+    #[test]
+    fn test_panel_plans_only_count_and_change_that_panel() {
+        let mut store = UnitStore::new();
+        let annex = store.add_panel("Annex").unwrap();
+        ConfigurePlan::for_panel(&store, None, &[(UnitKind::Detector, 2)])
+            .apply(&mut store)
+            .unwrap();
+
+        let plan = ConfigurePlan::for_panel(&store, Some(annex), &[(UnitKind::Detector, 1)]);
+        assert_eq!(
+            plan.additions(),
+            &[(UnitKind::Detector, "Detector 3".to_string())]
+        );
+        plan.apply(&mut store).unwrap();
+        assert_eq!(store.counts_on_panel(annex)[&UnitKind::Detector], 1);
+        assert_eq!(store.counts_on_panel(1)[&UnitKind::Detector], 2);
+
+        let plan = ConfigurePlan::for_panel(&store, Some(annex), &[(UnitKind::Detector, 0)]);
+        assert_eq!(plan.removals(), &[3]);
+    }
+}
@@ -0,0 +1,424 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The longest building, floor or room name, in characters.
+pub const MAX_LOCATION_PART_LENGTH: usize = 64;
+/// The longest description, in characters.
+pub const MAX_DESCRIPTION_LENGTH: usize = 500;
+/// The longest serial number, in characters.
+pub const MAX_SERIAL_NUMBER_LENGTH: usize = 32;
+/// The longest attribute key, in characters.
+pub const MAX_ATTRIBUTE_KEY_LENGTH: usize = 32;
+/// The longest attribute value, in characters.
+pub const MAX_ATTRIBUTE_VALUE_LENGTH: usize = 128;
+/// The most attributes a unit can have.
+pub const MAX_ATTRIBUTES: usize = 32;
+
+// Separates the parts of a location in its text form, e.g. in CSV files
+const LOCATION_SEPARATOR: char = '/';
+// Separate attributes and their keys and values in their text form
+const ATTRIBUTE_SEPARATOR: char = ';';
+const KEY_VALUE_SEPARATOR: char = '=';
+
+/// Free-form key/value pairs recorded for a unit, e.g. "Manufacturer" = "Apollo".
+pub type Attributes = BTreeMap<String, String>;
+
+/// Reasons a commissioning detail of a unit is refused.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DetailError {
+    TooLong {
+        field: &'static str,
+        max: usize,
+    },
+    ForbiddenCharacter {
+        field: &'static str,
+        character: char,
+    },
+    InvalidDate(String),
+    InvalidLocation(String),
+    EmptyAttributeKey,
+    TooManyAttributes {
+        max: usize,
+    },
+}
+
+impl fmt::Display for DetailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DetailError::TooLong { field, max } => {
+                write!(f, "{} must be at most {} characters", field, max)
+            }
+            DetailError::ForbiddenCharacter { field, character } => {
+                write!(f, "{} must not contain {:?}", field, character)
+            }
+            DetailError::InvalidDate(text) => {
+                write!(f, "\"{}\" is not a date in the form YYYY-MM-DD", text)
+            }
+            DetailError::InvalidLocation(text) => {
+                write!(
+                    f,
+                    "\"{}\" is not a location in the form building / floor / room",
+                    text
+                )
+            }
+            DetailError::EmptyAttributeKey => write!(f, "attribute name must not be empty"),
+            DetailError::TooManyAttributes { max } => {
+                write!(f, "a unit can have at most {} attributes", max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DetailError {}
+
+/// Where a unit is installed. Any part may be empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Location {
+    pub building: String,
+    pub floor: String,
+    pub room: String,
+}
+
+impl Location {
+    /// A location from its parts, with surrounding whitespace removed.
+    pub fn new(building: &str, floor: &str, room: &str) -> Self {
+        Self {
+            building: building.trim().to_string(),
+            floor: floor.trim().to_string(),
+            room: room.trim().to_string(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.building.is_empty() && self.floor.is_empty() && self.room.is_empty()
+    }
+
+    /// Checks each part fits in [`MAX_LOCATION_PART_LENGTH`] on one line, without the `/` that separates the parts in
+    /// text form.
+    pub fn validate(&self) -> Result<(), DetailError> {
+        for (field, part) in [
+            ("building", &self.building),
+            ("floor", &self.floor),
+            ("room", &self.room),
+        ] {
+            check_text(field, part, MAX_LOCATION_PART_LENGTH, &[LOCATION_SEPARATOR])?;
+        }
+        Ok(())
+    }
+}
+
+/// "building / floor / room", or nothing for an empty location.
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return Ok(());
+        }
+        write!(f, "{} / {} / {}", self.building, self.floor, self.room)
+    }
+}
+
+/// Parses the text form written by `Display`. Empty text is an empty location.
+impl FromStr for Location {
+    type Err = DetailError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        if text.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        let parts: Vec<&str> = text.split(LOCATION_SEPARATOR).collect();
+        let [building, floor, room] = parts.as_slice() else {
+            return Err(DetailError::InvalidLocation(text.to_string()));
+        };
+        let location = Self::new(building, floor, room);
+        location.validate()?;
+        Ok(location)
+    }
+}
+
+/// A calendar date, stored and shown as YYYY-MM-DD.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InstallDate {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl InstallDate {
+    /// The date, if it exists. Years before 1900 are refused as typing mistakes.
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Self> {
+        let days_in_month = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if year.is_multiple_of(4)
+                && (!year.is_multiple_of(100) || year.is_multiple_of(400)) =>
+            {
+                29
+            }
+            2 => 28,
+            _ => return None,
+        };
+        ((1900..=9999).contains(&year) && (1..=days_in_month).contains(&day)).then_some(Self {
+            year,
+            month,
+            day,
+        })
+    }
+
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+}
+
+impl fmt::Display for InstallDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for InstallDate {
+    type Err = DetailError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || DetailError::InvalidDate(text.to_string());
+        let trimmed = text.trim();
+        let parts: Vec<&str> = trimmed.split('-').collect();
+        let [year, month, day] = parts.as_slice() else {
+            return Err(invalid());
+        };
+        if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return Err(invalid());
+        }
+        let (Ok(year), Ok(month), Ok(day)) = (year.parse(), month.parse(), day.parse()) else {
+            return Err(invalid());
+        };
+        Self::new(year, month, day).ok_or_else(invalid)
+    }
+}
+
+impl Serialize for InstallDate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for InstallDate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Checks a description fits in [`MAX_DESCRIPTION_LENGTH`]. Line breaks are allowed, other control characters are not.
+pub fn check_description(description: &str) -> Result<(), DetailError> {
+    if description.chars().count() > MAX_DESCRIPTION_LENGTH {
+        return Err(DetailError::TooLong {
+            field: "description",
+            max: MAX_DESCRIPTION_LENGTH,
+        });
+    }
+    match description.chars().find(|c| c.is_control() && *c != '\n') {
+        Some(character) => Err(DetailError::ForbiddenCharacter {
+            field: "description",
+            character,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Checks a serial number fits in [`MAX_SERIAL_NUMBER_LENGTH`] and uses only letters, digits, `-`, `/`, `.` and `_`.
+pub fn check_serial_number(serial_number: &str) -> Result<(), DetailError> {
+    if serial_number.chars().count() > MAX_SERIAL_NUMBER_LENGTH {
+        return Err(DetailError::TooLong {
+            field: "serial number",
+            max: MAX_SERIAL_NUMBER_LENGTH,
+        });
+    }
+    match serial_number
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && !matches!(c, '-' | '/' | '.' | '_'))
+    {
+        Some(character) => Err(DetailError::ForbiddenCharacter {
+            field: "serial number",
+            character,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Checks `key` = `value` could be added to `attributes`, replacing any value the key already has.
+///
+/// Keys must not be empty, and neither may contain the `;` or (for keys) `=` that separate attributes in text form.
+pub fn check_attribute(attributes: &Attributes, key: &str, value: &str) -> Result<(), DetailError> {
+    if key.is_empty() {
+        return Err(DetailError::EmptyAttributeKey);
+    }
+    check_text(
+        "attribute name",
+        key,
+        MAX_ATTRIBUTE_KEY_LENGTH,
+        &[ATTRIBUTE_SEPARATOR, KEY_VALUE_SEPARATOR],
+    )?;
+    check_text(
+        "attribute value",
+        value,
+        MAX_ATTRIBUTE_VALUE_LENGTH,
+        &[ATTRIBUTE_SEPARATOR],
+    )?;
+    if !attributes.contains_key(key) && attributes.len() >= MAX_ATTRIBUTES {
+        return Err(DetailError::TooManyAttributes {
+            max: MAX_ATTRIBUTES,
+        });
+    }
+    Ok(())
+}
+
+/// Writes attributes as `key=value; key=value`.
+pub fn format_attributes(attributes: &Attributes) -> String {
+    attributes
+        .iter()
+        .map(|(key, value)| format!("{}{}{}", key, KEY_VALUE_SEPARATOR, value))
+        .collect::<Vec<_>>()
+        .join(&format!("{} ", ATTRIBUTE_SEPARATOR))
+}
+
+/// Parses the text form written by [`format_attributes`], checking every attribute.
+pub fn parse_attributes(text: &str) -> Result<Attributes, DetailError> {
+    let mut attributes = Attributes::new();
+    for pair in text.split(ATTRIBUTE_SEPARATOR) {
+        if pair.trim().is_empty() {
+            continue;
+        }
+        let (key, value) = pair.split_once(KEY_VALUE_SEPARATOR).unwrap_or((pair, ""));
+        let (key, value) = (key.trim(), value.trim());
+        check_attribute(&attributes, key, value)?;
+        attributes.insert(key.to_string(), value.to_string());
+    }
+    Ok(attributes)
+}
+
+// Checks a single-line text field against a length limit and the characters it may not contain
+fn check_text(
+    field: &'static str,
+    text: &str,
+    max: usize,
+    forbidden: &[char],
+) -> Result<(), DetailError> {
+    if text.chars().count() > max {
+        return Err(DetailError::TooLong { field, max });
+    }
+    match text
+        .chars()
+        .find(|c| c.is_control() || forbidden.contains(c))
+    {
+        Some(character) => Err(DetailError::ForbiddenCharacter { field, character }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_install_dates() {
+        let date: InstallDate = "2024-02-29".parse().unwrap();
+        assert_eq!((date.year(), date.month(), date.day()), (2024, 2, 29));
+        assert_eq!(date.to_string(), "2024-02-29");
+        assert_eq!(serde_json::to_string(&date).unwrap(), "\"2024-02-29\"");
+
+        for text in [
+            "2023-02-29",
+            "2024-13-01",
+            "1899-12-31",
+            "2024-1-1",
+            "24-01-01",
+            "soon",
+        ] {
+            assert_eq!(
+                text.parse::<InstallDate>(),
+                Err(DetailError::InvalidDate(text.to_string())),
+                "{}",
+                text
+            );
+        }
+        assert!(serde_json::from_str::<InstallDate>("\"2024-04-31\"").is_err());
+    }
+
+    #[test]
+    fn test_location_text_form() {
+        let location = Location::new(" Block A ", "2", "");
+        assert_eq!(location.to_string(), "Block A / 2 / ");
+        assert_eq!("Block A / 2 / ".parse(), Ok(location));
+        assert_eq!("".parse(), Ok(Location::default()));
+        assert_eq!(
+            "Block A / 2".parse::<Location>(),
+            Err(DetailError::InvalidLocation("Block A / 2".to_string()))
+        );
+        assert!(matches!(
+            Location::new("x".repeat(65).as_str(), "", "").validate(),
+            Err(DetailError::TooLong {
+                field: "building",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_serial_numbers_and_descriptions() {
+        assert_eq!(check_serial_number("AB-1234/5.6_7"), Ok(()));
+        assert_eq!(
+            check_serial_number("AB 12"),
+            Err(DetailError::ForbiddenCharacter {
+                field: "serial number",
+                character: ' '
+            })
+        );
+        assert_eq!(
+            check_description("Above the door\nnext to the lift"),
+            Ok(())
+        );
+        assert!(check_description("\u{7}").is_err());
+        assert!(check_description(&"x".repeat(MAX_DESCRIPTION_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_attributes_round_trip_and_limits() {
+        let attributes = parse_attributes("Manufacturer = Apollo; Sensitivity=High;;").unwrap();
+        assert_eq!(attributes.len(), 2);
+        assert_eq!(
+            format_attributes(&attributes),
+            "Manufacturer=Apollo; Sensitivity=High"
+        );
+        assert_eq!(
+            parse_attributes(&format_attributes(&attributes)),
+            Ok(attributes.clone())
+        );
+
+        assert_eq!(
+            check_attribute(&attributes, "", "x"),
+            Err(DetailError::EmptyAttributeKey)
+        );
+        assert!(check_attribute(&attributes, "a=b", "x").is_err());
+        let full: Attributes = (0..MAX_ATTRIBUTES)
+            .map(|n| (n.to_string(), String::new()))
+            .collect();
+        assert_eq!(
+            check_attribute(&full, "new", ""),
+            Err(DetailError::TooManyAttributes {
+                max: MAX_ATTRIBUTES
+            })
+        );
+        assert_eq!(check_attribute(&full, "0", "replaced"), Ok(()));
+    }
+}
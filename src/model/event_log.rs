@@ -0,0 +1,602 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::{UnitKind, UnitState, UnitStore};
+
+/// A point in time in milliseconds since the Unix epoch, shown in UTC.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize,
+)]
+pub struct Timestamp(pub u64);
+
+impl Timestamp {
+    /// The current time. In the browser this is the clock of the page, as wasm has no system time.
+    pub fn now() -> Self {
+        #[cfg(target_arch = "wasm32")]
+        let millis = js_sys::Date::now() as u64;
+        #[cfg(not(target_arch = "wasm32"))]
+        let millis = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0);
+        Self(millis)
+    }
+
+    /// The timestamp of a UTC date and time, or `None` for an impossible one.
+    pub fn from_utc(
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+    ) -> Option<Self> {
+        super::InstallDate::new(year, month, day)?;
+        if hour > 23 || minute > 59 || second > 59 {
+            return None;
+        }
+        let days = days_from_civil(year as i64, month as i64, day as i64);
+        let seconds = days * 86_400 + hour as i64 * 3600 + minute as i64 * 60 + second as i64;
+        u64::try_from(seconds)
+            .ok()
+            .map(|seconds| Self(seconds * 1000))
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.0 / 1000;
+        let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+        let time = seconds % 86_400;
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            year,
+            month,
+            day,
+            time / 3600,
+            time % 3600 / 60,
+            time % 60
+        )
+    }
+}
+
+/// Parses "YYYY-MM-DD HH:MM:SS" as written by Display. A `T` may separate date and time, and the seconds or the whole
+/// time may be left out, as in the value of an HTML `datetime-local` or `date` input.
+impl FromStr for Timestamp {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("\"{}\" is not a date and time", text);
+        let text = text.trim();
+        let (date, time) = text
+            .split_once(['T', ' '])
+            .map_or((text, None), |(date, time)| (date, Some(time)));
+        let date: super::InstallDate = date.parse().map_err(|_| invalid())?;
+        let mut parts = [0u8; 3];
+        if let Some(time) = time {
+            let fields: Vec<&str> = time.split(':').collect();
+            if !(2..=3).contains(&fields.len()) {
+                return Err(invalid());
+            }
+            for (part, field) in parts.iter_mut().zip(fields) {
+                *part = field.parse().map_err(|_| invalid())?;
+            }
+        }
+        Self::from_utc(
+            date.year(),
+            date.month(),
+            date.day(),
+            parts[0],
+            parts[1],
+            parts[2],
+        )
+        .ok_or_else(invalid)
+    }
+}
+
+// Days since 1970-01-01 of a date in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// The broad type of a [`LogEvent`], used to filter the log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum LogCategory {
+    /// A change to the configuration.
+    Config,
+    /// A unit changing its runtime state.
+    State,
+    /// An operator acknowledging, silencing or resetting the panel.
+    Panel,
+}
+
+impl LogCategory {
+    pub const ALL: [LogCategory; 3] = [LogCategory::Config, LogCategory::State, LogCategory::Panel];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LogCategory::Config => "Config",
+            LogCategory::State => "State",
+            LogCategory::Panel => "Panel",
+        }
+    }
+}
+
+impl fmt::Display for LogCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+/// What happened.
+///
+/// Names are captured when the event happens so the log still reads correctly after a rename or removal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LogEvent {
+    UnitAdded {
+        kind: UnitKind,
+        name: String,
+    },
+    UnitRemoved {
+        name: String,
+    },
+    UnitRenamed {
+        from: String,
+        to: String,
+    },
+    Cleared,
+    /// Any other configuration change, including undo and redo, described as in the history list.
+    ConfigChanged {
+        description: String,
+    },
+    StateChanged {
+        from: UnitState,
+        to: UnitState,
+    },
+    /// An alarm from a unit in test, which does not raise a fire.
+    TestActivation,
+    Acknowledged,
+    Silenced,
+    Reset,
+}
+
+impl LogEvent {
+    pub fn category(&self) -> LogCategory {
+        match self {
+            LogEvent::UnitAdded { .. }
+            | LogEvent::UnitRemoved { .. }
+            | LogEvent::UnitRenamed { .. }
+            | LogEvent::Cleared
+            | LogEvent::ConfigChanged { .. } => LogCategory::Config,
+            LogEvent::StateChanged { .. } | LogEvent::TestActivation => LogCategory::State,
+            LogEvent::Acknowledged | LogEvent::Silenced | LogEvent::Reset => LogCategory::Panel,
+        }
+    }
+}
+
+impl fmt::Display for LogEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogEvent::UnitAdded { kind, name } => write!(f, "Added {} {}", kind, name),
+            LogEvent::UnitRemoved { name } => write!(f, "Removed {}", name),
+            LogEvent::UnitRenamed { from, to } => write!(f, "Renamed {} to {}", from, to),
+            LogEvent::Cleared => write!(f, "Cleared the configuration"),
+            LogEvent::ConfigChanged { description } => write!(f, "{}", description),
+            LogEvent::StateChanged { from, to } => write!(f, "{} → {}", from, to),
+            LogEvent::TestActivation => write!(f, "Test activation"),
+            LogEvent::Acknowledged => write!(f, "Acknowledged"),
+            LogEvent::Silenced => write!(f, "Silenced"),
+            LogEvent::Reset => write!(f, "Reset"),
+        }
+    }
+}
+
+/// An event waiting to be written to the log, as queued by [`History`](super::History) and
+/// [`PanelStatus`](super::PanelStatus).
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogRecord {
+    pub unit: Option<u64>,
+    pub event: LogEvent,
+}
+
+impl LogRecord {
+    pub fn new(unit: Option<u64>, event: LogEvent) -> Self {
+        Self { unit, event }
+    }
+}
+
+/// One line of the event log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Assigned by the server when the entry is saved, counting up from 1 without gaps. Entries not saved yet are numbered
+    /// after the last entry the client has.
+    pub sequence: u64,
+    pub timestamp: Timestamp,
+    pub operator: String,
+    pub unit: Option<u64>,
+    /// The name of the unit when the entry was written.
+    pub unit_name: Option<String>,
+    pub event: LogEvent,
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}] ", self.timestamp, self.operator)?;
+        if let Some(name) = &self.unit_name {
+            write!(f, "{}: ", name)?;
+        }
+        write!(f, "{}", self.event)
+    }
+}
+
+/// Which entries of the log to show. Every part left as `None` matches all entries.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogFilter {
+    pub category: Option<LogCategory>,
+    pub unit: Option<u64>,
+    /// Entries at or after this time.
+    pub from: Option<Timestamp>,
+    /// Entries before this time.
+    pub until: Option<Timestamp>,
+}
+
+impl LogFilter {
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        self.category
+            .is_none_or(|category| entry.event.category() == category)
+            && self.unit.is_none_or(|unit| entry.unit == Some(unit))
+            && self.from.is_none_or(|from| entry.timestamp >= from)
+            && self.until.is_none_or(|until| entry.timestamp < until)
+    }
+}
+
+/// The operator recorded until someone logs in.
+pub const DEFAULT_OPERATOR: &str = "Operator";
+
+/// The append-only history of configuration changes, state changes and panel operations.
+///
+/// Entries are never removed. The log remembers how many of them have been saved to the server so only new ones are
+/// sent; saved entries take the sequence numbers the server gave them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventLog {
+    entries: Vec<LogEntry>,
+    operator: String,
+    // How many entries the server has; None until the saved log has been restored
+    synced: Option<usize>,
+}
+
+impl EventLog {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            operator: DEFAULT_OPERATOR.to_string(),
+            synced: None,
+        }
+    }
+
+    pub fn entries(&self) -> &[LogEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Who is recorded as the operator of new entries.
+    pub fn operator(&self) -> &str {
+        &self.operator
+    }
+
+    pub fn set_operator(&mut self, operator: &str) {
+        self.operator = operator.to_string();
+    }
+
+    /// Writes `records` at `timestamp`, taking unit names from `store`.
+    pub fn append(
+        &mut self,
+        store: &UnitStore,
+        timestamp: Timestamp,
+        records: impl IntoIterator<Item = LogRecord>,
+    ) {
+        for record in records {
+            let unit_name = match &record.event {
+                LogEvent::UnitRemoved { name } => Some(name.clone()),
+                _ => record
+                    .unit
+                    .and_then(|id| store.get_unit(id))
+                    .map(|unit| unit.name().to_string()),
+            };
+            self.entries.push(LogEntry {
+                sequence: self.next_sequence(),
+                timestamp,
+                operator: self.operator.clone(),
+                unit: record.unit,
+                unit_name,
+                event: record.event,
+            });
+        }
+    }
+
+    /// Puts the entries saved on the server in front of the ones written since the app started, which are renumbered to
+    /// follow them.
+    pub fn restore(&mut self, saved: Vec<LogEntry>) {
+        let written = std::mem::replace(&mut self.entries, saved);
+        self.synced = Some(self.entries.len());
+        self.append_unsynced(written);
+    }
+
+    /// Whether the saved log has been restored, so new entries can be sent.
+    pub fn is_restored(&self) -> bool {
+        self.synced.is_some()
+    }
+
+    /// Entries not yet saved to the server. Empty until the saved log has been restored, so nothing is sent twice.
+    pub fn unsynced(&self) -> &[LogEntry] {
+        match self.synced {
+            Some(synced) => &self.entries[synced.min(self.entries.len())..],
+            None => &[],
+        }
+    }
+
+    /// Replaces the first unsynced entries with `stored`, the same entries as the server saved and numbered them. Entries
+    /// written since they were sent are renumbered to follow them.
+    pub fn mark_synced(&mut self, stored: Vec<LogEntry>) {
+        let Some(synced) = self.synced else {
+            return;
+        };
+        let sent = stored.len().min(self.entries.len() - synced);
+        let written = self.entries.split_off(synced + sent);
+        self.entries.truncate(synced);
+        self.entries.extend(stored);
+        self.synced = Some(self.entries.len());
+        self.append_unsynced(written);
+    }
+
+    /// The entries `filter` matches, oldest first.
+    pub fn filter<'a>(&'a self, filter: &'a LogFilter) -> impl Iterator<Item = &'a LogEntry> {
+        self.entries.iter().filter(|entry| filter.matches(entry))
+    }
+
+    fn next_sequence(&self) -> u64 {
+        self.entries.last().map_or(1, |entry| entry.sequence + 1)
+    }
+
+    fn append_unsynced(&mut self, entries: Vec<LogEntry>) {
+        for mut entry in entries {
+            entry.sequence = self.next_sequence();
+            self.entries.push(entry);
+        }
+    }
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The columns written by [`log_to_csv`], in order.
+pub const LOG_CSV_COLUMNS: [&str; 7] = [
+    "sequence",
+    "time",
+    "operator",
+    "type",
+    "unit",
+    "unit_name",
+    "event",
+];
+
+/// Writes `entries` as CSV, one row per entry, with times in UTC.
+pub fn log_to_csv<'a>(entries: impl IntoIterator<Item = &'a LogEntry>) -> String {
+    let quote = |field: &str| {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
+    };
+    let mut csv = LOG_CSV_COLUMNS.join(",");
+    csv.push('\n');
+    for entry in entries {
+        let fields = [
+            entry.sequence.to_string(),
+            entry.timestamp.to_string(),
+            entry.operator.clone(),
+            entry.event.category().to_string(),
+            entry.unit.map(|id| id.to_string()).unwrap_or_default(),
+            entry.unit_name.clone().unwrap_or_default(),
+            entry.event.to_string(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| quote(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Writes `entries` as a pretty-printed JSON array of [`LogEntry`].
+pub fn log_to_json<'a>(entries: impl IntoIterator<Item = &'a LogEntry>) -> String {
+    let entries: Vec<&LogEntry> = entries.into_iter().collect();
+    serde_json::to_string_pretty(&entries).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::UnitKind;
+
+    #[test]
+    fn test_timestamps_format_and_parse_in_utc() {
+        assert_eq!(Timestamp(0).to_string(), "1970-01-01 00:00:00");
+        let leap_day = Timestamp::from_utc(2024, 2, 29, 23, 59, 58).unwrap();
+        assert_eq!(leap_day.to_string(), "2024-02-29 23:59:58");
+        assert_eq!("2024-02-29 23:59:58".parse(), Ok(leap_day));
+        assert_eq!(
+            "2024-02-29T23:59".parse::<Timestamp>().unwrap().to_string(),
+            "2024-02-29 23:59:00"
+        );
+        assert_eq!(
+            "2024-03-01".parse::<Timestamp>().unwrap().to_string(),
+            "2024-03-01 00:00:00"
+        );
+        assert!("2023-02-29".parse::<Timestamp>().is_err());
+        assert!("2024-01-01 24:00".parse::<Timestamp>().is_err());
+        assert!("2024-01-01 10".parse::<Timestamp>().is_err());
+    }
+
+    #[test]
+    fn test_append_filter_and_export() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector, east");
+        let mut log = EventLog::new();
+        log.set_operator("Kari");
+
+        log.append(
+            &store,
+            Timestamp(1_000),
+            [LogRecord::new(
+                Some(det),
+                LogEvent::UnitRenamed {
+                    from: "Detector 1".to_string(),
+                    to: "Detector, east".to_string(),
+                },
+            )],
+        );
+        log.append(
+            &store,
+            Timestamp(61_000),
+            [
+                LogRecord::new(
+                    Some(det),
+                    LogEvent::StateChanged {
+                        from: UnitState::Normal,
+                        to: UnitState::Fire,
+                    },
+                ),
+                LogRecord::new(None, LogEvent::Acknowledged),
+            ],
+        );
+        let sequences: Vec<u64> = log.entries().iter().map(|entry| entry.sequence).collect();
+        assert_eq!(sequences, vec![1, 2, 3]);
+
+        let by_unit = LogFilter {
+            unit: Some(det),
+            ..Default::default()
+        };
+        assert_eq!(log.filter(&by_unit).count(), 2);
+        let later_state = LogFilter {
+            category: Some(LogCategory::State),
+            from: Some(Timestamp(60_000)),
+            until: Some(Timestamp(61_001)),
+            ..Default::default()
+        };
+        let found: Vec<&LogEntry> = log.filter(&later_state).collect();
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].to_string(),
+            "1970-01-01 00:01:01 [Kari] Detector, east: Normal → Fire"
+        );
+
+        assert_eq!(
+            log_to_csv(log.entries()),
+            "sequence,time,operator,type,unit,unit_name,event\n\
+             1,1970-01-01 00:00:01,Kari,Config,1,\"Detector, east\",\"Renamed Detector 1 to Detector, east\"\n\
+             2,1970-01-01 00:01:01,Kari,State,1,\"Detector, east\",Normal → Fire\n\
+             3,1970-01-01 00:01:01,Kari,Panel,,,Acknowledged\n"
+        );
+        let json: Vec<LogEntry> = serde_json::from_str(&log_to_json(log.entries())).unwrap();
+        assert_eq!(json, log.entries());
+    }
+
+    #[test]
+    fn test_restore_puts_saved_entries_first() {
+        let store = UnitStore::new();
+        let mut log = EventLog::new();
+        log.append(
+            &store,
+            Timestamp(5_000),
+            [LogRecord::new(None, LogEvent::Reset)],
+        );
+        // Nothing is sent before the saved log is known
+        assert!(log.unsynced().is_empty());
+
+        let mut saved = EventLog::new();
+        saved.append(
+            &store,
+            Timestamp(1_000),
+            [
+                LogRecord::new(None, LogEvent::Cleared),
+                LogRecord::new(None, LogEvent::Silenced),
+            ],
+        );
+        log.restore(saved.entries().to_vec());
+
+        assert_eq!(log.len(), 3);
+        assert_eq!(log.entries()[2].sequence, 3);
+        assert_eq!(log.entries()[2].event, LogEvent::Reset);
+        assert_eq!(log.unsynced().len(), 1);
+        assert!(log.is_restored());
+    }
+
+    #[test]
+    fn test_mark_synced_takes_the_numbers_of_the_server() {
+        let store = UnitStore::new();
+        let mut log = EventLog::new();
+        log.restore(Vec::new());
+        log.append(
+            &store,
+            Timestamp(1_000),
+            [
+                LogRecord::new(None, LogEvent::Acknowledged),
+                LogRecord::new(None, LogEvent::Silenced),
+            ],
+        );
+        let mut sent = log.unsynced().to_vec();
+        // Another client saved entries 1 to 4 in the meantime
+        for (entry, sequence) in sent.iter_mut().zip(5..) {
+            entry.sequence = sequence;
+        }
+        log.append(
+            &store,
+            Timestamp(2_000),
+            [LogRecord::new(None, LogEvent::Reset)],
+        );
+
+        log.mark_synced(sent);
+
+        let sequences: Vec<u64> = log.entries().iter().map(|entry| entry.sequence).collect();
+        assert_eq!(sequences, vec![5, 6, 7]);
+        assert_eq!(log.unsynced().len(), 1);
+        assert_eq!(log.unsynced()[0].event, LogEvent::Reset);
+    }
+}
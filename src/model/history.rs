@@ -0,0 +1,1264 @@
+use std::collections::VecDeque;
+
+use super::{
+    BulkEdit, ConfigurePlan, CsvImport, InputLogic, InstallDate, Location, LogEvent, LogRecord,
+    LoopAddress, Rule, Unit, UnitKind, UnitProperties, UnitStore, ZoningThresholds,
+};
+
+/// The default number of commands kept for undo.
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// The most commands kept for undo that need a copy of the whole store to be undone. Older commands are dropped
+/// sooner, since copies of large configurations are big.
+const MAX_SNAPSHOTS: usize = 10;
+
+/// A mutation of the [`UnitStore`] that can be undone and redone.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Adds a unit of `kind` under `parent`, which is the Root unit for a unit of its own.
+    AddUnit {
+        parent: u64,
+        kind: UnitKind,
+        name: String,
+    },
+    RemoveUnit {
+        id: u64,
+    },
+    /// Moves a unit, with its subtree, under another unit.
+    MoveUnit {
+        id: u64,
+        parent: u64,
+    },
+    SetName {
+        id: u64,
+        name: String,
+    },
+    SetProperties {
+        id: u64,
+        properties: UnitProperties,
+    },
+    Clear,
+    Configure {
+        plan: ConfigurePlan,
+    },
+    AddLoop {
+        capacity: u16,
+    },
+    RemoveLoop {
+        number: u16,
+    },
+    SetLoopCapacity {
+        number: u16,
+        capacity: u16,
+    },
+    ReaddressLoop {
+        number: u16,
+    },
+    SetAddress {
+        id: u64,
+        address: Option<LoopAddress>,
+    },
+    AutoAddress {
+        id: u64,
+    },
+    SetIsolator {
+        id: u64,
+        isolator: bool,
+    },
+    SetThresholds {
+        thresholds: ZoningThresholds,
+    },
+    ImportCsv {
+        import: CsvImport,
+    },
+    Bulk {
+        edit: BulkEdit,
+    },
+    SetLocation {
+        id: u64,
+        location: Location,
+    },
+    SetDescription {
+        id: u64,
+        description: String,
+    },
+    SetInstallDate {
+        id: u64,
+        install_date: Option<InstallDate>,
+    },
+    SetSerialNumber {
+        id: u64,
+        serial_number: String,
+    },
+    /// Sets an attribute, or removes it when `value` is `None`.
+    SetAttribute {
+        id: u64,
+        key: String,
+        value: Option<String>,
+    },
+    AddPanel {
+        name: String,
+    },
+    RenamePanel {
+        number: u16,
+        name: String,
+    },
+    RemovePanel {
+        number: u16,
+    },
+    AddNetworkLink {
+        a: u16,
+        b: u16,
+    },
+    RemoveNetworkLink {
+        a: u16,
+        b: u16,
+    },
+    AssignZone {
+        member: u64,
+        zone: u64,
+    },
+    UnassignZone {
+        member: u64,
+    },
+    AddRule {
+        name: String,
+        logic: InputLogic,
+    },
+    RenameRule {
+        id: u64,
+        name: String,
+    },
+    SetRuleLogic {
+        id: u64,
+        logic: InputLogic,
+    },
+    /// Adds `unit` to the inputs of `rule`, or removes it when `enabled` is false.
+    SetRuleInput {
+        rule: u64,
+        unit: u64,
+        enabled: bool,
+    },
+    /// Adds `unit` to the outputs of `rule`, or removes it when `enabled` is false.
+    SetRuleOutput {
+        rule: u64,
+        unit: u64,
+        enabled: bool,
+    },
+    RemoveRule {
+        id: u64,
+    },
+}
+
+impl Command {
+    /// Applies the command. Returns false, leaving the store untouched, if it does not apply.
+    fn apply(&self, store: &mut UnitStore) -> bool {
+        match self {
+            Command::RemoveUnit { id } => store.remove_unit(*id),
+            Command::MoveUnit { id, parent } => store.move_unit(*id, *parent).is_ok(),
+            Command::AddUnit { parent, kind, name } => {
+                UnitKind::CONFIGURABLE.contains(kind)
+                    && store.check_new_name(*kind, name).is_ok()
+                    && store.add_child(*parent, *kind, name).is_some()
+            }
+            Command::SetName { id, name } => {
+                if store.check_name(*id, name).is_err() {
+                    return false;
+                }
+                match store.get_unit_mut(*id) {
+                    Some(unit) => {
+                        unit.set_name(name);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Command::SetProperties { id, properties } => match store.get_unit_mut(*id) {
+                Some(unit) => unit.set_properties(*properties).is_ok(),
+                None => false,
+            },
+            Command::Clear => {
+                store.clear();
+                true
+            }
+            Command::Configure { plan } => plan.apply(store).is_ok(),
+            Command::AddLoop { capacity } => store.add_loop(*capacity).is_ok(),
+            Command::RemoveLoop { number } => store.remove_loop(*number),
+            Command::SetLoopCapacity { number, capacity } => {
+                store.set_loop_capacity(*number, *capacity).is_ok()
+            }
+            Command::ReaddressLoop { number } => store.readdress_loop(*number).is_ok(),
+            Command::SetAddress { id, address } => store.set_address(*id, *address).is_ok(),
+            Command::AutoAddress { id } => store.auto_address(*id).is_ok(),
+            Command::SetIsolator { id, isolator } => match store.get_unit_mut(*id) {
+                Some(unit) if unit.kind().is_addressable() => {
+                    unit.set_isolator(*isolator);
+                    true
+                }
+                _ => false,
+            },
+            Command::SetThresholds { thresholds } => {
+                store.set_thresholds(thresholds.clone());
+                true
+            }
+            Command::ImportCsv { import } => import.apply(store),
+            Command::Bulk { edit } => edit.apply(store).is_ok(),
+            Command::SetLocation { id, location } => store
+                .get_unit_mut(*id)
+                .is_some_and(|unit| unit.set_location(location.clone()).is_ok()),
+            Command::SetDescription { id, description } => store
+                .get_unit_mut(*id)
+                .is_some_and(|unit| unit.set_description(description).is_ok()),
+            Command::SetInstallDate { id, install_date } => match store.get_unit_mut(*id) {
+                Some(unit) => {
+                    unit.set_install_date(*install_date);
+                    true
+                }
+                None => false,
+            },
+            Command::SetSerialNumber { id, serial_number } => store
+                .get_unit_mut(*id)
+                .is_some_and(|unit| unit.set_serial_number(serial_number).is_ok()),
+            Command::SetAttribute { id, key, value } => match store.get_unit_mut(*id) {
+                Some(unit) => match value {
+                    Some(value) => unit.set_attribute(key, value).is_ok(),
+                    None => unit.remove_attribute(key),
+                },
+                None => false,
+            },
+            Command::AddPanel { name } => store.add_panel(name).is_ok(),
+            Command::RenamePanel { number, name } => store.rename_panel(*number, name).is_ok(),
+            Command::RemovePanel { number } => store.remove_panel(*number).is_ok(),
+            Command::AddNetworkLink { a, b } => store.add_network_link(*a, *b).is_ok(),
+            Command::RemoveNetworkLink { a, b } => store.remove_network_link(*a, *b).is_ok(),
+            Command::AssignZone { member, zone } => store.assign_to_zone(*member, *zone).is_ok(),
+            Command::UnassignZone { member } => store.unassign_from_zone(*member),
+            Command::AddRule { name, logic } => {
+                store.cause_effect_mut().add_rule(name, *logic).is_ok()
+            }
+            Command::RenameRule { id, name } => match store.cause_effect_mut().get_rule_mut(*id) {
+                Some(rule) => {
+                    rule.set_name(name);
+                    true
+                }
+                None => false,
+            },
+            Command::SetRuleLogic { id, logic } => {
+                store.cause_effect_mut().set_logic(*id, *logic).is_ok()
+            }
+            Command::SetRuleInput {
+                rule,
+                unit,
+                enabled,
+            } => store.set_rule_input(*rule, *unit, *enabled).is_ok(),
+            Command::SetRuleOutput {
+                rule,
+                unit,
+                enabled,
+            } => store.set_rule_output(*rule, *unit, *enabled).is_ok(),
+            Command::RemoveRule { id } => store.cause_effect_mut().remove_rule(*id),
+        }
+    }
+
+    /// Describes the command for the history list, using names as they are before it runs.
+    fn describe(&self, store: &UnitStore) -> String {
+        let name_of = |id: &u64| {
+            store
+                .get_unit(*id)
+                .map(|unit| unit.name().to_string())
+                .unwrap_or_else(|| format!("unit {}", id))
+        };
+        let panel_name = |number: &u16| {
+            store
+                .network()
+                .get_panel(*number)
+                .map(|panel| panel.name().to_string())
+                .unwrap_or_else(|| format!("panel {}", number))
+        };
+        let rule_name = |id: &u64| {
+            store
+                .cause_effect()
+                .get_rule(*id)
+                .map(|rule| rule.name().to_string())
+                .unwrap_or_else(|| format!("rule {}", id))
+        };
+        match self {
+            Command::AddUnit {
+                parent: 0, name, ..
+            } => format!("Add {}", name),
+            Command::AddUnit { parent, name, .. } => {
+                format!("Add {} under {}", name, name_of(parent))
+            }
+            Command::RemoveUnit { id } => format!("Remove {}", name_of(id)),
+            Command::MoveUnit { id, parent } => {
+                format!("Move {} under {}", name_of(id), name_of(parent))
+            }
+            Command::SetName { id, name } => format!("Rename {} to {}", name_of(id), name),
+            Command::SetProperties { id, .. } => format!("Edit {}", name_of(id)),
+            Command::Clear => "Clear".to_string(),
+            Command::Configure { plan } => format!(
+                "Configure (+{} / -{} units)",
+                plan.additions().len(),
+                plan.removals().len()
+            ),
+            Command::AddLoop { .. } => "Add loop".to_string(),
+            Command::RemoveLoop { number } => format!("Remove loop {}", number),
+            Command::SetLoopCapacity { number, capacity } => {
+                format!("Set loop {} capacity to {}", number, capacity)
+            }
+            Command::ReaddressLoop { number } => format!("Re-address loop {}", number),
+            Command::SetAddress { id, address } => match address {
+                Some(address) => format!("Address {} as {}", name_of(id), address),
+                None => format!("Take {} off its loop", name_of(id)),
+            },
+            Command::AutoAddress { id } => format!("Address {}", name_of(id)),
+            Command::SetIsolator { id, isolator: true } => {
+                format!("Add isolator to {}", name_of(id))
+            }
+            Command::SetIsolator {
+                id,
+                isolator: false,
+            } => {
+                format!("Remove isolator from {}", name_of(id))
+            }
+            Command::SetThresholds { thresholds } => {
+                format!("Set zoning rules to {}", thresholds.variant)
+            }
+            Command::ImportCsv { import } => format!(
+                "Import CSV (+{} / ~{} units)",
+                import.creations(),
+                import.updates()
+            ),
+            Command::Bulk { edit } => edit.describe(store),
+            Command::SetLocation { id, .. } => format!("Set location of {}", name_of(id)),
+            Command::SetDescription { id, .. } => format!("Describe {}", name_of(id)),
+            Command::SetInstallDate { id, .. } => format!("Set install date of {}", name_of(id)),
+            Command::SetSerialNumber { id, .. } => {
+                format!("Set serial number of {}", name_of(id))
+            }
+            Command::SetAttribute {
+                id,
+                key,
+                value: Some(_),
+            } => format!("Set {} of {}", key, name_of(id)),
+            Command::SetAttribute {
+                id,
+                key,
+                value: None,
+            } => format!("Remove {} from {}", key, name_of(id)),
+            Command::AddPanel { name } => format!("Add panel {}", name),
+            Command::RenamePanel { number, name } => {
+                format!("Rename {} to {}", panel_name(number), name)
+            }
+            Command::RemovePanel { number } => format!("Remove {}", panel_name(number)),
+            Command::AddNetworkLink { a, b } => {
+                format!("Link {} and {}", panel_name(a), panel_name(b))
+            }
+            Command::RemoveNetworkLink { a, b } => {
+                format!("Unlink {} and {}", panel_name(a), panel_name(b))
+            }
+            Command::AssignZone { member, zone } => {
+                format!("Put {} in {}", name_of(member), name_of(zone))
+            }
+            Command::UnassignZone { member } => format!("Take {} out of its zone", name_of(member)),
+            Command::AddRule { name, .. } => format!("Add rule {}", name),
+            Command::RenameRule { id, name } => format!("Rename {} to {}", rule_name(id), name),
+            Command::SetRuleLogic { id, logic } => {
+                format!("Set logic of {} to {}", rule_name(id), logic)
+            }
+            Command::SetRuleInput {
+                rule,
+                unit,
+                enabled: true,
+            } => format!("Add {} to the inputs of {}", name_of(unit), rule_name(rule)),
+            Command::SetRuleInput {
+                rule,
+                unit,
+                enabled: false,
+            } => format!(
+                "Remove {} from the inputs of {}",
+                name_of(unit),
+                rule_name(rule)
+            ),
+            Command::SetRuleOutput {
+                rule,
+                unit,
+                enabled: true,
+            } => format!(
+                "Add {} to the outputs of {}",
+                name_of(unit),
+                rule_name(rule)
+            ),
+            Command::SetRuleOutput {
+                rule,
+                unit,
+                enabled: false,
+            } => format!(
+                "Remove {} from the outputs of {}",
+                name_of(unit),
+                rule_name(rule)
+            ),
+            Command::RemoveRule { id } => format!("Remove {}", rule_name(id)),
+        }
+    }
+}
+
+impl Command {
+    /// The unit the command is about, if it is about a single unit.
+    fn unit(&self) -> Option<u64> {
+        match self {
+            Command::RemoveUnit { id }
+            | Command::MoveUnit { id, .. }
+            | Command::SetName { id, .. }
+            | Command::SetProperties { id, .. }
+            | Command::SetAddress { id, .. }
+            | Command::AutoAddress { id }
+            | Command::SetIsolator { id, .. }
+            | Command::SetLocation { id, .. }
+            | Command::SetDescription { id, .. }
+            | Command::SetInstallDate { id, .. }
+            | Command::SetSerialNumber { id, .. }
+            | Command::SetAttribute { id, .. } => Some(*id),
+            Command::AssignZone { member, .. } | Command::UnassignZone { member } => Some(*member),
+            Command::SetRuleInput { unit, .. } | Command::SetRuleOutput { unit, .. } => Some(*unit),
+            _ => None,
+        }
+    }
+
+    /// The event log record of the command, using names as they are before it runs.
+    fn log_record(&self, store: &UnitStore, label: &str) -> LogRecord {
+        let name_of = |id: &u64| {
+            store
+                .get_unit(*id)
+                .map(|unit| unit.name().to_string())
+                .unwrap_or_else(|| format!("unit {}", id))
+        };
+        let event = match self {
+            Command::AddUnit { kind, name, .. } => {
+                // The unit takes the next id when the command runs
+                return LogRecord::new(
+                    Some(store.next_id()),
+                    LogEvent::UnitAdded {
+                        kind: *kind,
+                        name: name.clone(),
+                    },
+                );
+            }
+            Command::RemoveUnit { id } => LogEvent::UnitRemoved { name: name_of(id) },
+            Command::SetName { id, name } => LogEvent::UnitRenamed {
+                from: name_of(id),
+                to: name.clone(),
+            },
+            Command::Clear => LogEvent::Cleared,
+            _ => LogEvent::ConfigChanged {
+                description: label.to_string(),
+            },
+        };
+        LogRecord::new(self.unit(), event)
+    }
+}
+
+// How to get back to the state before a command ran
+#[derive(Debug, Clone, PartialEq)]
+enum Inverse {
+    // The id an added unit took
+    Added(u64),
+    // The unit as it was, for edits that leave its address and parent alone
+    Unit(Box<Unit>),
+    Parent {
+        id: u64,
+        parent: Option<u64>,
+    },
+    Address {
+        id: u64,
+        address: Option<LoopAddress>,
+    },
+    LoopCapacity {
+        number: u16,
+        capacity: u16,
+    },
+    Thresholds(ZoningThresholds),
+    Zone {
+        member: u64,
+        zone: Option<u64>,
+    },
+    // The rule as it was, for edits of a single rule
+    Rule(Box<Rule>),
+    PanelName {
+        number: u16,
+        name: String,
+    },
+    Link {
+        a: u16,
+        b: u16,
+        linked: bool,
+    },
+    // Removals cascade into subtrees, memberships and rules, and adds consume ids, so anything else restores the whole
+    // store. That also keeps ids identical when the command is redone.
+    Restore(Box<UnitStore>),
+}
+
+impl Inverse {
+    /// Captures what `command` is about to change. Returns None if it is about something that does not exist.
+    fn of(command: &Command, store: &UnitStore) -> Option<Self> {
+        let unit = |id: &u64| Some(Inverse::Unit(Box::new(store.get_unit(*id)?.clone())));
+        let rule = |id: &u64| {
+            Some(Inverse::Rule(Box::new(
+                store.cause_effect().get_rule(*id)?.clone(),
+            )))
+        };
+        match command {
+            Command::AddUnit { .. } => Some(Inverse::Added(store.next_id())),
+            Command::SetName { id, .. }
+            | Command::SetProperties { id, .. }
+            | Command::SetIsolator { id, .. }
+            | Command::SetLocation { id, .. }
+            | Command::SetDescription { id, .. }
+            | Command::SetInstallDate { id, .. }
+            | Command::SetSerialNumber { id, .. }
+            | Command::SetAttribute { id, .. } => unit(id),
+            // A move to another panel's unit takes the subtree to that panel too
+            Command::MoveUnit { id, parent }
+                if *parent != 0 && store.panel_of(*parent) != store.panel_of(*id) =>
+            {
+                Some(Inverse::Restore(Box::new(store.clone())))
+            }
+            Command::MoveUnit { id, .. } => Some(Inverse::Parent {
+                id: *id,
+                parent: store.get_unit(*id)?.parent(),
+            }),
+            Command::SetAddress { id, .. } | Command::AutoAddress { id } => {
+                Some(Inverse::Address {
+                    id: *id,
+                    address: store.get_unit(*id)?.address(),
+                })
+            }
+            Command::SetLoopCapacity { number, .. } => Some(Inverse::LoopCapacity {
+                number: *number,
+                capacity: store.get_loop(*number)?.capacity(),
+            }),
+            Command::SetThresholds { .. } => Some(Inverse::Thresholds(store.thresholds().clone())),
+            Command::AssignZone { member, .. } | Command::UnassignZone { member } => {
+                Some(Inverse::Zone {
+                    member: *member,
+                    zone: store.zone_of(*member),
+                })
+            }
+            Command::RenameRule { id, .. }
+            | Command::SetRuleLogic { id, .. }
+            | Command::SetRuleInput { rule: id, .. }
+            | Command::SetRuleOutput { rule: id, .. } => rule(id),
+            Command::RenamePanel { number, .. } => Some(Inverse::PanelName {
+                number: *number,
+                name: store.network().get_panel(*number)?.name().to_string(),
+            }),
+            Command::AddNetworkLink { a, b } => Some(Inverse::Link {
+                a: *a,
+                b: *b,
+                linked: false,
+            }),
+            Command::RemoveNetworkLink { a, b } => Some(Inverse::Link {
+                a: *a,
+                b: *b,
+                linked: true,
+            }),
+            _ => Some(Inverse::Restore(Box::new(store.clone()))),
+        }
+    }
+
+    /// Puts back what the command changed. Commands are undone most recent first, so what they changed is still there.
+    fn apply(self, store: &mut UnitStore) {
+        match self {
+            Inverse::Added(id) => {
+                store.remove_last_added(id);
+            }
+            Inverse::Unit(unit) => {
+                if let Some(current) = store.get_unit_mut(unit.id()) {
+                    *current = *unit;
+                }
+            }
+            Inverse::Parent { id, parent } => {
+                if let Some(parent) = parent {
+                    let _ = store.move_unit(id, parent);
+                }
+            }
+            Inverse::Address { id, address } => {
+                let _ = store.set_address(id, address);
+            }
+            Inverse::LoopCapacity { number, capacity } => {
+                let _ = store.set_loop_capacity(number, capacity);
+            }
+            Inverse::Thresholds(thresholds) => store.set_thresholds(thresholds),
+            Inverse::Zone { member, zone } => match zone {
+                Some(zone) => {
+                    let _ = store.assign_to_zone(member, zone);
+                }
+                None => {
+                    store.unassign_from_zone(member);
+                }
+            },
+            Inverse::Rule(rule) => {
+                if let Some(current) = store.cause_effect_mut().get_rule_mut(rule.id()) {
+                    *current = *rule;
+                }
+            }
+            Inverse::PanelName { number, name } => {
+                let _ = store.rename_panel(number, &name);
+            }
+            Inverse::Link { a, b, linked: true } => {
+                let _ = store.add_network_link(a, b);
+            }
+            Inverse::Link {
+                a,
+                b,
+                linked: false,
+            } => {
+                let _ = store.remove_network_link(a, b);
+            }
+            Inverse::Restore(previous) => *store = *previous,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct HistoryEntry {
+    label: String,
+    command: Command,
+    inverse: Inverse,
+}
+
+/// Undo and redo stacks for [`Command`]s applied to a [`UnitStore`].
+///
+/// Only changes made through [`History::execute`] are tracked; undoing a command restores the store as it was before
+/// that command ran. Edits of a single unit, rule, zone membership or setting only keep what they change; anything else
+/// keeps a copy of the whole store, and only the most recent few of those are kept. Every command, undo and redo also
+/// queues a [`LogRecord`] for the event log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct History {
+    undo: VecDeque<HistoryEntry>,
+    // Labels and commands of undone commands, which are run again to redo them
+    redo: Vec<(String, Command)>,
+    limit: usize,
+    changes: Vec<LogRecord>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::with_limit(DEFAULT_HISTORY_LIMIT)
+    }
+
+    /// Creates a history that keeps at most `limit` commands for undo.
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            limit: limit.max(1),
+            changes: Vec::new(),
+        }
+    }
+
+    /// Applies `command` and records it. Clears the redo stack. Returns false if the command did not apply.
+    pub fn execute(&mut self, store: &mut UnitStore, command: Command) -> bool {
+        match self.run(store, command) {
+            Some(entry) => {
+                self.redo.clear();
+                self.push_undo(entry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Reverts the most recent command. Returns false if there is nothing to undo.
+    pub fn undo(&mut self, store: &mut UnitStore) -> bool {
+        let Some(entry) = self.undo.pop_back() else {
+            return false;
+        };
+        entry.inverse.apply(store);
+        self.changes.push(LogRecord::new(
+            entry.command.unit(),
+            LogEvent::ConfigChanged {
+                description: format!("Undo {}", entry.label),
+            },
+        ));
+        self.redo.push((entry.label, entry.command));
+        true
+    }
+
+    /// Re-applies the most recently undone command. Returns false if there is nothing to redo.
+    pub fn redo(&mut self, store: &mut UnitStore) -> bool {
+        let Some((_, command)) = self.redo.pop() else {
+            return false;
+        };
+        match self.run(store, command) {
+            Some(entry) => {
+                self.push_undo(entry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Labels of the commands that can be undone, most recent first.
+    pub fn undo_labels(&self) -> impl Iterator<Item = &str> {
+        self.undo.iter().rev().map(|entry| entry.label.as_str())
+    }
+
+    /// Labels of the commands that can be redone, next to redo first.
+    pub fn redo_labels(&self) -> impl Iterator<Item = &str> {
+        self.redo.iter().rev().map(|(label, _)| label.as_str())
+    }
+
+    /// Forgets every recorded command because the project `name` was opened, and queues a log record for it. Log records
+    /// queued before are kept.
+    pub fn project_opened(&mut self, name: &str) {
+        self.undo.clear();
+        self.redo.clear();
+        self.changes.push(LogRecord::new(
+            None,
+            LogEvent::ConfigChanged {
+                description: format!("Open {}", name),
+            },
+        ));
+    }
+
+    pub fn has_changes(&self) -> bool {
+        !self.changes.is_empty()
+    }
+
+    /// Hands over the log records queued since the last call.
+    pub fn take_changes(&mut self) -> Vec<LogRecord> {
+        std::mem::take(&mut self.changes)
+    }
+
+    fn run(&mut self, store: &mut UnitStore, command: Command) -> Option<HistoryEntry> {
+        let label = command.describe(store);
+        let record = command.log_record(store, &label);
+        let inverse = Inverse::of(&command, store)?;
+        if !command.apply(store) {
+            return None;
+        }
+        self.changes.push(record);
+        Some(HistoryEntry {
+            label,
+            command,
+            inverse,
+        })
+    }
+
+    fn push_undo(&mut self, entry: HistoryEntry) {
+        self.undo.push_back(entry);
+        while self.undo.len() > self.limit || self.snapshots() > MAX_SNAPSHOTS {
+            self.undo.pop_front();
+        }
+    }
+
+    // Number of recorded commands that keep a copy of the whole store
+    fn snapshots(&self) -> usize {
+        self.undo
+            .iter()
+            .filter(|entry| matches!(entry.inverse, Inverse::Restore(_)))
+            .count()
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Configures one more unit of `kind`
+    fn add(history: &mut History, store: &mut UnitStore, kind: UnitKind) -> bool {
+        let count = store.count_of_kind(kind) + 1;
+        let plan = ConfigurePlan::for_panel(store, None, &[(kind, count)]);
+        history.execute(store, Command::Configure { plan })
+    }
+
+    #[test]
+    fn test_undo_and_redo_configure_keeps_ids() {
+        let mut store = UnitStore::new();
+        let mut history = History::new();
+
+        assert!(add(&mut history, &mut store, UnitKind::Detector));
+        assert!(history.undo(&mut store));
+        assert_eq!(store.count(), 0);
+
+        assert!(history.redo(&mut store));
+        assert_eq!(store.get_unit(1).unwrap().name(), "Detector 1");
+        assert_eq!(store.next_id(), 2);
+    }
+
+    #[test]
+    fn test_undo_and_redo_add_unit_keeps_ids() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let mut history = History::new();
+        let add_sounder = Command::AddUnit {
+            parent: det,
+            kind: UnitKind::FireAlarmDevice,
+            name: "Base Sounder".to_string(),
+        };
+
+        assert!(history.execute(&mut store, add_sounder));
+        assert_eq!(
+            history.undo_labels().next(),
+            Some("Add Base Sounder under Detector 1")
+        );
+        assert!(history.undo(&mut store));
+        assert_eq!(store.count(), 1);
+        assert_eq!(store.next_id(), 2);
+
+        assert!(history.redo(&mut store));
+        let sounder = store.get_unit(2).unwrap();
+        assert_eq!(sounder.name(), "Base Sounder");
+        assert_eq!(sounder.parent(), Some(det));
+    }
+
+    #[test]
+    fn test_undo_moving_to_another_panel_restores_the_panels() {
+        let mut store = UnitStore::new();
+        let annex = store.add_panel("Annex").unwrap();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let sounder = store
+            .add_child(det, UnitKind::FireAlarmDevice, "Base Sounder")
+            .unwrap();
+        let other = store.add_unit(UnitKind::Detector, "Detector 2");
+        store.set_panel(other, annex).unwrap();
+        let before = store.clone();
+        let mut history = History::new();
+
+        assert!(history.execute(
+            &mut store,
+            Command::MoveUnit {
+                id: det,
+                parent: other
+            }
+        ));
+        assert_eq!(store.panel_of(sounder), Some(annex));
+        assert!(history.undo(&mut store));
+        assert_eq!(store, before);
+    }
+
+    #[test]
+    fn test_add_and_rename_check_names() {
+        let mut store = UnitStore::new();
+        store.add_unit(UnitKind::Detector, "Detector 1");
+        let det = store.add_unit(UnitKind::Detector, "Detector 2");
+        let mut history = History::new();
+        let add = |name: &str| Command::AddUnit {
+            parent: 0,
+            kind: UnitKind::Detector,
+            name: name.to_string(),
+        };
+        let rename = |name: &str| Command::SetName {
+            id: det,
+            name: name.to_string(),
+        };
+
+        assert!(!history.execute(&mut store, add("Detector 1")));
+        assert!(!history.execute(&mut store, add(" ")));
+        assert!(!history.execute(&mut store, rename("Detector 1")));
+        assert!(!history.execute(&mut store, rename("")));
+        assert_eq!(store.get_unit(det).unwrap().name(), "Detector 2");
+        assert!(!history.can_undo());
+
+        assert!(history.execute(&mut store, rename("Kitchen")));
+        assert!(history.execute(&mut store, add("Detector 2")));
+    }
+
+    #[test]
+    fn test_undo_remove_restores_memberships() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let dz = store.add_unit(UnitKind::DetectionZone, "Detection Zone 1");
+        store.assign_to_zone(det, dz).unwrap();
+        let mut history = History::new();
+
+        history.execute(&mut store, Command::RemoveUnit { id: dz });
+        assert_eq!(store.zone_of(det), None);
+
+        history.undo(&mut store);
+        assert_eq!(store.zone_of(det), Some(dz));
+    }
+
+    #[test]
+    fn test_undo_rename() {
+        let mut store = UnitStore::new();
+        let id = store.add_unit(UnitKind::Output, "Output 1");
+        let mut history = History::new();
+
+        history.execute(
+            &mut store,
+            Command::SetName {
+                id,
+                name: "Door Release".to_string(),
+            },
+        );
+        assert_eq!(store.get_unit(id).unwrap().name(), "Door Release");
+
+        history.undo(&mut store);
+        assert_eq!(store.get_unit(id).unwrap().name(), "Output 1");
+    }
+
+    #[test]
+    fn test_undo_clear_and_configure() {
+        let mut store = UnitStore::new();
+        let mut history = History::new();
+
+        let plan = ConfigurePlan::for_panel(&store, None, &[(UnitKind::Detector, 3)]);
+        history.execute(&mut store, Command::Configure { plan });
+        let configured = store.clone();
+        history.execute(&mut store, Command::Clear);
+        assert_eq!(store.count(), 0);
+
+        history.undo(&mut store);
+        assert_eq!(store, configured);
+        history.undo(&mut store);
+        assert_eq!(store, UnitStore::new());
+    }
+
+    #[test]
+    fn test_commands_undo_and_redo_queue_log_records() {
+        let mut store = UnitStore::new();
+        let mut history = History::new();
+
+        add(&mut history, &mut store, UnitKind::Detector);
+        history.execute(
+            &mut store,
+            Command::SetName {
+                id: 1,
+                name: "Kitchen".to_string(),
+            },
+        );
+        history.execute(&mut store, Command::AutoAddress { id: 1 });
+        history.undo(&mut store);
+        history.execute(&mut store, Command::RemoveUnit { id: 1 });
+        history.execute(&mut store, Command::Clear);
+
+        assert_eq!(
+            history.take_changes(),
+            vec![
+                LogRecord::new(
+                    None,
+                    LogEvent::ConfigChanged {
+                        description: "Configure (+1 / -0 units)".to_string()
+                    }
+                ),
+                LogRecord::new(
+                    Some(1),
+                    LogEvent::UnitRenamed {
+                        from: "Detector 1".to_string(),
+                        to: "Kitchen".to_string()
+                    }
+                ),
+                LogRecord::new(
+                    Some(1),
+                    LogEvent::ConfigChanged {
+                        description: "Address Kitchen".to_string()
+                    }
+                ),
+                LogRecord::new(
+                    Some(1),
+                    LogEvent::ConfigChanged {
+                        description: "Undo Address Kitchen".to_string()
+                    }
+                ),
+                LogRecord::new(
+                    Some(1),
+                    LogEvent::UnitRemoved {
+                        name: "Kitchen".to_string()
+                    }
+                ),
+                LogRecord::new(None, LogEvent::Cleared),
+            ]
+        );
+        assert!(!history.has_changes());
+    }
+
+    #[test]
+    fn test_failed_command_is_not_recorded() {
+        let mut store = UnitStore::new();
+        let mut history = History::new();
+
+        assert!(!history.execute(&mut store, Command::RemoveUnit { id: 42 }));
+        assert!(!history.execute(
+            &mut store,
+            Command::SetName {
+                id: 42,
+                name: "Nothing".to_string()
+            }
+        ));
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn test_execute_clears_redo() {
+        let mut store = UnitStore::new();
+        let mut history = History::new();
+        add(&mut history, &mut store, UnitKind::Detector);
+        history.undo(&mut store);
+        assert!(history.can_redo());
+
+        add(&mut history, &mut store, UnitKind::Output);
+
+        assert!(!history.can_redo());
+        assert!(!history.redo(&mut store));
+    }
+
+    #[test]
+    fn test_labels_are_most_recent_first() {
+        let mut store = UnitStore::new();
+        let mut history = History::new();
+        add(&mut history, &mut store, UnitKind::Detector);
+        history.execute(&mut store, Command::RemoveUnit { id: 1 });
+        history.execute(&mut store, Command::Clear);
+        history.undo(&mut store);
+
+        let undo: Vec<&str> = history.undo_labels().collect();
+        let redo: Vec<&str> = history.redo_labels().collect();
+        assert_eq!(undo, vec!["Remove Detector 1", "Configure (+1 / -0 units)"]);
+        assert_eq!(redo, vec!["Clear"]);
+    }
+
+    #[test]
+    fn test_limit_drops_oldest_commands() {
+        let mut store = UnitStore::new();
+        let mut history = History::with_limit(2);
+        for _ in 1..=3 {
+            add(&mut history, &mut store, UnitKind::Detector);
+        }
+
+        assert_eq!(history.undo_labels().count(), 2);
+        assert!(history.undo(&mut store));
+        assert!(history.undo(&mut store));
+        assert!(!history.undo(&mut store));
+        assert_eq!(store.count(), 1);
+    }
+
+    #[test]
+    fn test_undo_readdress_loop() {
+        let mut store = UnitStore::new();
+        let number = store.add_loop(10).unwrap();
+        let id = store.add_unit(UnitKind::Detector, "Detector 1");
+        store
+            .set_address(id, Some(LoopAddress::new(number, 7)))
+            .unwrap();
+        let mut history = History::new();
+
+        assert!(history.execute(&mut store, Command::ReaddressLoop { number }));
+        assert_eq!(
+            store.get_unit(id).unwrap().address(),
+            Some(LoopAddress::new(number, 1))
+        );
+
+        history.undo(&mut store);
+        assert_eq!(
+            store.get_unit(id).unwrap().address(),
+            Some(LoopAddress::new(number, 7))
+        );
+        assert_eq!(history.undo_labels().next(), None);
+    }
+
+    #[test]
+    fn test_undo_rule_and_zone_edits() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let dz = store.add_unit(UnitKind::DetectionZone, "Detection Zone 1");
+        let out = store.add_unit(UnitKind::Output, "Output 1");
+        let mut history = History::new();
+
+        assert!(history.execute(
+            &mut store,
+            Command::AssignZone {
+                member: det,
+                zone: dz
+            }
+        ));
+        assert!(history.execute(
+            &mut store,
+            Command::AddRule {
+                name: "Rule 1".to_string(),
+                logic: InputLogic::Any,
+            }
+        ));
+        let rule = store.cause_effect().rules()[0].id();
+        for command in [
+            Command::SetRuleInput {
+                rule,
+                unit: dz,
+                enabled: true,
+            },
+            Command::SetRuleOutput {
+                rule,
+                unit: out,
+                enabled: true,
+            },
+            Command::RenameRule {
+                id: rule,
+                name: "Door release".to_string(),
+            },
+            Command::SetRuleLogic {
+                id: rule,
+                logic: InputLogic::All,
+            },
+        ] {
+            assert!(history.execute(&mut store, command));
+        }
+        assert!(!history.execute(
+            &mut store,
+            Command::SetRuleLogic {
+                id: rule,
+                logic: InputLogic::Coincidence(0),
+            }
+        ));
+        assert!(!history.execute(
+            &mut store,
+            Command::SetRuleOutput {
+                rule,
+                unit: det,
+                enabled: true,
+            }
+        ));
+        let edited = store.clone();
+        assert!(history.execute(&mut store, Command::RemoveRule { id: rule }));
+        assert!(store.cause_effect().rules().is_empty());
+
+        assert_eq!(
+            history.undo_labels().take(2).collect::<Vec<_>>(),
+            vec!["Remove Door release", "Set logic of Door release to All"]
+        );
+        history.undo(&mut store);
+        assert_eq!(store, edited);
+        while history.undo(&mut store) {}
+        assert_eq!(store.zone_of(det), None);
+        assert!(store.cause_effect().rules().is_empty());
+    }
+
+    #[test]
+    fn test_undo_set_properties() {
+        let mut store = UnitStore::new();
+        let id = store.add_unit(UnitKind::Output, "Output 1");
+        let mut history = History::new();
+
+        assert!(history.execute(
+            &mut store,
+            Command::SetProperties {
+                id,
+                properties: UnitProperties::Output { delay_seconds: 30 },
+            }
+        ));
+        assert!(!history.execute(
+            &mut store,
+            Command::SetProperties {
+                id,
+                properties: UnitProperties::None,
+            }
+        ));
+
+        history.undo(&mut store);
+        assert_eq!(
+            store.get_unit(id).unwrap().properties(),
+            UnitProperties::Output { delay_seconds: 0 }
+        );
+    }
+
+    #[test]
+    fn test_undo_restores_what_edits_changed() {
+        let mut store = UnitStore::new();
+        let number = store.add_loop(10).unwrap();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let dz1 = store.add_unit(UnitKind::DetectionZone, "Detection Zone 1");
+        let dz2 = store.add_unit(UnitKind::DetectionZone, "Detection Zone 2");
+        let det2 = store.add_unit(UnitKind::Detector, "Detector 2");
+        let out = store.add_unit(UnitKind::Output, "Output 1");
+        store.assign_to_zone(det, dz1).unwrap();
+        store
+            .get_unit_mut(det)
+            .unwrap()
+            .set_attribute("colour", "red")
+            .unwrap();
+        let before = store.clone();
+        let mut history = History::new();
+
+        for command in [
+            Command::SetAttribute {
+                id: det,
+                key: "colour".to_string(),
+                value: None,
+            },
+            Command::SetAttribute {
+                id: det,
+                key: "make".to_string(),
+                value: Some("Acme".to_string()),
+            },
+            Command::SetIsolator {
+                id: det,
+                isolator: true,
+            },
+            Command::SetAddress {
+                id: det,
+                address: Some(LoopAddress::new(number, 4)),
+            },
+            Command::SetLoopCapacity {
+                number,
+                capacity: 20,
+            },
+            Command::AssignZone {
+                member: det,
+                zone: dz2,
+            },
+            Command::UnassignZone { member: det },
+            Command::MoveUnit {
+                id: det,
+                parent: det2,
+            },
+            Command::MoveUnit {
+                id: det2,
+                parent: out,
+            },
+            Command::SetThresholds {
+                thresholds: ZoningThresholds::presets().pop().unwrap(),
+            },
+        ] {
+            assert!(history.execute(&mut store, command));
+        }
+        assert_eq!(history.snapshots(), 0);
+
+        while history.undo(&mut store) {}
+        assert_eq!(store, before);
+    }
+
+    #[test]
+    fn test_only_recent_snapshots_are_kept() {
+        let mut store = UnitStore::new();
+        let mut history = History::new();
+        for _ in 1..=MAX_SNAPSHOTS + 5 {
+            add(&mut history, &mut store, UnitKind::Detector);
+        }
+        history.execute(
+            &mut store,
+            Command::SetName {
+                id: 1,
+                name: "Kitchen".to_string(),
+            },
+        );
+
+        assert_eq!(history.undo_labels().count(), MAX_SNAPSHOTS + 1);
+        while history.undo(&mut store) {}
+        assert_eq!(store.count(), 5);
+        assert_eq!(store.get_unit(1).unwrap().name(), "Detector 1");
+    }
+}
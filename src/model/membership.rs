@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::UnitKind;
+
+/// Reasons a unit cannot be assigned to a zone.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MembershipError {
+    UnitNotFound(u64),
+    ZoneNotFound(u64),
+    NotAssignable { member: UnitKind, zone: UnitKind },
+}
+
+impl fmt::Display for MembershipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MembershipError::UnitNotFound(id) => write!(f, "unit {} does not exist", id),
+            MembershipError::ZoneNotFound(id) => write!(f, "zone {} does not exist", id),
+            MembershipError::NotAssignable { member, zone } => {
+                write!(f, "a {} cannot be assigned to a {}", member, zone)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MembershipError {}
+
+/// Which zone each unit belongs to.
+///
+/// Detectors and manual call points belong to a detection zone, while detection zones and fire alarm
+/// devices belong to an alarm zone. Every member has at most one zone, so the relation is kept as a
+/// member -> zone map. Kind checks are done by [`super::UnitStore`], which knows the units.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Membership {
+    zones: BTreeMap<u64, u64>,
+}
+
+impl Membership {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Puts `member` in `zone`, replacing any previous zone. Returns the previous zone.
+    pub fn assign(&mut self, member: u64, zone: u64) -> Option<u64> {
+        self.zones.insert(member, zone)
+    }
+
+    pub fn unassign(&mut self, member: u64) -> Option<u64> {
+        self.zones.remove(&member)
+    }
+
+    pub fn zone_of(&self, member: u64) -> Option<u64> {
+        self.zones.get(&member).copied()
+    }
+
+    /// Returns the members of `zone` in ascending id order.
+    pub fn members_of(&self, zone: u64) -> impl Iterator<Item = u64> + '_ {
+        self.zones
+            .iter()
+            .filter(move |(_, z)| **z == zone)
+            .map(|(member, _)| *member)
+    }
+
+    /// Iterates over `(member, zone)` pairs in ascending member id order.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.zones.iter().map(|(member, zone)| (*member, *zone))
+    }
+
+    /// Drops every membership that involves `id`, either as member or as zone.
+    pub fn forget(&mut self, id: u64) {
+        self.zones
+            .retain(|member, zone| *member != id && *zone != id);
+    }
+
+    pub fn clear(&mut self) {
+        self.zones.clear();
+    }
+}
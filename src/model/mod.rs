@@ -1,5 +1,60 @@
+pub mod access;
+pub mod addressing;
+pub mod bulk;
+pub mod cause_effect;
+pub mod configure;
+pub mod details;
+pub mod event_log;
+pub mod history;
+pub mod membership;
+pub mod network;
+pub mod project;
+pub mod properties;
+pub mod query;
+pub mod report;
+pub mod selection;
+pub mod simulation;
+pub mod state;
 pub mod unit;
+pub mod unit_csv;
+pub mod unit_kind;
+pub mod unit_map;
 pub mod unit_store;
+pub mod validation;
+pub mod zoning;
 
+pub use access::AccessLevel;
+pub use addressing::{
+    AddressError, Loop, LoopAddress, LoopUtilisation, DEFAULT_LOOP_CAPACITY, MAX_LOOP_ADDRESS,
+};
+pub use bulk::{BulkAction, BulkEdit, NUMBER_PLACEHOLDER};
+pub use cause_effect::{CauseEffectError, CauseEffectMatrix, InputLogic, Rule};
+pub use configure::ConfigurePlan;
+pub use details::{
+    check_attribute, check_description, check_serial_number, format_attributes, parse_attributes,
+    Attributes, DetailError, InstallDate, Location, MAX_DESCRIPTION_LENGTH,
+    MAX_SERIAL_NUMBER_LENGTH,
+};
+pub use event_log::{
+    log_to_csv, log_to_json, EventLog, LogCategory, LogEntry, LogEvent, LogFilter, LogRecord,
+    Timestamp, DEFAULT_OPERATOR,
+};
+pub use history::{Command, History};
+pub use membership::{Membership, MembershipError};
+pub use network::{Network, NetworkError, PanelFilter, MAX_PANEL_NAME_LENGTH};
+pub use project::{load_project, save_project};
+pub use properties::{
+    AlarmDeviceType, DetectorType, PropertyError, UnitProperties, MAX_OUTPUT_DELAY_SECONDS,
+};
+pub use query::{LoopFilter, SortKey, UnitQuery, ZoneFilter};
+pub use report::{Report, ReportFormat};
+pub use selection::Selection;
+pub use simulation::{Simulation, SimulationState};
+pub use state::{PanelStatus, StateError, UnitState};
 pub use unit::Unit;
-pub use unit_store::UnitStore;
+pub use unit_csv::{export_csv, CsvImport};
+pub use unit_kind::UnitKind;
+pub use unit_map::UnitMap;
+pub use unit_store::{NameError, UnitStore, MAX_NAME_LENGTH};
+pub use validation::{validate, Finding, Severity, MAX_UNITS_PER_KIND};
+pub use zoning::{RuleSet, ZoningThresholds};
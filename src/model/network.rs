@@ -0,0 +1,423 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// The longest name a panel may have, in characters.
+pub const MAX_PANEL_NAME_LENGTH: usize = 64;
+
+/// Reasons a change to the panels or the network between them is refused.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetworkError {
+    PanelNotFound(u16),
+    UnitNotFound(u64),
+    RootHasNoPanel,
+    /// Units mounted below another unit belong to that unit's panel.
+    Mounted {
+        unit: u64,
+        parent: u64,
+    },
+    NameEmpty,
+    NameTooLong {
+        max: usize,
+    },
+    DuplicateName {
+        other: u16,
+    },
+    /// A project always has at least one panel.
+    LastPanel,
+    /// Panels that still own units cannot be removed.
+    PanelNotEmpty {
+        panel: u16,
+        units: usize,
+    },
+    SelfLink(u16),
+    LinkExists {
+        a: u16,
+        b: u16,
+    },
+    LinkNotFound {
+        a: u16,
+        b: u16,
+    },
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkError::PanelNotFound(number) => write!(f, "panel {} does not exist", number),
+            NetworkError::UnitNotFound(id) => write!(f, "unit {} does not exist", id),
+            NetworkError::RootHasNoPanel => write!(f, "the Root unit does not belong to a panel"),
+            NetworkError::Mounted { unit, parent } => write!(
+                f,
+                "unit {} is mounted below unit {} and belongs to its panel",
+                unit, parent
+            ),
+            NetworkError::NameEmpty => write!(f, "panel name must not be empty"),
+            NetworkError::NameTooLong { max } => {
+                write!(f, "panel name must be at most {} characters", max)
+            }
+            NetworkError::DuplicateName { other } => {
+                write!(f, "panel {} already has this name", other)
+            }
+            NetworkError::LastPanel => write!(f, "the last panel cannot be removed"),
+            NetworkError::PanelNotEmpty { panel, units } => {
+                write!(f, "panel {} still has {} units", panel, units)
+            }
+            NetworkError::SelfLink(number) => {
+                write!(f, "panel {} cannot be linked to itself", number)
+            }
+            NetworkError::LinkExists { a, b } => {
+                write!(f, "panels {} and {} are already linked", a, b)
+            }
+            NetworkError::LinkNotFound { a, b } => {
+                write!(f, "panels {} and {} are not linked", a, b)
+            }
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {}
+
+/// A control panel of the site. Each panel owns its own units.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Panel {
+    number: u16,
+    name: String,
+}
+
+impl Panel {
+    pub fn new(number: u16, name: &str) -> Self {
+        Self {
+            number,
+            name: name.to_string(),
+        }
+    }
+
+    pub fn number(&self) -> u16 {
+        self.number
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for Panel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "P{} {}", self.number, self.name)
+    }
+}
+
+/// A network connection between two panels. Links carry events both ways, so the lower panel number is always `a`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct NetworkLink {
+    pub a: u16,
+    pub b: u16,
+}
+
+impl NetworkLink {
+    pub fn new(a: u16, b: u16) -> Self {
+        Self {
+            a: a.min(b),
+            b: a.max(b),
+        }
+    }
+
+    /// The panel at the other end from `panel`, if the link touches `panel`.
+    pub fn other_end(&self, panel: u16) -> Option<u16> {
+        if self.a == panel {
+            Some(self.b)
+        } else if self.b == panel {
+            Some(self.a)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for NetworkLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "P{} – P{}", self.a, self.b)
+    }
+}
+
+/// Restricts what the pages show to the units of one panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PanelFilter {
+    #[default]
+    All,
+    Panel(u16),
+}
+
+impl PanelFilter {
+    /// The selected panel, or `None` for all panels.
+    pub fn panel(&self) -> Option<u16> {
+        match self {
+            PanelFilter::All => None,
+            PanelFilter::Panel(number) => Some(*number),
+        }
+    }
+}
+
+/// The panels of a site, the links between them and which panel owns each unit.
+///
+/// Units that were never assigned belong to the main panel, the first one in the list, so stores from before panels
+/// existed keep every unit on one panel. Unit checks are done by [`super::UnitStore`], which knows the units.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Network {
+    panels: Vec<Panel>,
+    links: Vec<NetworkLink>,
+    assignments: BTreeMap<u64, u16>,
+}
+
+impl Network {
+    /// A network with a single panel.
+    pub fn new() -> Self {
+        Self {
+            panels: vec![Panel::new(1, "Panel 1")],
+            links: Vec::new(),
+            assignments: BTreeMap::new(),
+        }
+    }
+
+    pub fn panels(&self) -> &[Panel] {
+        &self.panels
+    }
+
+    pub fn get_panel(&self, number: u16) -> Option<&Panel> {
+        self.panels.iter().find(|panel| panel.number == number)
+    }
+
+    /// The panel that owns units nobody assigned elsewhere.
+    pub fn main_panel(&self) -> u16 {
+        self.panels.first().map_or(1, |panel| panel.number)
+    }
+
+    pub fn links(&self) -> &[NetworkLink] {
+        &self.links
+    }
+
+    /// Checks whether panel `number` may be called `name`. Pass a number no panel has to check a new panel.
+    pub fn check_panel_name(&self, number: u16, name: &str) -> Result<(), NetworkError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(NetworkError::NameEmpty);
+        }
+        if name.chars().count() > MAX_PANEL_NAME_LENGTH {
+            return Err(NetworkError::NameTooLong {
+                max: MAX_PANEL_NAME_LENGTH,
+            });
+        }
+        if let Some(other) = self
+            .panels
+            .iter()
+            .find(|panel| panel.number != number && panel.name == name)
+        {
+            return Err(NetworkError::DuplicateName {
+                other: other.number,
+            });
+        }
+        Ok(())
+    }
+
+    /// Adds a panel numbered one above the highest existing panel. Returns its number.
+    pub fn add_panel(&mut self, name: &str) -> Result<u16, NetworkError> {
+        let number = self
+            .panels
+            .iter()
+            .map(|panel| panel.number)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        self.check_panel_name(number, name)?;
+        self.panels.push(Panel::new(number, name.trim()));
+        Ok(number)
+    }
+
+    pub fn rename_panel(&mut self, number: u16, name: &str) -> Result<(), NetworkError> {
+        self.get_panel(number)
+            .ok_or(NetworkError::PanelNotFound(number))?;
+        self.check_panel_name(number, name)?;
+        if let Some(panel) = self.panels.iter_mut().find(|panel| panel.number == number) {
+            panel.name = name.trim().to_string();
+        }
+        Ok(())
+    }
+
+    /// Removes a panel and its links. Whether it still owns units is checked by the store.
+    pub(super) fn remove_panel(&mut self, number: u16) -> Result<(), NetworkError> {
+        self.get_panel(number)
+            .ok_or(NetworkError::PanelNotFound(number))?;
+        if self.panels.len() == 1 {
+            return Err(NetworkError::LastPanel);
+        }
+        self.panels.retain(|panel| panel.number != number);
+        self.links.retain(|link| link.other_end(number).is_none());
+        self.assignments.retain(|_, panel| *panel != number);
+        Ok(())
+    }
+
+    /// The panel that owns `unit`.
+    pub fn panel_of(&self, unit: u64) -> u16 {
+        self.assignments
+            .get(&unit)
+            .copied()
+            .unwrap_or_else(|| self.main_panel())
+    }
+
+    /// Gives `unit` to `panel`. Returns the panel that owned it before.
+    pub fn assign(&mut self, unit: u64, panel: u16) -> Result<u16, NetworkError> {
+        self.get_panel(panel)
+            .ok_or(NetworkError::PanelNotFound(panel))?;
+        let previous = self.panel_of(unit);
+        self.assignments.insert(unit, panel);
+        Ok(previous)
+    }
+
+    /// Drops the assignment of a unit that no longer exists.
+    pub fn forget(&mut self, unit: u64) {
+        self.assignments.remove(&unit);
+    }
+
+    /// Units with an explicit assignment and their panel, in ascending unit order.
+    pub fn assignments(&self) -> impl Iterator<Item = (u64, u16)> + '_ {
+        self.assignments.iter().map(|(unit, panel)| (*unit, *panel))
+    }
+
+    pub fn is_linked(&self, a: u16, b: u16) -> bool {
+        self.links.contains(&NetworkLink::new(a, b))
+    }
+
+    pub fn add_link(&mut self, a: u16, b: u16) -> Result<(), NetworkError> {
+        for number in [a, b] {
+            self.get_panel(number)
+                .ok_or(NetworkError::PanelNotFound(number))?;
+        }
+        if a == b {
+            return Err(NetworkError::SelfLink(a));
+        }
+        let link = NetworkLink::new(a, b);
+        if self.links.contains(&link) {
+            return Err(NetworkError::LinkExists {
+                a: link.a,
+                b: link.b,
+            });
+        }
+        self.links.push(link);
+        self.links.sort();
+        Ok(())
+    }
+
+    pub fn remove_link(&mut self, a: u16, b: u16) -> Result<(), NetworkError> {
+        let link = NetworkLink::new(a, b);
+        if !self.links.contains(&link) {
+            return Err(NetworkError::LinkNotFound {
+                a: link.a,
+                b: link.b,
+            });
+        }
+        self.links.retain(|other| *other != link);
+        Ok(())
+    }
+
+    /// Every panel `panel` can reach over the network, including itself.
+    pub fn reachable_from(&self, panel: u16) -> BTreeSet<u16> {
+        let mut reached = BTreeSet::from([panel]);
+        let mut pending = vec![panel];
+        while let Some(current) = pending.pop() {
+            for next in self.links.iter().filter_map(|link| link.other_end(current)) {
+                if reached.insert(next) {
+                    pending.push(next);
+                }
+            }
+        }
+        reached
+    }
+
+    /// Whether events from panel `a` reach panel `b`, directly or through other panels.
+    pub fn is_connected(&self, a: u16, b: u16) -> bool {
+        self.reachable_from(a).contains(&b)
+    }
+}
+
+impl Default for Network {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panels_are_numbered_and_named() {
+        let mut network = Network::new();
+        assert_eq!(network.main_panel(), 1);
+
+        let second = network.add_panel("  Annex ").unwrap();
+        assert_eq!(second, 2);
+        assert_eq!(network.get_panel(2).unwrap().name(), "Annex");
+        assert_eq!(
+            network.add_panel("Annex"),
+            Err(NetworkError::DuplicateName { other: 2 })
+        );
+        assert_eq!(network.add_panel(" "), Err(NetworkError::NameEmpty));
+        assert_eq!(
+            network.rename_panel(2, "Panel 1"),
+            Err(NetworkError::DuplicateName { other: 1 })
+        );
+        network.rename_panel(2, "Car park").unwrap();
+        assert_eq!(network.get_panel(2).unwrap().to_string(), "P2 Car park");
+
+        network.remove_panel(1).unwrap();
+        assert_eq!(network.main_panel(), 2);
+        assert_eq!(network.remove_panel(2), Err(NetworkError::LastPanel));
+        assert_eq!(network.remove_panel(7), Err(NetworkError::PanelNotFound(7)));
+    }
+
+    #[test]
+    fn test_units_belong_to_the_main_panel_until_assigned() {
+        let mut network = Network::new();
+        network.add_panel("Annex").unwrap();
+
+        assert_eq!(network.panel_of(5), 1);
+        assert_eq!(network.assign(5, 2), Ok(1));
+        assert_eq!(network.panel_of(5), 2);
+        assert_eq!(network.assign(5, 9), Err(NetworkError::PanelNotFound(9)));
+
+        // Removing a panel hands its units back to the main panel
+        network.remove_panel(2).unwrap();
+        assert_eq!(network.panel_of(5), 1);
+    }
+
+    #[test]
+    fn test_links_connect_panels_through_each_other() {
+        let mut network = Network::new();
+        for name in ["P2", "P3", "P4"] {
+            network.add_panel(name).unwrap();
+        }
+        network.add_link(2, 1).unwrap();
+        network.add_link(2, 3).unwrap();
+
+        assert_eq!(network.links()[0], NetworkLink { a: 1, b: 2 });
+        assert!(network.is_linked(1, 2));
+        assert!(network.is_connected(1, 3));
+        assert!(!network.is_connected(1, 4));
+        assert_eq!(network.reachable_from(3), BTreeSet::from([1, 2, 3]));
+        assert_eq!(
+            network.add_link(1, 2),
+            Err(NetworkError::LinkExists { a: 1, b: 2 })
+        );
+        assert_eq!(network.add_link(4, 4), Err(NetworkError::SelfLink(4)));
+        assert_eq!(network.add_link(1, 8), Err(NetworkError::PanelNotFound(8)));
+
+        network.remove_panel(2).unwrap();
+        assert!(network.links().is_empty());
+        assert_eq!(
+            network.remove_link(1, 3),
+            Err(NetworkError::LinkNotFound { a: 1, b: 3 })
+        );
+    }
+}
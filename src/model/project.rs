@@ -0,0 +1,774 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    DetailError, InputLogic, LoopAddress, UnitKind, UnitStore, MAX_LOOP_ADDRESS,
+    MAX_PANEL_NAME_LENGTH,
+};
+
+/// The project file format version written by this build.
+///
+/// Bump it whenever the serialized shape of [`UnitStore`] changes, and keep reading older versions where possible.
+///
+/// Version 2 added unit properties, loops and addresses, zoning thresholds, commissioning details and panels. Version 1
+/// files load with the defaults for all of them.
+pub const PROJECT_FORMAT_VERSION: u32 = 2;
+
+/// Reasons a project file cannot be loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProjectError {
+    /// The file is not valid JSON or does not have the expected shape. Carries the parser message with line and column.
+    Parse(String),
+    UnsupportedVersion {
+        found: u32,
+        supported: u32,
+    },
+    MissingRoot,
+    RootNotFirst,
+    RootHasParent,
+    ExtraRoot(u64),
+    DuplicateId(u64),
+    NextIdTooLow {
+        next_id: u64,
+        max_id: u64,
+    },
+    MissingParent {
+        unit: u64,
+    },
+    UnknownParent {
+        unit: u64,
+        parent: u64,
+    },
+    ParentCycle(u64),
+    InvalidParent {
+        unit: u64,
+        parent: u64,
+    },
+    InvalidMembership {
+        member: u64,
+        zone: u64,
+    },
+    UnknownRuleUnit {
+        rule: u64,
+        unit: u64,
+    },
+    RuleNextIdTooLow {
+        next_id: u64,
+        max_id: u64,
+    },
+    InvalidRuleLogic(u64),
+    InvalidLoop(u16),
+    InvalidAddress {
+        unit: u64,
+        address: LoopAddress,
+    },
+    DuplicateAddress {
+        unit: u64,
+        address: LoopAddress,
+    },
+    InvalidDetail {
+        unit: u64,
+        error: DetailError,
+    },
+    NoPanels,
+    InvalidPanel(u16),
+    InvalidPanelAssignment {
+        unit: u64,
+        panel: u16,
+    },
+    InvalidNetworkLink {
+        a: u16,
+        b: u16,
+    },
+}
+
+impl fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectError::Parse(message) => write!(f, "invalid project file: {}", message),
+            ProjectError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "project file version {} is newer than the supported version {}",
+                found, supported
+            ),
+            ProjectError::MissingRoot => write!(f, "the Root unit with id 0 is missing"),
+            ProjectError::RootNotFirst => write!(f, "the Root unit must be the first unit"),
+            ProjectError::RootHasParent => write!(f, "the Root unit must not have a parent"),
+            ProjectError::ExtraRoot(id) => write!(f, "unit {} is a second Root unit", id),
+            ProjectError::DuplicateId(id) => write!(f, "unit id {} is used more than once", id),
+            ProjectError::NextIdTooLow { next_id, max_id } => write!(
+                f,
+                "next id {} must be greater than the highest unit id {}",
+                next_id, max_id
+            ),
+            ProjectError::MissingParent { unit } => write!(f, "unit {} has no parent", unit),
+            ProjectError::UnknownParent { unit, parent } => write!(
+                f,
+                "unit {} has parent {} which does not exist",
+                unit, parent
+            ),
+            ProjectError::ParentCycle(id) => {
+                write!(f, "unit {} is part of a parent cycle", id)
+            }
+            ProjectError::InvalidParent { unit, parent } => write!(
+                f,
+                "unit {} cannot sit below unit {}; zones sit directly under the Root and have no units below them",
+                unit, parent
+            ),
+            ProjectError::InvalidMembership { member, zone } => {
+                write!(f, "unit {} cannot be a member of zone {}", member, zone)
+            }
+            ProjectError::UnknownRuleUnit { rule, unit } => write!(
+                f,
+                "rule {} refers to unit {} which does not exist or has the wrong kind",
+                rule, unit
+            ),
+            ProjectError::RuleNextIdTooLow { next_id, max_id } => write!(
+                f,
+                "next rule id {} must be greater than the highest rule id {}",
+                next_id, max_id
+            ),
+            ProjectError::InvalidRuleLogic(rule) => write!(
+                f,
+                "rule {} is a coincidence of zero inputs, it must require at least one",
+                rule
+            ),
+            ProjectError::InvalidLoop(number) => {
+                write!(
+                    f,
+                    "loop {} is defined twice or has an invalid capacity",
+                    number
+                )
+            }
+            ProjectError::InvalidAddress { unit, address } => write!(
+                f,
+                "unit {} has address {} which is not a valid address for it",
+                unit, address
+            ),
+            ProjectError::DuplicateAddress { unit, address } => write!(
+                f,
+                "unit {} has address {} which is already used by another unit",
+                unit, address
+            ),
+            ProjectError::InvalidDetail { unit, error } => write!(f, "unit {}: {}", unit, error),
+            ProjectError::NoPanels => write!(f, "the project has no panels"),
+            ProjectError::InvalidPanel(number) => write!(
+                f,
+                "panel {} is defined twice or has an invalid name",
+                number
+            ),
+            ProjectError::InvalidPanelAssignment { unit, panel } => write!(
+                f,
+                "unit {} is assigned to panel {} which does not exist or cannot own it",
+                unit, panel
+            ),
+            ProjectError::InvalidNetworkLink { a, b } => write!(
+                f,
+                "the network link between panels {} and {} is invalid or defined twice",
+                a, b
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProjectError {}
+
+#[derive(Serialize)]
+struct ProjectFileRef<'a> {
+    version: u32,
+    store: &'a UnitStore,
+}
+
+// The version was already checked through `VersionProbe`
+#[derive(Deserialize)]
+struct ProjectFile {
+    store: UnitStore,
+}
+
+// Only the version is read first, so a newer file is reported as such instead of as a shape mismatch
+#[derive(Deserialize)]
+struct VersionProbe {
+    version: u32,
+}
+
+/// Serializes the store into the versioned project file format.
+pub fn save_project(store: &UnitStore) -> String {
+    let file = ProjectFileRef {
+        version: PROJECT_FORMAT_VERSION,
+        store,
+    };
+    // None of the serialized types can fail to convert to JSON
+    serde_json::to_string_pretty(&file).expect("project serialization failed")
+}
+
+/// Parses and validates a project file.
+pub fn load_project(json: &str) -> Result<UnitStore, ProjectError> {
+    let probe: VersionProbe =
+        serde_json::from_str(json).map_err(|err| ProjectError::Parse(err.to_string()))?;
+    if probe.version > PROJECT_FORMAT_VERSION {
+        return Err(ProjectError::UnsupportedVersion {
+            found: probe.version,
+            supported: PROJECT_FORMAT_VERSION,
+        });
+    }
+
+    let file: ProjectFile =
+        serde_json::from_str(json).map_err(|err| ProjectError::Parse(err.to_string()))?;
+    validate_store(&file.store)?;
+    Ok(file.store)
+}
+
+/// Checks the invariants [`UnitStore`] relies on but cannot enforce while deserializing.
+pub fn validate_store(store: &UnitStore) -> Result<(), ProjectError> {
+    let units = store.get_all_units();
+
+    let root = units.first().ok_or(ProjectError::MissingRoot)?;
+    if root.id() != 0 || root.kind() != UnitKind::Root {
+        return Err(if units.iter().any(|unit| unit.id() == 0) {
+            ProjectError::RootNotFirst
+        } else {
+            ProjectError::MissingRoot
+        });
+    }
+    if root.parent().is_some() {
+        return Err(ProjectError::RootHasParent);
+    }
+
+    let mut kinds = BTreeMap::new();
+    for unit in units {
+        if kinds.insert(unit.id(), unit.kind()).is_some() {
+            return Err(ProjectError::DuplicateId(unit.id()));
+        }
+        if unit.id() != 0 && unit.kind() == UnitKind::Root {
+            return Err(ProjectError::ExtraRoot(unit.id()));
+        }
+        unit.check_details()
+            .map_err(|error| ProjectError::InvalidDetail {
+                unit: unit.id(),
+                error,
+            })?;
+    }
+
+    let max_id = units.iter().map(|unit| unit.id()).max().unwrap_or(0);
+    if store.next_id() <= max_id {
+        return Err(ProjectError::NextIdTooLow {
+            next_id: store.next_id(),
+            max_id,
+        });
+    }
+
+    for unit in units.iter().skip(1) {
+        let parent = unit
+            .parent()
+            .ok_or(ProjectError::MissingParent { unit: unit.id() })?;
+        if !kinds.contains_key(&parent) {
+            return Err(ProjectError::UnknownParent {
+                unit: unit.id(),
+                parent,
+            });
+        }
+    }
+
+    // Walking up from every unit must reach the Root without visiting a unit twice
+    for unit in units.iter().skip(1) {
+        let mut visited = HashSet::new();
+        let mut current = unit.parent();
+        while let Some(id) = current {
+            if !visited.insert(id) {
+                return Err(ProjectError::ParentCycle(unit.id()));
+            }
+            current = store.get_unit(id).and_then(|parent| parent.parent());
+        }
+    }
+
+    for unit in units.iter().skip(1) {
+        let parent = unit.parent().unwrap_or(0);
+        if kinds[&parent].is_zone() || (unit.kind().is_zone() && parent != 0) {
+            return Err(ProjectError::InvalidParent {
+                unit: unit.id(),
+                parent,
+            });
+        }
+    }
+
+    for (member, zone) in store.membership().iter() {
+        let member_kind = kinds.get(&member);
+        let zone_kind = kinds.get(&zone);
+        let valid = match (member_kind, zone_kind) {
+            (Some(member_kind), Some(zone_kind)) => member_kind.zone_kind() == Some(*zone_kind),
+            _ => false,
+        };
+        if !valid {
+            return Err(ProjectError::InvalidMembership { member, zone });
+        }
+    }
+
+    let matrix = store.cause_effect();
+    let max_rule_id = matrix
+        .rules()
+        .iter()
+        .map(|rule| rule.id())
+        .max()
+        .unwrap_or(0);
+    if matrix.next_id() <= max_rule_id {
+        return Err(ProjectError::RuleNextIdTooLow {
+            next_id: matrix.next_id(),
+            max_id: max_rule_id,
+        });
+    }
+    for rule in matrix.rules() {
+        if rule.logic() == InputLogic::Coincidence(0) {
+            return Err(ProjectError::InvalidRuleLogic(rule.id()));
+        }
+        for input in rule.inputs() {
+            if !kinds.get(input).is_some_and(|kind| kind.is_cause()) {
+                return Err(ProjectError::UnknownRuleUnit {
+                    rule: rule.id(),
+                    unit: *input,
+                });
+            }
+        }
+        for output in rule.outputs() {
+            if !kinds.get(output).is_some_and(|kind| kind.is_effect()) {
+                return Err(ProjectError::UnknownRuleUnit {
+                    rule: rule.id(),
+                    unit: *output,
+                });
+            }
+        }
+    }
+
+    let mut loops = BTreeMap::new();
+    for l in store.loops() {
+        if l.capacity() == 0
+            || l.capacity() > MAX_LOOP_ADDRESS
+            || loops.insert(l.number(), l.capacity()).is_some()
+        {
+            return Err(ProjectError::InvalidLoop(l.number()));
+        }
+    }
+
+    let mut addresses = HashSet::new();
+    for unit in units {
+        let Some(address) = unit.address() else {
+            continue;
+        };
+        let in_range = loops
+            .get(&address.loop_number)
+            .is_some_and(|capacity| (1..=*capacity).contains(&address.address));
+        if !unit.kind().is_addressable() || !in_range {
+            return Err(ProjectError::InvalidAddress {
+                unit: unit.id(),
+                address,
+            });
+        }
+        if !addresses.insert(address) {
+            return Err(ProjectError::DuplicateAddress {
+                unit: unit.id(),
+                address,
+            });
+        }
+    }
+
+    let network = store.network();
+    if network.panels().is_empty() {
+        return Err(ProjectError::NoPanels);
+    }
+    let mut panels = BTreeMap::new();
+    for panel in network.panels() {
+        let name = panel.name();
+        let valid_name = !name.trim().is_empty() && name.chars().count() <= MAX_PANEL_NAME_LENGTH;
+        if !valid_name || panels.insert(panel.number(), name).is_some() {
+            return Err(ProjectError::InvalidPanel(panel.number()));
+        }
+    }
+    let mut names = HashSet::new();
+    for panel in network.panels() {
+        if !names.insert(panel.name()) {
+            return Err(ProjectError::InvalidPanel(panel.number()));
+        }
+    }
+    for (unit, panel) in network.assignments() {
+        if unit == 0 || !kinds.contains_key(&unit) || !panels.contains_key(&panel) {
+            return Err(ProjectError::InvalidPanelAssignment { unit, panel });
+        }
+    }
+    // Units mounted below another unit belong to its panel
+    for unit in units.iter().skip(1) {
+        let parent = unit.parent().unwrap_or(0);
+        let panel = network.panel_of(unit.id());
+        if parent != 0 && network.panel_of(parent) != panel {
+            return Err(ProjectError::InvalidPanelAssignment {
+                unit: unit.id(),
+                panel,
+            });
+        }
+    }
+    let mut links = HashSet::new();
+    for link in network.links() {
+        let valid = link.a < link.b
+            && panels.contains_key(&link.a)
+            && panels.contains_key(&link.b)
+            && links.insert(*link);
+        if !valid {
+            return Err(ProjectError::InvalidNetworkLink {
+                a: link.a,
+                b: link.b,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Location;
+    use serde_json::Value;
+
+    fn sample_store() -> UnitStore {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let removed = store.add_unit(UnitKind::Detector, "Detector 2");
+        store.add_loop(10).unwrap();
+        store.auto_address(det).unwrap();
+        let dz = store.add_unit(UnitKind::DetectionZone, "Detection Zone 1");
+        let out = store.add_unit(UnitKind::Output, "Output 1");
+        store.assign_to_zone(det, dz).unwrap();
+        let rule = store
+            .cause_effect_mut()
+            .add_rule("Rule 1", InputLogic::Coincidence(2))
+            .unwrap();
+        store.set_rule_input(rule, dz, true).unwrap();
+        store.set_rule_output(rule, out, true).unwrap();
+        store.remove_unit(removed);
+        let unit = store.get_unit_mut(det).unwrap();
+        unit.set_location(Location::new("Block A", "2", "201"))
+            .unwrap();
+        unit.set_install_date("2024-05-01".parse().ok());
+        unit.set_attribute("Manufacturer", "Apollo").unwrap();
+        let annex = store.add_panel("Annex").unwrap();
+        store.set_panel(out, annex).unwrap();
+        store.add_network_link(1, annex).unwrap();
+        store
+    }
+
+    // Loads `json` after applying `edit` to its parsed form
+    fn load_edited(edit: impl FnOnce(&mut Value)) -> Result<UnitStore, ProjectError> {
+        let mut value: Value = serde_json::from_str(&save_project(&sample_store())).unwrap();
+        edit(&mut value);
+        load_project(&value.to_string())
+    }
+
+    #[test]
+    fn test_round_trip_preserves_store() {
+        let store = sample_store();
+
+        let loaded = load_project(&save_project(&store)).unwrap();
+
+        assert_eq!(loaded, store);
+    }
+
+    #[test]
+    fn test_ids_are_not_reused_after_load() {
+        let store = sample_store();
+        let mut loaded = load_project(&save_project(&store)).unwrap();
+
+        // Detector 2 had id 2 and was removed before saving
+        let id = loaded.add_unit(UnitKind::Detector, "Detector 3");
+        assert_eq!(id, 5);
+    }
+
+    #[test]
+    fn test_saved_file_is_versioned() {
+        let value: Value = serde_json::from_str(&save_project(&UnitStore::new())).unwrap();
+
+        assert_eq!(value["version"], PROJECT_FORMAT_VERSION);
+    }
+
+    #[test]
+    fn test_rejects_invalid_json() {
+        let err = load_project("{ not json").unwrap_err();
+
+        assert!(matches!(err, ProjectError::Parse(message) if message.contains("line 1")));
+    }
+
+    #[test]
+    fn test_rejects_newer_version() {
+        let err = load_edited(|value| value["version"] = Value::from(99)).unwrap_err();
+
+        assert_eq!(
+            err,
+            ProjectError::UnsupportedVersion {
+                found: 99,
+                supported: PROJECT_FORMAT_VERSION
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejects_missing_root() {
+        let err = load_edited(|value| {
+            value["store"]["units"].as_array_mut().unwrap().remove(0);
+        })
+        .unwrap_err();
+
+        assert_eq!(err, ProjectError::MissingRoot);
+    }
+
+    #[test]
+    fn test_rejects_duplicate_ids() {
+        let err =
+            load_edited(|value| value["store"]["units"][2]["id"] = Value::from(1)).unwrap_err();
+
+        assert_eq!(err, ProjectError::DuplicateId(1));
+    }
+
+    #[test]
+    fn test_rejects_invalid_details() {
+        let err = load_edited(|value| {
+            value["store"]["units"][1]["serial_number"] = Value::from("SN 1");
+        })
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ProjectError::InvalidDetail {
+                unit: 1,
+                error: DetailError::ForbiddenCharacter {
+                    field: "serial number",
+                    character: ' '
+                }
+            }
+        );
+
+        let err = load_edited(|value| {
+            value["store"]["units"][1]["install_date"] = Value::from("2024-02-30");
+        })
+        .unwrap_err();
+        assert!(matches!(err, ProjectError::Parse(message) if message.contains("2024-02-30")));
+    }
+
+    #[test]
+    fn test_rejects_next_id_not_above_max_id() {
+        let err = load_edited(|value| value["store"]["next_id"] = Value::from(4)).unwrap_err();
+
+        assert_eq!(
+            err,
+            ProjectError::NextIdTooLow {
+                next_id: 4,
+                max_id: 4
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejects_unknown_parent() {
+        let err = load_edited(|value| value["store"]["units"][1]["parent"] = Value::from(77))
+            .unwrap_err();
+
+        assert_eq!(
+            err,
+            ProjectError::UnknownParent {
+                unit: 1,
+                parent: 77
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejects_parent_cycle() {
+        let err = load_edited(|value| {
+            value["store"]["units"][1]["parent"] = Value::from(3);
+            value["store"]["units"][2]["parent"] = Value::from(1);
+        })
+        .unwrap_err();
+
+        assert_eq!(err, ProjectError::ParentCycle(1));
+    }
+
+    #[test]
+    fn test_rejects_mounted_units_on_another_panel() {
+        let mut store = sample_store();
+        let sounder = store
+            .add_child(1, UnitKind::FireAlarmDevice, "Base Sounder")
+            .unwrap();
+        let annex = 2;
+        let mut value: Value = serde_json::from_str(&save_project(&store)).unwrap();
+        value["store"]["network"]["assignments"][sounder.to_string()] = Value::from(annex);
+
+        assert_eq!(
+            load_project(&value.to_string()),
+            Err(ProjectError::InvalidPanelAssignment {
+                unit: sounder,
+                panel: annex
+            })
+        );
+    }
+
+    #[test]
+    fn test_rejects_units_below_zones() {
+        let err =
+            load_edited(|value| value["store"]["units"][1]["parent"] = Value::from(3)).unwrap_err();
+
+        assert_eq!(err, ProjectError::InvalidParent { unit: 1, parent: 3 });
+    }
+
+    #[test]
+    fn test_rejects_dangling_rule_reference() {
+        let err = load_edited(|value| {
+            value["store"]["cause_effect"]["rules"][0]["outputs"] = Value::from(vec![2]);
+        })
+        .unwrap_err();
+
+        assert_eq!(err, ProjectError::UnknownRuleUnit { rule: 1, unit: 2 });
+    }
+
+    #[test]
+    fn test_rejects_next_rule_id_not_above_max_rule_id() {
+        let err = load_edited(|value| {
+            value["store"]["cause_effect"]["next_id"] = Value::from(1);
+        })
+        .unwrap_err();
+
+        assert_eq!(
+            err,
+            ProjectError::RuleNextIdTooLow {
+                next_id: 1,
+                max_id: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejects_coincidence_of_zero_inputs() {
+        let err = load_edited(|value| {
+            value["store"]["cause_effect"]["rules"][0]["logic"] =
+                serde_json::json!({ "Coincidence": 0 });
+        })
+        .unwrap_err();
+
+        assert_eq!(err, ProjectError::InvalidRuleLogic(1));
+    }
+
+    #[test]
+    fn test_rejects_address_collision() {
+        let err = load_edited(|value| {
+            value["store"]["units"][2]["address"] = value["store"]["units"][1]["address"].clone();
+        })
+        .unwrap_err();
+
+        assert_eq!(
+            err,
+            ProjectError::InvalidAddress {
+                unit: 3,
+                address: LoopAddress::new(1, 1)
+            }
+        );
+
+        let err = load_edited(|value| {
+            value["store"]["units"][3]["address"] = value["store"]["units"][1]["address"].clone();
+        })
+        .unwrap_err();
+
+        assert_eq!(
+            err,
+            ProjectError::DuplicateAddress {
+                unit: 4,
+                address: LoopAddress::new(1, 1)
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_loop_capacity() {
+        let err = load_edited(|value| value["store"]["loops"][0]["capacity"] = Value::from(0))
+            .unwrap_err();
+
+        assert_eq!(err, ProjectError::InvalidLoop(1));
+    }
+
+    #[test]
+    fn test_rejects_invalid_network() {
+        let err = load_edited(|value| {
+            value["store"]["network"]["assignments"]["4"] = Value::from(7);
+        })
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ProjectError::InvalidPanelAssignment { unit: 4, panel: 7 }
+        );
+
+        let err = load_edited(|value| {
+            value["store"]["network"]["links"][0]["b"] = Value::from(1);
+        })
+        .unwrap_err();
+        assert_eq!(err, ProjectError::InvalidNetworkLink { a: 1, b: 1 });
+
+        let err = load_edited(|value| {
+            value["store"]["network"]["panels"][1]["name"] = Value::from("Panel 1");
+        })
+        .unwrap_err();
+        assert_eq!(err, ProjectError::InvalidPanel(2));
+    }
+
+    #[test]
+    fn test_files_without_panels_have_one_panel() {
+        let loaded = load_edited(|value| {
+            value["store"].as_object_mut().unwrap().remove("network");
+        })
+        .unwrap();
+
+        assert_eq!(loaded.network().panels().len(), 1);
+        assert_eq!(loaded.panel_of(4), Some(1));
+    }
+
+    #[test]
+    fn test_units_without_properties_get_defaults() {
+        let loaded = load_edited(|value| {
+            for unit in value["store"]["units"].as_array_mut().unwrap() {
+                unit.as_object_mut().unwrap().remove("properties");
+            }
+        })
+        .unwrap();
+
+        assert_eq!(
+            loaded.get_unit(4).unwrap().properties(),
+            crate::model::UnitProperties::Output { delay_seconds: 0 }
+        );
+    }
+
+    #[test]
+    fn test_loads_version_1_files() {
+        let json = r#"{
+            "version": 1,
+            "store": {
+                "units": [
+                    { "id": 0, "kind": "Root", "name": "Root", "parent": null },
+                    { "id": 1, "kind": "Detector", "name": "Detector 1", "parent": 0 },
+                    { "id": 2, "kind": "DetectionZone", "name": "Detection Zone 1", "parent": 0 },
+                    { "id": 3, "kind": "Output", "name": "Output 1", "parent": 0 }
+                ],
+                "next_id": 4,
+                "membership": { "zones": { "1": 2 } },
+                "cause_effect": {
+                    "rules": [
+                        { "id": 1, "name": "Rule 1", "logic": "Any", "inputs": [2], "outputs": [3] }
+                    ],
+                    "next_id": 2
+                }
+            }
+        }"#;
+
+        let loaded = load_project(json).unwrap();
+
+        assert_eq!(loaded.count(), 3);
+        assert_eq!(loaded.zone_of(1), Some(2));
+        assert_eq!(loaded.cause_effect().rules().len(), 1);
+        assert_eq!(loaded.get_unit(1).unwrap().address(), None);
+        assert_eq!(loaded.network().panels().len(), 1);
+        assert_eq!(loaded.panel_of(3), Some(1));
+    }
+}
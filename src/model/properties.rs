@@ -0,0 +1,206 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::UnitKind;
+
+/// The longest activation delay an output may have, in seconds.
+pub const MAX_OUTPUT_DELAY_SECONDS: u32 = 600;
+
+/// The sensing principle of a detector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DetectorType {
+    #[default]
+    OpticalSmoke,
+    Heat,
+    MultiSensor,
+    Beam,
+}
+
+impl DetectorType {
+    pub const ALL: [DetectorType; 4] = [
+        DetectorType::OpticalSmoke,
+        DetectorType::Heat,
+        DetectorType::MultiSensor,
+        DetectorType::Beam,
+    ];
+}
+
+impl fmt::Display for DetectorType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DetectorType::OpticalSmoke => "Optical Smoke",
+            DetectorType::Heat => "Heat",
+            DetectorType::MultiSensor => "Multi-sensor",
+            DetectorType::Beam => "Beam",
+        })
+    }
+}
+
+/// How a fire alarm device warns occupants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AlarmDeviceType {
+    #[default]
+    Sounder,
+    Beacon,
+    SounderBeacon,
+    VoiceSounder,
+}
+
+impl AlarmDeviceType {
+    pub const ALL: [AlarmDeviceType; 4] = [
+        AlarmDeviceType::Sounder,
+        AlarmDeviceType::Beacon,
+        AlarmDeviceType::SounderBeacon,
+        AlarmDeviceType::VoiceSounder,
+    ];
+
+    /// Whether the device sounds, as opposed to only flashing.
+    pub fn is_audible(&self) -> bool {
+        !matches!(self, AlarmDeviceType::Beacon)
+    }
+}
+
+impl fmt::Display for AlarmDeviceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AlarmDeviceType::Sounder => "Sounder",
+            AlarmDeviceType::Beacon => "Beacon",
+            AlarmDeviceType::SounderBeacon => "Sounder Beacon",
+            AlarmDeviceType::VoiceSounder => "Voice Sounder",
+        })
+    }
+}
+
+/// Reasons a set of properties is refused.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyError {
+    WrongKind { expected: UnitKind },
+    DelayTooLong { seconds: u32, max: u32 },
+}
+
+impl fmt::Display for PropertyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PropertyError::WrongKind { expected } => {
+                write!(f, "these properties belong to a {}", expected)
+            }
+            PropertyError::DelayTooLong { seconds, max } => write!(
+                f,
+                "a delay of {} s is longer than the maximum of {} s",
+                seconds, max
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PropertyError {}
+
+/// Settings that only make sense for one kind of unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum UnitProperties {
+    /// Kinds without specific settings.
+    #[default]
+    None,
+    Detector {
+        detector_type: DetectorType,
+    },
+    FireAlarmDevice {
+        device_type: AlarmDeviceType,
+    },
+    Output {
+        /// Seconds between the output being driven and it operating.
+        delay_seconds: u32,
+    },
+    DetectionZone {
+        /// The floor area the zone covers in square metres. 0 when not known yet.
+        floor_area_m2: u32,
+        /// The number of exits from the zone, each of which needs a manual call point.
+        exits: u32,
+    },
+}
+
+impl UnitProperties {
+    /// The properties a new unit of `kind` starts with.
+    pub fn default_for(kind: UnitKind) -> Self {
+        match kind {
+            UnitKind::Detector => UnitProperties::Detector {
+                detector_type: DetectorType::default(),
+            },
+            UnitKind::FireAlarmDevice => UnitProperties::FireAlarmDevice {
+                device_type: AlarmDeviceType::default(),
+            },
+            UnitKind::Output => UnitProperties::Output { delay_seconds: 0 },
+            UnitKind::DetectionZone => UnitProperties::DetectionZone {
+                floor_area_m2: 0,
+                exits: 0,
+            },
+            _ => UnitProperties::None,
+        }
+    }
+
+    /// Whether these properties are the right shape for `kind`.
+    pub fn fits(&self, kind: UnitKind) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(&Self::default_for(kind))
+    }
+
+    /// Checks the properties against `kind` and their own limits.
+    pub fn validate(&self, kind: UnitKind) -> Result<(), PropertyError> {
+        if !self.fits(kind) {
+            return Err(PropertyError::WrongKind { expected: kind });
+        }
+        if let UnitProperties::Output { delay_seconds } = self {
+            if *delay_seconds > MAX_OUTPUT_DELAY_SECONDS {
+                return Err(PropertyError::DelayTooLong {
+                    seconds: *delay_seconds,
+                    max: MAX_OUTPUT_DELAY_SECONDS,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_for_matches_kind() {
+        for kind in UnitKind::CONFIGURABLE {
+            assert!(UnitProperties::default_for(kind).fits(kind));
+        }
+        assert_eq!(
+            UnitProperties::default_for(UnitKind::AlarmZone),
+            UnitProperties::None
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_wrong_kind() {
+        let props = UnitProperties::Output { delay_seconds: 0 };
+
+        assert_eq!(
+            props.validate(UnitKind::Detector),
+            Err(PropertyError::WrongKind {
+                expected: UnitKind::Detector
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_limits_output_delay() {
+        let ok = UnitProperties::Output {
+            delay_seconds: MAX_OUTPUT_DELAY_SECONDS,
+        };
+        let too_long = UnitProperties::Output {
+            delay_seconds: MAX_OUTPUT_DELAY_SECONDS + 1,
+        };
+
+        assert_eq!(ok.validate(UnitKind::Output), Ok(()));
+        assert!(matches!(
+            too_long.validate(UnitKind::Output),
+            Err(PropertyError::DelayTooLong { .. })
+        ));
+    }
+}
@@ -0,0 +1,285 @@
+use std::cmp::{Ordering, Reverse};
+
+use super::{Unit, UnitKind, UnitStore};
+
+/// The column a unit list is sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    /// The order units were added in.
+    #[default]
+    Added,
+    Name,
+    Kind,
+    Zone,
+    Address,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 5] = [
+        SortKey::Added,
+        SortKey::Name,
+        SortKey::Kind,
+        SortKey::Zone,
+        SortKey::Address,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortKey::Added => "#",
+            SortKey::Name => "Name",
+            SortKey::Kind => "Kind",
+            SortKey::Zone => "Zone",
+            SortKey::Address => "Address",
+        }
+    }
+}
+
+/// Restricts a unit list to the members of one zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZoneFilter {
+    /// Units that can join a zone but have not.
+    Unassigned,
+    Zone(u64),
+}
+
+/// Restricts a unit list to the devices on one loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopFilter {
+    /// Addressable devices without an address.
+    Unaddressed,
+    Loop(u16),
+}
+
+/// Search text, filters and sort order for a list of units.
+///
+/// Every filter that is set must match. The Root unit is never listed.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct UnitQuery {
+    /// Matched against unit names, ignoring case. Empty matches everything.
+    pub text: String,
+    /// Also match names that contain the letters of `text` in order with gaps, e.g. "dtc1" finds "Detector 1".
+    pub fuzzy: bool,
+    pub kind: Option<UnitKind>,
+    pub zone: Option<ZoneFilter>,
+    pub loop_filter: Option<LoopFilter>,
+    /// Only units owned by this panel.
+    pub panel: Option<u16>,
+    pub sort: SortKey,
+    pub descending: bool,
+}
+
+impl UnitQuery {
+    /// Whether `unit` passes the search text and every filter.
+    pub fn matches(&self, store: &UnitStore, unit: &Unit) -> bool {
+        if unit.kind() == UnitKind::Root {
+            return false;
+        }
+        if self.kind.is_some_and(|kind| unit.kind() != kind) {
+            return false;
+        }
+        if self.panel.is_some() && store.panel_of(unit.id()) != self.panel {
+            return false;
+        }
+        let zone_matches = match self.zone {
+            None => true,
+            Some(ZoneFilter::Unassigned) => {
+                unit.kind().zone_kind().is_some() && store.zone_of(unit.id()).is_none()
+            }
+            Some(ZoneFilter::Zone(zone)) => store.zone_of(unit.id()) == Some(zone),
+        };
+        let loop_matches = match self.loop_filter {
+            None => true,
+            Some(LoopFilter::Unaddressed) => {
+                unit.kind().is_addressable() && unit.address().is_none()
+            }
+            Some(LoopFilter::Loop(number)) => {
+                unit.address().map(|address| address.loop_number) == Some(number)
+            }
+        };
+        zone_matches && loop_matches && self.matches_text(unit.name())
+    }
+
+    fn matches_text(&self, name: &str) -> bool {
+        let text = self.text.trim();
+        if text.is_empty() {
+            return true;
+        }
+        let name = name.to_lowercase();
+        let text = text.to_lowercase();
+        name.contains(&text) || (self.fuzzy && fuzzy_match(&text, &name))
+    }
+
+    /// The matching units in the requested order. Ties keep the order units were added in.
+    pub fn run<'a>(&self, store: &'a UnitStore) -> Vec<&'a Unit> {
+        let mut units: Vec<&Unit> = store
+            .get_all_units()
+            .iter()
+            .filter(|unit| self.matches(store, unit))
+            .collect();
+
+        let zone_name = |unit: &Unit| {
+            store
+                .zone_of(unit.id())
+                .and_then(|zone| store.get_unit(zone))
+                .map(|zone| zone.name().to_lowercase())
+        };
+        let descending = self.descending;
+        let ordering = |a: &&Unit, b: &&Unit| -> Ordering {
+            let ordering = match self.sort {
+                // Names are sorted by a cached key below
+                SortKey::Added | SortKey::Name => Ordering::Equal,
+                SortKey::Kind => a.kind().cmp(&b.kind()),
+                SortKey::Zone => return missing_last(zone_name(a), zone_name(b), descending),
+                SortKey::Address => return missing_last(a.address(), b.address(), descending),
+            };
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        };
+        match self.sort {
+            // Lowercases each name once instead of on every comparison
+            SortKey::Name if descending => {
+                units.sort_by_cached_key(|unit| Reverse(unit.name().to_lowercase()))
+            }
+            SortKey::Name => units.sort_by_cached_key(|unit| unit.name().to_lowercase()),
+            _ => units.sort_by(ordering),
+        }
+        if self.sort == SortKey::Added && descending {
+            units.reverse();
+        }
+        units
+    }
+
+    /// Sorts by `key`, or flips the direction if the list is already sorted by it.
+    pub fn toggle_sort(&mut self, key: SortKey) {
+        if self.sort == key {
+            self.descending = !self.descending;
+        } else {
+            self.sort = key;
+            self.descending = false;
+        }
+    }
+}
+
+// Compares optional sort values so units without a zone or address go last whichever way the list is sorted
+fn missing_last<T: Ord>(a: Option<T>, b: Option<T>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if descending => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Whether every character of `pattern` appears in `text` in the same order, not necessarily next to each other.
+pub fn fuzzy_match(pattern: &str, text: &str) -> bool {
+    let mut text = text.chars();
+    pattern
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .all(|c| text.any(|t| t == c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(units: &[&Unit]) -> Vec<String> {
+        units.iter().map(|unit| unit.name().to_string()).collect()
+    }
+
+    fn sample_store() -> UnitStore {
+        let mut store = UnitStore::new();
+        let zone = store.add_unit(UnitKind::DetectionZone, "Basement");
+        let kitchen = store.add_unit(UnitKind::Detector, "Kitchen");
+        store.add_unit(UnitKind::Detector, "Detector 1");
+        let hall = store.add_unit(UnitKind::ManualCallPoint, "Hall");
+        store.assign_to_zone(kitchen, zone).unwrap();
+        store.assign_to_zone(hall, zone).unwrap();
+        store.add_loop(10).unwrap();
+        store.auto_address(hall).unwrap();
+        store.auto_address(kitchen).unwrap();
+        store
+    }
+
+    #[test]
+    fn test_substring_and_fuzzy_search() {
+        let store = sample_store();
+        let mut query = UnitQuery {
+            text: "TECT".to_string(),
+            ..UnitQuery::default()
+        };
+
+        assert_eq!(names(&query.run(&store)), vec!["Detector 1"]);
+
+        query.text = "dtc1".to_string();
+        assert!(query.run(&store).is_empty());
+        query.fuzzy = true;
+        assert_eq!(names(&query.run(&store)), vec!["Detector 1"]);
+    }
+
+    #[test]
+    fn test_filters_combine() {
+        let store = sample_store();
+        let query = UnitQuery {
+            kind: Some(UnitKind::Detector),
+            zone: Some(ZoneFilter::Zone(1)),
+            ..UnitQuery::default()
+        };
+        assert_eq!(names(&query.run(&store)), vec!["Kitchen"]);
+
+        let query = UnitQuery {
+            zone: Some(ZoneFilter::Unassigned),
+            ..UnitQuery::default()
+        };
+        assert_eq!(names(&query.run(&store)), vec!["Basement", "Detector 1"]);
+
+        let query = UnitQuery {
+            loop_filter: Some(LoopFilter::Unaddressed),
+            ..UnitQuery::default()
+        };
+        assert_eq!(names(&query.run(&store)), vec!["Detector 1"]);
+    }
+
+    #[test]
+    fn test_sort_by_name_and_address() {
+        let store = sample_store();
+        let mut query = UnitQuery::default();
+        assert_eq!(
+            names(&query.run(&store)),
+            vec!["Basement", "Kitchen", "Detector 1", "Hall"]
+        );
+
+        query.toggle_sort(SortKey::Name);
+        assert_eq!(
+            names(&query.run(&store)),
+            vec!["Basement", "Detector 1", "Hall", "Kitchen"]
+        );
+        query.toggle_sort(SortKey::Name);
+        assert!(query.descending);
+        assert_eq!(names(&query.run(&store))[0], "Kitchen");
+
+        query.toggle_sort(SortKey::Address);
+        assert!(!query.descending);
+        assert_eq!(
+            names(&query.run(&store)),
+            vec!["Hall", "Kitchen", "Basement", "Detector 1"]
+        );
+        query.toggle_sort(SortKey::Address);
+        assert_eq!(
+            names(&query.run(&store)),
+            vec!["Kitchen", "Hall", "Basement", "Detector 1"]
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert!(fuzzy_match("dz1", "detection zone 1"));
+        assert!(fuzzy_match("", "anything"));
+        assert!(!fuzzy_match("zd", "detection zone"));
+        assert!(!fuzzy_match("x", ""));
+    }
+}
@@ -0,0 +1,450 @@
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Unit, UnitKind, UnitProperties, UnitStore};
+
+/// The document formats a [`Report`] can be rendered to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReportFormat {
+    /// A standalone page with its own print styles.
+    Html,
+    Markdown,
+}
+
+impl ReportFormat {
+    pub const ALL: [ReportFormat; 2] = [ReportFormat::Html, ReportFormat::Markdown];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReportFormat::Html => "HTML",
+            ReportFormat::Markdown => "Markdown",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Html => "html",
+            ReportFormat::Markdown => "md",
+        }
+    }
+
+    pub fn mime(&self) -> &'static str {
+        match self {
+            ReportFormat::Html => "text/html",
+            ReportFormat::Markdown => "text/markdown",
+        }
+    }
+}
+
+/// A table in a report. Every row has one cell per header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl ReportTable {
+    fn new(headers: &[&str]) -> Self {
+        Self {
+            headers: headers.iter().map(|header| header.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+}
+
+/// A headed part of a report: some lines of text followed by an optional table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportSection {
+    pub heading: String,
+    /// 2 for top-level sections, 3 for sections nested under them.
+    pub level: u8,
+    pub notes: Vec<String>,
+    pub table: Option<ReportTable>,
+}
+
+impl ReportSection {
+    fn new(heading: String, level: u8) -> Self {
+        Self {
+            heading,
+            level,
+            notes: Vec::new(),
+            table: None,
+        }
+    }
+}
+
+/// The device schedule and commissioning report for a configuration.
+///
+/// Holds the content as sections so it can be rendered to any [`ReportFormat`]: totals per kind, one device table per
+/// zone, the loop and address listing, the cause and effect rules and a sign-off block for the commissioning engineer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub title: String,
+    pub sections: Vec<ReportSection>,
+}
+
+impl Report {
+    pub fn new(title: &str, store: &UnitStore) -> Self {
+        let mut sections = vec![totals_section(store)];
+        sections.extend(zone_sections(store));
+        sections.extend(loop_sections(store));
+        sections.push(cause_effect_section(store));
+        sections.push(sign_off_section());
+        Self {
+            title: title.to_string(),
+            sections,
+        }
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Html => self.to_html(),
+            ReportFormat::Markdown => self.to_markdown(),
+        }
+    }
+
+    /// Renders a standalone HTML page with print-friendly styles and no external resources.
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        let title = escape_html(&self.title);
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+            title, REPORT_CSS, title
+        );
+        for section in &self.sections {
+            html.push_str("<section>\n");
+            let _ = writeln!(
+                html,
+                "<h{level}>{}</h{level}>",
+                escape_html(&section.heading),
+                level = section.level
+            );
+            for note in &section.notes {
+                let _ = writeln!(html, "<p>{}</p>", escape_html(note));
+            }
+            if let Some(table) = &section.table {
+                html.push_str("<table>\n<thead><tr>");
+                for header in &table.headers {
+                    let _ = write!(html, "<th>{}</th>", escape_html(header));
+                }
+                html.push_str("</tr></thead>\n<tbody>\n");
+                for row in &table.rows {
+                    html.push_str("<tr>");
+                    for cell in row {
+                        let _ = write!(html, "<td>{}</td>", escape_html(cell));
+                    }
+                    html.push_str("</tr>\n");
+                }
+                html.push_str("</tbody>\n</table>\n");
+            }
+            html.push_str("</section>\n");
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    /// Renders GitHub-flavoured Markdown with pipe tables.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# {}\n", self.title);
+        for section in &self.sections {
+            let _ = write!(
+                markdown,
+                "\n{} {}\n",
+                "#".repeat(section.level as usize),
+                section.heading
+            );
+            for note in &section.notes {
+                let _ = write!(markdown, "\n{}\n", note);
+            }
+            if let Some(table) = &section.table {
+                markdown.push('\n');
+                let _ = writeln!(markdown, "{}", markdown_row(&table.headers));
+                let _ = writeln!(markdown, "|{}", " --- |".repeat(table.headers.len()));
+                for row in &table.rows {
+                    let _ = writeln!(markdown, "{}", markdown_row(row));
+                }
+            }
+        }
+        markdown
+    }
+}
+
+const REPORT_CSS: &str = "body { font-family: sans-serif; margin: 2em; } \
+table { border-collapse: collapse; margin-bottom: 1em; } \
+th, td { border: 1px solid #999; padding: 4px 8px; text-align: left; } \
+section { break-inside: avoid; }";
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn markdown_row(cells: &[String]) -> String {
+    let cells: Vec<String> = cells
+        .iter()
+        .map(|cell| cell.replace('|', "\\|").replace('\n', " "))
+        .collect();
+    format!("| {} |", cells.join(" | "))
+}
+
+fn totals_section(store: &UnitStore) -> ReportSection {
+    let mut section = ReportSection::new("Totals".to_string(), 2);
+    let mut table = ReportTable::new(&["Kind", "Count"]);
+    for (kind, count) in store.counts_by_kind() {
+        table
+            .rows
+            .push(vec![kind.plural_label().to_string(), count.to_string()]);
+    }
+    table
+        .rows
+        .push(vec!["Total".to_string(), store.count().to_string()]);
+    section.table = Some(table);
+    section
+}
+
+fn zone_sections(store: &UnitStore) -> Vec<ReportSection> {
+    let mut sections = vec![ReportSection::new("Zones".to_string(), 2)];
+    for kind in [UnitKind::DetectionZone, UnitKind::AlarmZone] {
+        for zone in store.units_of_kind(kind) {
+            let mut section = ReportSection::new(format!("{} ({})", zone.name(), kind), 3);
+            if let Some(parent) = store.zone_of(zone.id()).and_then(|id| store.get_unit(id)) {
+                section.notes.push(format!("Part of {}", parent.name()));
+            }
+            if let UnitProperties::DetectionZone {
+                floor_area_m2,
+                exits,
+            } = zone.properties()
+            {
+                section.notes.push(format!(
+                    "Floor area: {} m², exits: {}",
+                    floor_area_m2, exits
+                ));
+            }
+            section.table = Some(device_table(store.members_of(zone.id())));
+            sections.push(section);
+        }
+    }
+
+    let unzoned: Vec<&Unit> = store
+        .get_all_units()
+        .iter()
+        .filter(|unit| unit.kind().is_addressable() && unit.kind().zone_kind().is_some())
+        .filter(|unit| store.zone_of(unit.id()).is_none())
+        .collect();
+    if !unzoned.is_empty() {
+        let mut section = ReportSection::new("Not in a zone".to_string(), 3);
+        section.table = Some(device_table(unzoned.into_iter()));
+        sections.push(section);
+    }
+    sections
+}
+
+fn device_table<'a>(units: impl Iterator<Item = &'a Unit>) -> ReportTable {
+    let mut table = ReportTable::new(&[
+        "Address",
+        "Name",
+        "Kind",
+        "Type",
+        "Location",
+        "Serial number",
+        "Tested",
+    ]);
+    let mut units: Vec<&Unit> = units.collect();
+    units.sort_by_key(|unit| (unit.address().is_none(), unit.address(), unit.id()));
+    for unit in units {
+        table.rows.push(vec![
+            address_of(unit),
+            unit.name().to_string(),
+            unit.kind().to_string(),
+            type_of(unit),
+            unit.location().to_string(),
+            unit.serial_number().to_string(),
+            // Left blank to be ticked off on paper
+            String::new(),
+        ]);
+    }
+    table
+}
+
+fn loop_sections(store: &UnitStore) -> Vec<ReportSection> {
+    let mut sections = vec![ReportSection::new("Loops".to_string(), 2)];
+    if store.loops().is_empty() {
+        sections[0].notes.push("No loops configured.".to_string());
+    }
+    for usage in store.loop_utilisation() {
+        let mut section = ReportSection::new(format!("Loop {}", usage.loop_number), 3);
+        section.notes.push(format!(
+            "{} of {} addresses used ({}%)",
+            usage.used,
+            usage.capacity,
+            usage.percent()
+        ));
+        let mut table = ReportTable::new(&["Address", "Name", "Kind", "Zone", "Isolator"]);
+        for unit in store.devices_on_loop(usage.loop_number) {
+            let zone = store
+                .zone_of(unit.id())
+                .and_then(|zone| store.get_unit(zone))
+                .map(|zone| zone.name().to_string())
+                .unwrap_or_default();
+            table.rows.push(vec![
+                address_of(unit),
+                unit.name().to_string(),
+                unit.kind().to_string(),
+                zone,
+                if unit.has_isolator() { "Yes" } else { "" }.to_string(),
+            ]);
+        }
+        section.table = Some(table);
+        sections.push(section);
+    }
+    sections
+}
+
+fn cause_effect_section(store: &UnitStore) -> ReportSection {
+    let mut section = ReportSection::new("Cause and Effect".to_string(), 2);
+    let rules = store.cause_effect().rules();
+    if rules.is_empty() {
+        section.notes.push("No rules configured.".to_string());
+        return section;
+    }
+    let names_of = |ids: &mut dyn Iterator<Item = &u64>| {
+        ids.filter_map(|id| store.get_unit(*id))
+            .map(|unit| unit.name().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut table = ReportTable::new(&["Rule", "Logic", "Causes", "Effects"]);
+    for rule in rules {
+        table.rows.push(vec![
+            rule.name().to_string(),
+            rule.logic().to_string(),
+            names_of(&mut rule.inputs().iter()),
+            names_of(&mut rule.outputs().iter()),
+        ]);
+    }
+    section.table = Some(table);
+    section
+}
+
+fn sign_off_section() -> ReportSection {
+    let mut section = ReportSection::new("Commissioning sign-off".to_string(), 2);
+    let mut table = ReportTable::new(&["", "Name", "Signature", "Date"]);
+    for role in ["Commissioning engineer", "Witness"] {
+        table.rows.push(vec![
+            role.to_string(),
+            String::new(),
+            String::new(),
+            String::new(),
+        ]);
+    }
+    section.table = Some(table);
+    section
+}
+
+fn address_of(unit: &Unit) -> String {
+    unit.address()
+        .map(|address| address.to_string())
+        .unwrap_or_default()
+}
+
+fn type_of(unit: &Unit) -> String {
+    match unit.properties() {
+        UnitProperties::Detector { detector_type } => detector_type.to_string(),
+        UnitProperties::FireAlarmDevice { device_type } => device_type.to_string(),
+        UnitProperties::Output { delay_seconds } if delay_seconds > 0 => {
+            format!("Delay {} s", delay_seconds)
+        }
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::InputLogic;
+
+    fn sample_store() -> UnitStore {
+        let mut store = UnitStore::new();
+        let dz = store.add_unit(UnitKind::DetectionZone, "Ground <Floor>");
+        let det = store.add_unit(UnitKind::Detector, "Kitchen | Hall");
+        store.add_unit(UnitKind::Output, "Door Release");
+        store.assign_to_zone(det, dz).unwrap();
+        store.add_loop(10).unwrap();
+        store.auto_address(det).unwrap();
+        let rule = store
+            .cause_effect_mut()
+            .add_rule("Release doors", InputLogic::Any)
+            .unwrap();
+        store.set_rule_input(rule, dz, true).unwrap();
+        store.set_rule_output(rule, 3, true).unwrap();
+        store
+    }
+
+    fn section<'a>(report: &'a Report, heading: &str) -> &'a ReportSection {
+        report
+            .sections
+            .iter()
+            .find(|section| section.heading == heading)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_totals_match_counts_by_kind() {
+        let store = sample_store();
+
+        let report = Report::new("Site", &store);
+
+        let table = section(&report, "Totals").table.as_ref().unwrap();
+        assert_eq!(table.rows.len(), store.counts_by_kind().len() + 1);
+        assert!(table
+            .rows
+            .contains(&vec!["Detectors".to_string(), "1".to_string()]));
+        assert_eq!(
+            table.rows.last().unwrap(),
+            &vec!["Total".to_string(), "3".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_zone_loop_and_rule_sections() {
+        let report = Report::new("Site", &sample_store());
+
+        let zone = section(&report, "Ground <Floor> (Detection Zone)");
+        assert_eq!(zone.table.as_ref().unwrap().rows[0][0], "L1.001");
+        assert!(!report
+            .sections
+            .iter()
+            .any(|section| section.heading == "Not in a zone"));
+        let loop_one = section(&report, "Loop 1");
+        assert_eq!(loop_one.notes, vec!["1 of 10 addresses used (10%)"]);
+        let rules = section(&report, "Cause and Effect").table.as_ref().unwrap();
+        assert_eq!(
+            rules.rows[0],
+            vec!["Release doors", "Any", "Ground <Floor>", "Door Release"]
+        );
+    }
+
+    #[test]
+    fn test_html_is_escaped_and_standalone() {
+        let html = Report::new("Site & Co", &sample_store()).to_html();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<h1>Site &amp; Co</h1>"));
+        assert!(html.contains("Ground &lt;Floor&gt; (Detection Zone)"));
+        assert!(!html.contains("<Floor>"));
+    }
+
+    #[test]
+    fn test_markdown_tables_escape_pipes() {
+        let markdown = Report::new("Site", &sample_store()).to_markdown();
+
+        assert!(markdown.starts_with("# Site\n"));
+        assert!(markdown.contains("\n## Totals\n"));
+        assert!(markdown.contains("| Kind | Count |\n| --- | --- |\n"));
+        assert!(markdown.contains("| L1.001 | Kitchen \\| Hall |"));
+    }
+}
@@ -0,0 +1,130 @@
+/// The units picked in a unit list, with the usual click, shift-click and ctrl-click behaviour.
+///
+/// Ids are kept in the order they were selected, which is the order bulk renames number them in.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Selection {
+    ids: Vec<u64>,
+    // Where the next shift-click range starts
+    anchor: Option<u64>,
+}
+
+impl Selection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ids(&self) -> &[u64] {
+        &self.ids
+    }
+
+    pub fn contains(&self, id: u64) -> bool {
+        self.ids.contains(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// The selected unit if exactly one is selected.
+    pub fn single(&self) -> Option<u64> {
+        match self.ids.as_slice() {
+            [id] => Some(*id),
+            _ => None,
+        }
+    }
+
+    /// A plain click: selects only `id`.
+    pub fn select_only(&mut self, id: u64) {
+        self.ids = vec![id];
+        self.anchor = Some(id);
+    }
+
+    /// A ctrl-click: adds `id`, or takes it out if it was already selected.
+    pub fn toggle(&mut self, id: u64) {
+        match self.ids.iter().position(|selected| *selected == id) {
+            Some(index) => {
+                self.ids.remove(index);
+            }
+            None => self.ids.push(id),
+        }
+        self.anchor = Some(id);
+    }
+
+    /// A shift-click: selects every unit of `order` from the last clicked unit to `id`. Without a last clicked unit in
+    /// `order` this is a plain click.
+    pub fn extend_to(&mut self, id: u64, order: &[u64]) {
+        let from = self
+            .anchor
+            .and_then(|anchor| order.iter().position(|listed| *listed == anchor));
+        let to = order.iter().position(|listed| *listed == id);
+        let (Some(from), Some(to)) = (from, to) else {
+            self.select_only(id);
+            return;
+        };
+        self.ids = if from <= to {
+            order[from..=to].to_vec()
+        } else {
+            order[to..=from].iter().rev().copied().collect()
+        };
+    }
+
+    /// Selects all of `ids` in order.
+    pub fn select_all(&mut self, ids: &[u64]) {
+        self.ids = ids.to_vec();
+        self.anchor = ids.first().copied();
+    }
+
+    pub fn clear(&mut self) {
+        self.ids.clear();
+        self.anchor = None;
+    }
+
+    /// Drops the ids `keep` refuses, e.g. units that no longer exist.
+    pub fn retain(&mut self, keep: impl Fn(u64) -> bool) {
+        self.ids.retain(|id| keep(*id));
+        if self.anchor.is_some_and(|anchor| !keep(anchor)) {
+            self.anchor = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_click_ctrl_click_and_shift_click() {
+        let order = [5, 3, 8, 1, 9];
+        let mut selection = Selection::new();
+
+        selection.select_only(3);
+        assert_eq!(selection.single(), Some(3));
+        selection.extend_to(1, &order);
+        assert_eq!(selection.ids(), &[3, 8, 1]);
+        selection.toggle(8);
+        assert_eq!(selection.ids(), &[3, 1]);
+        selection.toggle(9);
+        assert_eq!(selection.ids(), &[3, 1, 9]);
+
+        // The range starts at the last clicked unit and can run backwards
+        selection.extend_to(5, &order);
+        assert_eq!(selection.ids(), &[9, 1, 8, 3, 5]);
+    }
+
+    #[test]
+    fn test_shift_click_without_anchor_and_retain() {
+        let mut selection = Selection::new();
+        selection.extend_to(4, &[1, 4]);
+        assert_eq!(selection.ids(), &[4]);
+
+        selection.select_all(&[1, 2, 4]);
+        selection.retain(|id| id != 1);
+        assert_eq!(selection.ids(), &[2, 4]);
+        selection.extend_to(4, &[2, 4]);
+        assert_eq!(selection.ids(), &[4]);
+    }
+}
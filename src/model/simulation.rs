@@ -0,0 +1,422 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use super::{UnitKind, UnitStore};
+
+/// Reasons a simulated action is refused.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimulationError {
+    UnitNotFound(u64),
+    NotTriggerable(UnitKind),
+}
+
+impl fmt::Display for SimulationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimulationError::UnitNotFound(id) => write!(f, "unit {} does not exist", id),
+            SimulationError::NotTriggerable(kind) => write!(f, "a {} cannot be triggered", kind),
+        }
+    }
+}
+
+impl std::error::Error for SimulationError {}
+
+/// What happened to a unit at one point of the simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimulationEventKind {
+    Triggered,
+    Reset,
+    Activated,
+    Deactivated,
+}
+
+impl fmt::Display for SimulationEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimulationEventKind::Triggered => write!(f, "triggered"),
+            SimulationEventKind::Reset => write!(f, "reset"),
+            SimulationEventKind::Activated => write!(f, "activated"),
+            SimulationEventKind::Deactivated => write!(f, "deactivated"),
+        }
+    }
+}
+
+/// One line of the simulation timeline.
+///
+/// The unit name is captured when the entry is written so the log still reads correctly after a rename or removal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineEntry {
+    pub step: u64,
+    pub unit: u64,
+    pub kind: UnitKind,
+    pub name: String,
+    pub event: SimulationEventKind,
+}
+
+impl fmt::Display for TimelineEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {} {}", self.step, self.name, self.event)
+    }
+}
+
+/// The units activated by the current set of triggered inputs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SimulationState {
+    pub detection_zones: BTreeSet<u64>,
+    pub alarm_zones: BTreeSet<u64>,
+    pub fire_alarm_devices: BTreeSet<u64>,
+    pub outputs: BTreeSet<u64>,
+}
+
+impl SimulationState {
+    /// Works out what activates when `triggered` detectors and manual call points are in alarm.
+    ///
+    /// A detection zone is in alarm when any of its members is. An alarm zone activates when one of its detection zones is in
+    /// alarm or a rule drives it, and fire alarm devices sound when their alarm zone is active or a rule drives them directly.
+    /// Rules only drive units on panels the network connects to the panel of the input, see [`UnitStore::evaluate_rules`].
+    pub fn propagate(store: &UnitStore, triggered: &BTreeSet<u64>) -> Self {
+        Self::propagate_suppressing(store, triggered, &BTreeSet::new())
+    }
+
+    /// Like [`SimulationState::propagate`], but the `suppressed` units, e.g. disabled ones, neither go into alarm nor pass
+    /// an alarm on to their zone, their members or the outputs of their rules.
+    pub fn propagate_suppressing(
+        store: &UnitStore,
+        triggered: &BTreeSet<u64>,
+        suppressed: &BTreeSet<u64>,
+    ) -> Self {
+        let triggered: BTreeSet<u64> = triggered
+            .iter()
+            .copied()
+            .filter(|id| store.get_unit(*id).is_some() && !suppressed.contains(id))
+            .collect();
+
+        let detection_zones: BTreeSet<u64> = triggered
+            .iter()
+            .filter_map(|id| store.zone_of(*id))
+            .filter(|zone| !suppressed.contains(zone))
+            .collect();
+
+        let mut active_inputs = triggered.clone();
+        active_inputs.extend(detection_zones.iter().copied());
+        let effects = store.evaluate_rules(&active_inputs);
+
+        let of_kind = |ids: &BTreeSet<u64>, kind: UnitKind| -> BTreeSet<u64> {
+            ids.iter()
+                .copied()
+                .filter(|id| store.get_unit(*id).map(|unit| unit.kind()) == Some(kind))
+                .collect()
+        };
+
+        let mut alarm_zones = of_kind(&effects, UnitKind::AlarmZone);
+        alarm_zones.extend(
+            detection_zones
+                .iter()
+                .filter_map(|zone| store.zone_of(*zone)),
+        );
+
+        alarm_zones.retain(|zone| !suppressed.contains(zone));
+
+        let mut fire_alarm_devices = of_kind(&effects, UnitKind::FireAlarmDevice);
+        for zone in &alarm_zones {
+            fire_alarm_devices.extend(
+                store
+                    .members_of(*zone)
+                    .filter(|unit| unit.kind() == UnitKind::FireAlarmDevice)
+                    .map(|unit| unit.id()),
+            );
+        }
+
+        Self {
+            detection_zones,
+            alarm_zones,
+            fire_alarm_devices,
+            outputs: of_kind(&effects, UnitKind::Output),
+        }
+    }
+
+    fn all(&self) -> BTreeSet<u64> {
+        let mut all = self.detection_zones.clone();
+        all.extend(self.alarm_zones.iter().copied());
+        all.extend(self.fire_alarm_devices.iter().copied());
+        all.extend(self.outputs.iter().copied());
+        all
+    }
+}
+
+/// A bench simulation of a configured system.
+///
+/// Detectors and manual call points are triggered and reset by hand; every action advances the step counter by one and
+/// logs the inputs and activations that changed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Simulation {
+    triggered: BTreeSet<u64>,
+    state: SimulationState,
+    timeline: Vec<TimelineEntry>,
+    step: u64,
+}
+
+impl Simulation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn triggered(&self) -> &BTreeSet<u64> {
+        &self.triggered
+    }
+
+    pub fn is_triggered(&self, id: u64) -> bool {
+        self.triggered.contains(&id)
+    }
+
+    pub fn state(&self) -> &SimulationState {
+        &self.state
+    }
+
+    pub fn timeline(&self) -> &[TimelineEntry] {
+        &self.timeline
+    }
+
+    /// Puts a detector or manual call point into alarm.
+    pub fn trigger(&mut self, store: &UnitStore, id: u64) -> Result<(), SimulationError> {
+        let kind = store
+            .get_unit(id)
+            .ok_or(SimulationError::UnitNotFound(id))?
+            .kind();
+        if !matches!(kind, UnitKind::Detector | UnitKind::ManualCallPoint) {
+            return Err(SimulationError::NotTriggerable(kind));
+        }
+        if self.triggered.insert(id) {
+            self.step += 1;
+            self.record(store, id, SimulationEventKind::Triggered);
+            self.update(store);
+        }
+        Ok(())
+    }
+
+    /// Takes a single input out of alarm. Returns false if it was not triggered.
+    pub fn reset(&mut self, store: &UnitStore, id: u64) -> bool {
+        if !self.triggered.remove(&id) {
+            return false;
+        }
+        self.step += 1;
+        self.record(store, id, SimulationEventKind::Reset);
+        self.update(store);
+        true
+    }
+
+    /// Resets every triggered input at once.
+    pub fn reset_all(&mut self, store: &UnitStore) {
+        if self.triggered.is_empty() {
+            return;
+        }
+        self.step += 1;
+        for id in std::mem::take(&mut self.triggered) {
+            self.record(store, id, SimulationEventKind::Reset);
+        }
+        self.update(store);
+    }
+
+    /// Recomputes the state after the configuration changed, logging any difference.
+    pub fn refresh(&mut self, store: &UnitStore) {
+        self.triggered.retain(|id| store.get_unit(*id).is_some());
+        self.update(store);
+    }
+
+    /// Forgets the triggered inputs, the state and the timeline.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    fn update(&mut self, store: &UnitStore) {
+        let previous = self.state.all();
+        self.state = SimulationState::propagate(store, &self.triggered);
+        let current = self.state.all();
+
+        for id in current.difference(&previous) {
+            self.record(store, *id, SimulationEventKind::Activated);
+        }
+        for id in previous.difference(&current) {
+            self.record(store, *id, SimulationEventKind::Deactivated);
+        }
+    }
+
+    fn record(&mut self, store: &UnitStore, id: u64, event: SimulationEventKind) {
+        let (kind, name) = match store.get_unit(id) {
+            Some(unit) => (unit.kind(), unit.name().to_string()),
+            None => (UnitKind::Root, format!("Unit {}", id)),
+        };
+        self.timeline.push(TimelineEntry {
+            step: self.step,
+            unit: id,
+            kind,
+            name,
+            event,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::InputLogic;
+
+    struct Site {
+        store: UnitStore,
+        det1: u64,
+        det2: u64,
+        mcp: u64,
+        dz: u64,
+        az: u64,
+        fad: u64,
+        out: u64,
+    }
+
+    fn site() -> Site {
+        let mut store = UnitStore::new();
+        let det1 = store.add_unit(UnitKind::Detector, "Detector 1");
+        let det2 = store.add_unit(UnitKind::Detector, "Detector 2");
+        let mcp = store.add_unit(UnitKind::ManualCallPoint, "Manual Call Point 1");
+        let dz = store.add_unit(UnitKind::DetectionZone, "Detection Zone 1");
+        let az = store.add_unit(UnitKind::AlarmZone, "Alarm Zone 1");
+        let fad = store.add_unit(UnitKind::FireAlarmDevice, "Fire Alarm Device 1");
+        let out = store.add_unit(UnitKind::Output, "Output 1");
+        store.assign_to_zone(det1, dz).unwrap();
+        store.assign_to_zone(det2, dz).unwrap();
+        store.assign_to_zone(dz, az).unwrap();
+        store.assign_to_zone(fad, az).unwrap();
+
+        Site {
+            store,
+            det1,
+            det2,
+            mcp,
+            dz,
+            az,
+            fad,
+            out,
+        }
+    }
+
+    #[test]
+    fn test_trigger_propagates_through_zones() {
+        let site = site();
+        let mut sim = Simulation::new();
+
+        sim.trigger(&site.store, site.det1).unwrap();
+
+        let state = sim.state();
+        assert!(state.detection_zones.contains(&site.dz));
+        assert!(state.alarm_zones.contains(&site.az));
+        assert!(state.fire_alarm_devices.contains(&site.fad));
+        assert!(state.outputs.is_empty());
+    }
+
+    #[test]
+    fn test_trigger_evaluates_cause_effect_rules() {
+        let mut site = site();
+        let rule = site
+            .store
+            .cause_effect_mut()
+            .add_rule("MCP to output", InputLogic::Any)
+            .unwrap();
+        site.store.set_rule_input(rule, site.mcp, true).unwrap();
+        site.store.set_rule_output(rule, site.out, true).unwrap();
+        let mut sim = Simulation::new();
+
+        sim.trigger(&site.store, site.mcp).unwrap();
+
+        let state = sim.state();
+        assert_eq!(state.outputs, BTreeSet::from([site.out]));
+        // The manual call point is in no zone, so nothing else activates
+        assert!(state.alarm_zones.is_empty());
+    }
+
+    #[test]
+    fn test_zone_inputs_activate_rules() {
+        let mut site = site();
+        let rule = site
+            .store
+            .cause_effect_mut()
+            .add_rule("Zone to output", InputLogic::Any)
+            .unwrap();
+        site.store.set_rule_input(rule, site.dz, true).unwrap();
+        site.store.set_rule_output(rule, site.out, true).unwrap();
+        let mut sim = Simulation::new();
+
+        sim.trigger(&site.store, site.det2).unwrap();
+
+        assert!(sim.state().outputs.contains(&site.out));
+    }
+
+    #[test]
+    fn test_trigger_rejects_non_inputs() {
+        let site = site();
+        let mut sim = Simulation::new();
+
+        assert_eq!(
+            sim.trigger(&site.store, site.out),
+            Err(SimulationError::NotTriggerable(UnitKind::Output))
+        );
+        assert_eq!(
+            sim.trigger(&site.store, 999),
+            Err(SimulationError::UnitNotFound(999))
+        );
+        assert!(sim.timeline().is_empty());
+    }
+
+    #[test]
+    fn test_reset_deactivates_once_all_inputs_clear() {
+        let site = site();
+        let mut sim = Simulation::new();
+        sim.trigger(&site.store, site.det1).unwrap();
+        sim.trigger(&site.store, site.det2).unwrap();
+
+        assert!(sim.reset(&site.store, site.det1));
+        assert!(sim.state().alarm_zones.contains(&site.az));
+
+        assert!(sim.reset(&site.store, site.det2));
+        assert_eq!(sim.state(), &SimulationState::default());
+        assert!(!sim.reset(&site.store, site.det2));
+    }
+
+    #[test]
+    fn test_timeline_records_changes_per_step() {
+        let site = site();
+        let mut sim = Simulation::new();
+
+        sim.trigger(&site.store, site.det1).unwrap();
+        sim.trigger(&site.store, site.det2).unwrap();
+        sim.reset_all(&site.store);
+
+        let lines: Vec<String> = sim.timeline().iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "[1] Detector 1 triggered",
+                "[1] Detection Zone 1 activated",
+                "[1] Alarm Zone 1 activated",
+                "[1] Fire Alarm Device 1 activated",
+                "[2] Detector 2 triggered",
+                "[3] Detector 1 reset",
+                "[3] Detector 2 reset",
+                "[3] Detection Zone 1 deactivated",
+                "[3] Alarm Zone 1 deactivated",
+                "[3] Fire Alarm Device 1 deactivated",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_refresh_drops_removed_inputs() {
+        let mut site = site();
+        let mut sim = Simulation::new();
+        sim.trigger(&site.store, site.det1).unwrap();
+
+        site.store.remove_unit(site.det1);
+        sim.refresh(&site.store);
+
+        assert!(sim.triggered().is_empty());
+        assert!(sim.state().alarm_zones.is_empty());
+    }
+}
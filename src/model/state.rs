@@ -0,0 +1,811 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::{LogEvent, LogRecord, PanelFilter, Simulation, SimulationState, UnitKind, UnitStore};
+
+/// Why a device reports a fault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum FaultKind {
+    Open,
+    Short,
+    /// The device does not answer on its address.
+    Missing,
+}
+
+impl FaultKind {
+    pub const ALL: [FaultKind; 3] = [FaultKind::Open, FaultKind::Short, FaultKind::Missing];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FaultKind::Open => "Open circuit",
+            FaultKind::Short => "Short circuit",
+            FaultKind::Missing => "Device missing",
+        }
+    }
+}
+
+impl fmt::Display for FaultKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.label().to_lowercase())
+    }
+}
+
+/// The runtime state of one unit, as a panel would show it.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize,
+)]
+pub enum UnitState {
+    #[default]
+    Normal,
+    /// A detector reading close to its alarm threshold.
+    PreAlarm,
+    /// An input or zone in alarm. Fire is latched until the panel is reset.
+    Fire,
+    /// A sounder, output or alarm zone driven by an alarm.
+    Active,
+    Fault(FaultKind),
+    Disabled,
+    InTest,
+}
+
+impl UnitState {
+    pub const ALL: [UnitState; 9] = [
+        UnitState::Normal,
+        UnitState::PreAlarm,
+        UnitState::Fire,
+        UnitState::Active,
+        UnitState::Fault(FaultKind::Open),
+        UnitState::Fault(FaultKind::Short),
+        UnitState::Fault(FaultKind::Missing),
+        UnitState::Disabled,
+        UnitState::InTest,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            UnitState::Normal => "Normal",
+            UnitState::PreAlarm => "Pre-alarm",
+            UnitState::Fire => "Fire",
+            UnitState::Active => "Active",
+            UnitState::Fault(FaultKind::Open) => "Fault (open circuit)",
+            UnitState::Fault(FaultKind::Short) => "Fault (short circuit)",
+            UnitState::Fault(FaultKind::Missing) => "Fault (device missing)",
+            UnitState::Disabled => "Disabled",
+            UnitState::InTest => "In test",
+        }
+    }
+
+    pub fn is_fault(&self) -> bool {
+        matches!(self, UnitState::Fault(_))
+    }
+
+    /// The state `event` moves a unit in this state to, or `None` if the transition is not allowed.
+    ///
+    /// Fire has no way out here: only a panel reset (see [`PanelStatus::reset`]) clears it. An alarm during a test does
+    /// not raise a fire; [`PanelStatus::handle`] logs it as a test activation.
+    pub fn next(self, event: StateEvent) -> Option<UnitState> {
+        use StateEvent as E;
+        use UnitState as S;
+        match (self, event) {
+            (S::Normal, E::PreAlarm) => Some(S::PreAlarm),
+            (S::PreAlarm, E::PreAlarmCleared) => Some(S::Normal),
+            (S::Normal | S::PreAlarm, E::Alarm) => Some(S::Fire),
+            (S::Normal | S::PreAlarm | S::Active, E::Fault(kind)) => Some(S::Fault(kind)),
+            (S::Fault(current), E::Fault(kind)) if current != kind => Some(S::Fault(kind)),
+            (S::Fault(_), E::FaultCleared) => Some(S::Normal),
+            (S::Normal | S::PreAlarm | S::Active | S::Fault(_), E::Disable) => Some(S::Disabled),
+            (S::Disabled, E::Enable) => Some(S::Normal),
+            (S::Normal, E::StartTest) => Some(S::InTest),
+            (S::InTest, E::Alarm) => Some(S::InTest),
+            (S::InTest, E::EndTest) => Some(S::Normal),
+            (S::Normal, E::Activate) => Some(S::Active),
+            (S::Active, E::Deactivate) => Some(S::Normal),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for UnitState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+/// Something that happens to one unit, reported by a device, the simulation or an operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StateEvent {
+    PreAlarm,
+    PreAlarmCleared,
+    Alarm,
+    Fault(FaultKind),
+    FaultCleared,
+    Disable,
+    Enable,
+    StartTest,
+    EndTest,
+    Activate,
+    Deactivate,
+}
+
+impl StateEvent {
+    pub const ALL: [StateEvent; 13] = [
+        StateEvent::PreAlarm,
+        StateEvent::PreAlarmCleared,
+        StateEvent::Alarm,
+        StateEvent::Fault(FaultKind::Open),
+        StateEvent::Fault(FaultKind::Short),
+        StateEvent::Fault(FaultKind::Missing),
+        StateEvent::FaultCleared,
+        StateEvent::Disable,
+        StateEvent::Enable,
+        StateEvent::StartTest,
+        StateEvent::EndTest,
+        StateEvent::Activate,
+        StateEvent::Deactivate,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            StateEvent::PreAlarm => "Pre-alarm",
+            StateEvent::PreAlarmCleared => "Clear pre-alarm",
+            StateEvent::Alarm => "Alarm",
+            StateEvent::Fault(kind) => kind.label(),
+            StateEvent::FaultCleared => "Clear fault",
+            StateEvent::Disable => "Disable",
+            StateEvent::Enable => "Enable",
+            StateEvent::StartTest => "Start test",
+            StateEvent::EndTest => "End test",
+            StateEvent::Activate => "Activate",
+            StateEvent::Deactivate => "Deactivate",
+        }
+    }
+
+    /// Whether a unit of `kind` can report or receive this event at all.
+    ///
+    /// Only detectors have a pre-alarm, zones cannot be in fault, and only alarm zones, fire alarm devices and outputs
+    /// are activated.
+    pub fn applies_to(&self, kind: UnitKind) -> bool {
+        use StateEvent as E;
+        match kind {
+            UnitKind::Root => false,
+            UnitKind::Detector => !matches!(self, E::Activate | E::Deactivate),
+            UnitKind::ManualCallPoint => !matches!(
+                self,
+                E::PreAlarm | E::PreAlarmCleared | E::Activate | E::Deactivate
+            ),
+            UnitKind::DetectionZone => matches!(
+                self,
+                E::Alarm | E::Disable | E::Enable | E::StartTest | E::EndTest
+            ),
+            UnitKind::AlarmZone => {
+                matches!(self, E::Activate | E::Deactivate | E::Disable | E::Enable)
+            }
+            UnitKind::FireAlarmDevice | UnitKind::Output => {
+                !matches!(self, E::PreAlarm | E::PreAlarmCleared | E::Alarm)
+            }
+        }
+    }
+}
+
+impl fmt::Display for StateEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.label().to_lowercase())
+    }
+}
+
+/// Reasons a state change or panel operation is refused.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateError {
+    UnitNotFound(u64),
+    NotApplicable { kind: UnitKind, event: StateEvent },
+    IllegalTransition { from: UnitState, event: StateEvent },
+    NothingToAcknowledge,
+    NothingToSilence,
+    NothingToReset,
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::UnitNotFound(id) => write!(f, "unit {} does not exist", id),
+            StateError::NotApplicable { kind, event } => {
+                write!(f, "a {} cannot take \"{}\"", kind, event)
+            }
+            StateError::IllegalTransition { from, event } => {
+                write!(f, "\"{}\" is not allowed in state {}", event, from)
+            }
+            StateError::NothingToAcknowledge => write!(f, "there is nothing to acknowledge"),
+            StateError::NothingToSilence => write!(f, "there is no alarm to silence"),
+            StateError::NothingToReset => write!(f, "there is no alarm to reset"),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+/// How many units are in each state.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StateCounts {
+    counts: BTreeMap<UnitState, usize>,
+}
+
+impl StateCounts {
+    pub fn get(&self, state: UnitState) -> usize {
+        self.counts.get(&state).copied().unwrap_or(0)
+    }
+
+    pub fn fires(&self) -> usize {
+        self.get(UnitState::Fire)
+    }
+
+    /// Faults of every kind.
+    pub fn faults(&self) -> usize {
+        FaultKind::ALL
+            .iter()
+            .map(|kind| self.get(UnitState::Fault(*kind)))
+            .sum()
+    }
+}
+
+/// The runtime state of every unit in a store, with the panel-level acknowledge, silence and reset operations.
+///
+/// Units without an entry are [`UnitState::Normal`]. Every change goes through [`UnitState::next`], whether it comes
+/// from a [`Simulation`] or an operator, and queues a [`LogRecord`] for the event log.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PanelStatus {
+    states: HashMap<u64, UnitState>,
+    // Units that entered pre-alarm, fire or fault since the last acknowledge
+    unacknowledged: BTreeSet<u64>,
+    silenced: bool,
+    changes: Vec<LogRecord>,
+}
+
+impl PanelStatus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self, id: u64) -> UnitState {
+        self.states.get(&id).copied().unwrap_or_default()
+    }
+
+    pub fn unacknowledged(&self) -> &BTreeSet<u64> {
+        &self.unacknowledged
+    }
+
+    pub fn is_silenced(&self) -> bool {
+        self.silenced
+    }
+
+    /// Checks `event` against the unit's kind and state and moves it to the next state.
+    ///
+    /// An alarm from a unit in test leaves it in test and is logged as a [`LogEvent::TestActivation`].
+    pub fn handle(
+        &mut self,
+        store: &UnitStore,
+        id: u64,
+        event: StateEvent,
+    ) -> Result<UnitState, StateError> {
+        let from = self.check(store, id, event)?;
+        let to = from
+            .next(event)
+            .ok_or(StateError::IllegalTransition { from, event })?;
+        if from == UnitState::InTest && event == StateEvent::Alarm {
+            self.changes
+                .push(LogRecord::new(Some(id), LogEvent::TestActivation));
+        }
+        self.enter(id, to);
+        Ok(to)
+    }
+
+    /// The events the unit can take in its current state, in [`StateEvent::ALL`] order.
+    pub fn events_for(&self, store: &UnitStore, id: u64) -> Vec<StateEvent> {
+        StateEvent::ALL
+            .into_iter()
+            .filter(|event| {
+                self.check(store, id, *event)
+                    .is_ok_and(|from| from.next(*event).is_some())
+            })
+            .collect()
+    }
+
+    /// Acknowledges every pre-alarm, fire and fault shown so far.
+    pub fn acknowledge(&mut self) -> Result<(), StateError> {
+        if self.unacknowledged.is_empty() {
+            return Err(StateError::NothingToAcknowledge);
+        }
+        self.unacknowledged.clear();
+        self.changes
+            .push(LogRecord::new(None, LogEvent::Acknowledged));
+        Ok(())
+    }
+
+    /// Silences the sounders until a new fire is reported.
+    pub fn silence(&mut self) -> Result<(), StateError> {
+        if self.silenced || !self.states.values().any(|state| *state == UnitState::Fire) {
+            return Err(StateError::NothingToSilence);
+        }
+        self.silenced = true;
+        self.changes.push(LogRecord::new(None, LogEvent::Silenced));
+        Ok(())
+    }
+
+    /// Returns every unit in pre-alarm, fire or activated to Normal.
+    ///
+    /// Faults, disablements and tests stay, as they are cleared by the device or the operator rather than by a reset.
+    pub fn reset(&mut self) -> Result<(), StateError> {
+        let resets: Vec<u64> = self
+            .states
+            .iter()
+            .filter(|(_, state)| {
+                matches!(
+                    state,
+                    UnitState::PreAlarm | UnitState::Fire | UnitState::Active
+                )
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        if resets.is_empty() {
+            return Err(StateError::NothingToReset);
+        }
+        self.changes.push(LogRecord::new(None, LogEvent::Reset));
+        for id in resets {
+            self.enter(id, UnitState::Normal);
+        }
+        self.silenced = false;
+        Ok(())
+    }
+
+    /// Feeds what `simulation` has in alarm into the state machine.
+    ///
+    /// Triggered inputs and detection zones in alarm raise an alarm, and alarm zones, fire alarm devices and outputs are
+    /// activated while the simulation drives them. Fires stay latched after the simulation resets until the panel is
+    /// reset. Disabled units and units in test pass nothing on, so a disabled detector does not put its zone in alarm or
+    /// drive any sounder or output.
+    pub fn follow_simulation(&mut self, store: &UnitStore, simulation: &Simulation) {
+        let suppressed: BTreeSet<u64> = self
+            .states
+            .iter()
+            .filter(|(_, state)| matches!(state, UnitState::Disabled | UnitState::InTest))
+            .map(|(id, _)| *id)
+            .collect();
+        let alarm =
+            SimulationState::propagate_suppressing(store, simulation.triggered(), &suppressed);
+        let alarms: Vec<u64> = simulation
+            .triggered()
+            .iter()
+            .filter(|id| !suppressed.contains(id))
+            .chain(&alarm.detection_zones)
+            .copied()
+            .collect();
+        for id in alarms {
+            if matches!(self.state(id), UnitState::Normal | UnitState::PreAlarm) {
+                let _ = self.handle(store, id, StateEvent::Alarm);
+            }
+        }
+
+        let driven: BTreeSet<u64> = alarm
+            .alarm_zones
+            .iter()
+            .chain(&alarm.fire_alarm_devices)
+            .chain(&alarm.outputs)
+            .copied()
+            .collect();
+        let released: Vec<u64> = self
+            .states
+            .iter()
+            .filter(|(id, state)| **state == UnitState::Active && !driven.contains(id))
+            .map(|(id, _)| *id)
+            .collect();
+        for id in released {
+            let _ = self.handle(store, id, StateEvent::Deactivate);
+        }
+        for id in driven {
+            if self.state(id) == UnitState::Normal {
+                let _ = self.handle(store, id, StateEvent::Activate);
+            }
+        }
+    }
+
+    /// Counts the units of `store` on the panels `filter` lets through in each state. The Root is not counted.
+    pub fn counts_for(&self, store: &UnitStore, filter: PanelFilter) -> StateCounts {
+        let mut counts = StateCounts::default();
+        for unit in store.get_all_units().iter().skip(1) {
+            if filter
+                .panel()
+                .is_some_and(|panel| store.panel_of(unit.id()) != Some(panel))
+            {
+                continue;
+            }
+            *counts.counts.entry(self.state(unit.id())).or_default() += 1;
+        }
+        counts
+    }
+
+    /// Forgets the state of units that are no longer in `store`.
+    pub fn retain(&mut self, store: &UnitStore) {
+        self.states.retain(|id, _| store.get_unit(*id).is_some());
+        self.unacknowledged
+            .retain(|id| store.get_unit(*id).is_some());
+    }
+
+    /// Puts every unit back to Normal without logging it, e.g. after another project was opened.
+    pub fn clear(&mut self) {
+        let changes = std::mem::take(&mut self.changes);
+        *self = Self::new();
+        self.changes = changes;
+    }
+
+    pub fn has_changes(&self) -> bool {
+        !self.changes.is_empty()
+    }
+
+    /// Hands over the log records queued since the last call.
+    pub fn take_changes(&mut self) -> Vec<LogRecord> {
+        std::mem::take(&mut self.changes)
+    }
+
+    // Returns the current state if the unit exists and `event` applies to its kind
+    fn check(
+        &self,
+        store: &UnitStore,
+        id: u64,
+        event: StateEvent,
+    ) -> Result<UnitState, StateError> {
+        let kind = store
+            .get_unit(id)
+            .ok_or(StateError::UnitNotFound(id))?
+            .kind();
+        if !event.applies_to(kind) {
+            return Err(StateError::NotApplicable { kind, event });
+        }
+        Ok(self.state(id))
+    }
+
+    fn enter(&mut self, id: u64, state: UnitState) {
+        let previous = match state {
+            UnitState::Normal => self.states.remove(&id),
+            state => self.states.insert(id, state),
+        };
+        let from = previous.unwrap_or_default();
+        if from != state {
+            self.changes.push(LogRecord::new(
+                Some(id),
+                LogEvent::StateChanged { from, to: state },
+            ));
+        }
+        match state {
+            UnitState::PreAlarm | UnitState::Fire | UnitState::Fault(_) => {
+                self.unacknowledged.insert(id);
+            }
+            _ => {
+                self.unacknowledged.remove(&id);
+            }
+        }
+        // A new fire sounds again after a silence
+        if state == UnitState::Fire && from != UnitState::Fire {
+            self.silenced = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_transition() {
+        use FaultKind::{Missing, Open, Short};
+        use StateEvent as E;
+        use UnitState as S;
+        let legal = [
+            (S::Normal, E::PreAlarm, S::PreAlarm),
+            (S::Normal, E::Alarm, S::Fire),
+            (S::Normal, E::Fault(Open), S::Fault(Open)),
+            (S::Normal, E::Fault(Short), S::Fault(Short)),
+            (S::Normal, E::Fault(Missing), S::Fault(Missing)),
+            (S::Normal, E::Disable, S::Disabled),
+            (S::Normal, E::StartTest, S::InTest),
+            (S::Normal, E::Activate, S::Active),
+            (S::PreAlarm, E::PreAlarmCleared, S::Normal),
+            (S::PreAlarm, E::Alarm, S::Fire),
+            (S::PreAlarm, E::Fault(Open), S::Fault(Open)),
+            (S::PreAlarm, E::Fault(Short), S::Fault(Short)),
+            (S::PreAlarm, E::Fault(Missing), S::Fault(Missing)),
+            (S::PreAlarm, E::Disable, S::Disabled),
+            (S::Active, E::Fault(Open), S::Fault(Open)),
+            (S::Active, E::Fault(Short), S::Fault(Short)),
+            (S::Active, E::Fault(Missing), S::Fault(Missing)),
+            (S::Active, E::Disable, S::Disabled),
+            (S::Active, E::Deactivate, S::Normal),
+            (S::Fault(Open), E::Fault(Short), S::Fault(Short)),
+            (S::Fault(Open), E::Fault(Missing), S::Fault(Missing)),
+            (S::Fault(Short), E::Fault(Open), S::Fault(Open)),
+            (S::Fault(Short), E::Fault(Missing), S::Fault(Missing)),
+            (S::Fault(Missing), E::Fault(Open), S::Fault(Open)),
+            (S::Fault(Missing), E::Fault(Short), S::Fault(Short)),
+            (S::Fault(Open), E::FaultCleared, S::Normal),
+            (S::Fault(Short), E::FaultCleared, S::Normal),
+            (S::Fault(Missing), E::FaultCleared, S::Normal),
+            (S::Fault(Open), E::Disable, S::Disabled),
+            (S::Fault(Short), E::Disable, S::Disabled),
+            (S::Fault(Missing), E::Disable, S::Disabled),
+            (S::Disabled, E::Enable, S::Normal),
+            (S::InTest, E::Alarm, S::InTest),
+            (S::InTest, E::EndTest, S::Normal),
+        ];
+
+        for from in UnitState::ALL {
+            for event in StateEvent::ALL {
+                let expected = legal
+                    .iter()
+                    .find(|(state, legal_event, _)| *state == from && *legal_event == event)
+                    .map(|(_, _, to)| *to);
+                assert_eq!(from.next(event), expected, "{:?} on {:?}", event, from);
+            }
+        }
+    }
+
+    #[test]
+    fn test_events_are_checked_against_kind_and_state() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let mcp = store.add_unit(UnitKind::ManualCallPoint, "Manual Call Point 1");
+        let out = store.add_unit(UnitKind::Output, "Output 1");
+        let mut status = PanelStatus::new();
+
+        assert_eq!(
+            status.handle(&store, mcp, StateEvent::PreAlarm),
+            Err(StateError::NotApplicable {
+                kind: UnitKind::ManualCallPoint,
+                event: StateEvent::PreAlarm
+            })
+        );
+        assert_eq!(
+            status.handle(&store, out, StateEvent::Alarm),
+            Err(StateError::NotApplicable {
+                kind: UnitKind::Output,
+                event: StateEvent::Alarm
+            })
+        );
+        assert_eq!(
+            status.handle(&store, 0, StateEvent::Disable),
+            Err(StateError::NotApplicable {
+                kind: UnitKind::Root,
+                event: StateEvent::Disable
+            })
+        );
+        assert_eq!(
+            status.handle(&store, 99, StateEvent::Alarm),
+            Err(StateError::UnitNotFound(99))
+        );
+
+        status.handle(&store, det, StateEvent::Disable).unwrap();
+        assert_eq!(
+            status.handle(&store, det, StateEvent::Alarm),
+            Err(StateError::IllegalTransition {
+                from: UnitState::Disabled,
+                event: StateEvent::Alarm
+            })
+        );
+        assert_eq!(status.events_for(&store, det), vec![StateEvent::Enable]);
+        assert_eq!(
+            status.events_for(&store, mcp),
+            vec![
+                StateEvent::Alarm,
+                StateEvent::Fault(FaultKind::Open),
+                StateEvent::Fault(FaultKind::Short),
+                StateEvent::Fault(FaultKind::Missing),
+                StateEvent::Disable,
+                StateEvent::StartTest,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_handle_and_counts() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let mcp = store.add_unit(UnitKind::ManualCallPoint, "Manual Call Point 1");
+        store.add_unit(UnitKind::Output, "Output 1");
+        let mut status = PanelStatus::new();
+
+        status
+            .handle(&store, det, StateEvent::Fault(FaultKind::Short))
+            .unwrap();
+        status.handle(&store, mcp, StateEvent::Alarm).unwrap();
+        assert_eq!(
+            status.handle(&store, mcp, StateEvent::Disable),
+            Err(StateError::IllegalTransition {
+                from: UnitState::Fire,
+                event: StateEvent::Disable
+            })
+        );
+
+        let counts = status.counts_for(&store, PanelFilter::All);
+        assert_eq!(counts.fires(), 1);
+        assert_eq!(counts.faults(), 1);
+        assert_eq!(counts.get(UnitState::Normal), 1);
+    }
+
+    #[test]
+    fn test_acknowledge_silence_and_reset() {
+        let mut store = UnitStore::new();
+        let det1 = store.add_unit(UnitKind::Detector, "Detector 1");
+        let det2 = store.add_unit(UnitKind::Detector, "Detector 2");
+        let det3 = store.add_unit(UnitKind::Detector, "Detector 3");
+        let mut status = PanelStatus::new();
+        assert_eq!(status.acknowledge(), Err(StateError::NothingToAcknowledge));
+        assert_eq!(status.silence(), Err(StateError::NothingToSilence));
+        assert_eq!(status.reset(), Err(StateError::NothingToReset));
+
+        status.handle(&store, det1, StateEvent::Alarm).unwrap();
+        status
+            .handle(&store, det2, StateEvent::Fault(FaultKind::Open))
+            .unwrap();
+        assert_eq!(status.unacknowledged(), &BTreeSet::from([det1, det2]));
+        status.acknowledge().unwrap();
+        assert!(status.unacknowledged().is_empty());
+
+        status.silence().unwrap();
+        assert!(status.is_silenced());
+        assert_eq!(status.silence(), Err(StateError::NothingToSilence));
+        // A second fire sounds again and needs acknowledging
+        status.handle(&store, det3, StateEvent::Alarm).unwrap();
+        assert!(!status.is_silenced());
+        assert_eq!(status.unacknowledged(), &BTreeSet::from([det3]));
+
+        status.reset().unwrap();
+        assert_eq!(status.state(det1), UnitState::Normal);
+        assert_eq!(status.state(det3), UnitState::Normal);
+        let events: Vec<LogEvent> = status
+            .take_changes()
+            .into_iter()
+            .map(|record| record.event)
+            .collect();
+        assert_eq!(events.len(), 8);
+        assert_eq!(
+            events[..4],
+            [
+                LogEvent::StateChanged {
+                    from: UnitState::Normal,
+                    to: UnitState::Fire
+                },
+                LogEvent::StateChanged {
+                    from: UnitState::Normal,
+                    to: UnitState::Fault(FaultKind::Open)
+                },
+                LogEvent::Acknowledged,
+                LogEvent::Silenced,
+            ]
+        );
+        assert_eq!(events[5], LogEvent::Reset);
+        assert!(status.unacknowledged().is_empty());
+        // Faults are not cleared by a reset
+        assert_eq!(status.state(det2), UnitState::Fault(FaultKind::Open));
+        assert_eq!(status.reset(), Err(StateError::NothingToReset));
+    }
+
+    #[test]
+    fn test_alarm_in_test_is_logged_as_test_activation() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let mut status = PanelStatus::new();
+        status.handle(&store, det, StateEvent::StartTest).unwrap();
+        status.take_changes();
+
+        assert_eq!(
+            status.handle(&store, det, StateEvent::Alarm),
+            Ok(UnitState::InTest)
+        );
+        assert_eq!(
+            status.take_changes(),
+            vec![LogRecord::new(Some(det), LogEvent::TestActivation)]
+        );
+        assert_eq!(status.counts_for(&store, PanelFilter::All).fires(), 0);
+        assert!(status.unacknowledged().is_empty());
+    }
+
+    #[test]
+    fn test_disabled_inputs_drive_nothing() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let dz = store.add_unit(UnitKind::DetectionZone, "Detection Zone 1");
+        let az = store.add_unit(UnitKind::AlarmZone, "Alarm Zone 1");
+        let fad = store.add_unit(UnitKind::FireAlarmDevice, "Fire Alarm Device 1");
+        let out = store.add_unit(UnitKind::Output, "Output 1");
+        store.assign_to_zone(det, dz).unwrap();
+        store.assign_to_zone(dz, az).unwrap();
+        store.assign_to_zone(fad, az).unwrap();
+        let rule = store
+            .cause_effect_mut()
+            .add_rule("Rule 1", crate::model::InputLogic::Any)
+            .unwrap();
+        store.set_rule_input(rule, det, true).unwrap();
+        store.set_rule_output(rule, out, true).unwrap();
+        let mut status = PanelStatus::new();
+        status.handle(&store, det, StateEvent::Disable).unwrap();
+
+        let mut simulation = Simulation::new();
+        simulation.trigger(&store, det).unwrap();
+        status.follow_simulation(&store, &simulation);
+
+        for id in [dz, az, fad, out] {
+            assert_eq!(status.state(id), UnitState::Normal);
+        }
+        assert_eq!(status.state(det), UnitState::Disabled);
+
+        // Once enabled again, the same alarm goes through
+        status.handle(&store, det, StateEvent::Enable).unwrap();
+        status.follow_simulation(&store, &simulation);
+        assert_eq!(status.state(det), UnitState::Fire);
+        assert_eq!(status.state(dz), UnitState::Fire);
+        assert_eq!(status.state(fad), UnitState::Active);
+        assert_eq!(status.state(out), UnitState::Active);
+    }
+
+    #[test]
+    fn test_removed_units_are_forgotten() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let mut status = PanelStatus::new();
+        let mut simulation = Simulation::new();
+        simulation.trigger(&store, det).unwrap();
+        status.follow_simulation(&store, &simulation);
+        assert_eq!(status.state(det), UnitState::Fire);
+
+        store.remove_unit(det);
+        simulation.refresh(&store);
+        status.retain(&store);
+        assert!(simulation.triggered().is_empty());
+        assert!(status.unacknowledged().is_empty());
+
+        // A unit that takes the same id later starts out Normal
+        store.clear();
+        let reused = store.add_unit(UnitKind::Detector, "Detector 1");
+        assert_eq!(reused, det);
+        assert_eq!(status.state(reused), UnitState::Normal);
+    }
+
+    #[test]
+    fn test_follows_simulation_but_keeps_disablements() {
+        let mut store = UnitStore::new();
+        let det1 = store.add_unit(UnitKind::Detector, "Detector 1");
+        let det2 = store.add_unit(UnitKind::Detector, "Detector 2");
+        let dz = store.add_unit(UnitKind::DetectionZone, "Detection Zone 1");
+        let az = store.add_unit(UnitKind::AlarmZone, "Alarm Zone 1");
+        let fad = store.add_unit(UnitKind::FireAlarmDevice, "Fire Alarm Device 1");
+        store.assign_to_zone(det1, dz).unwrap();
+        store.assign_to_zone(dz, az).unwrap();
+        store.assign_to_zone(fad, az).unwrap();
+        let mut status = PanelStatus::new();
+        status.handle(&store, fad, StateEvent::Disable).unwrap();
+        status
+            .handle(&store, det2, StateEvent::Fault(FaultKind::Missing))
+            .unwrap();
+
+        let mut simulation = Simulation::new();
+        simulation.trigger(&store, det1).unwrap();
+        status.follow_simulation(&store, &simulation);
+
+        assert_eq!(status.state(det1), UnitState::Fire);
+        assert_eq!(status.state(dz), UnitState::Fire);
+        assert_eq!(status.state(az), UnitState::Active);
+        assert_eq!(status.state(fad), UnitState::Disabled);
+        assert_eq!(status.counts_for(&store, PanelFilter::All).fires(), 2);
+
+        // Fires stay latched until the panel is reset
+        simulation.reset_all(&store);
+        status.follow_simulation(&store, &simulation);
+        assert_eq!(status.state(det1), UnitState::Fire);
+        assert_eq!(status.state(az), UnitState::Normal);
+
+        status.reset().unwrap();
+        status.follow_simulation(&store, &simulation);
+        assert_eq!(status.state(det1), UnitState::Normal);
+        assert_eq!(status.state(dz), UnitState::Normal);
+        assert_eq!(status.state(det2), UnitState::Fault(FaultKind::Missing));
+    }
+}
@@ -1,14 +1,54 @@
-#[derive(Debug, Clone, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+use super::details::{check_attribute, check_description, check_serial_number};
+use super::{
+    Attributes, DetailError, InstallDate, Location, LoopAddress, PropertyError, UnitKind,
+    UnitProperties,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Unit {
     id: u64,
+    kind: UnitKind,
     name: String,
+    // None only for the Root unit
+    parent: Option<u64>,
+    #[serde(default)]
+    properties: UnitProperties,
+    // Only addressable kinds have one, and only once placed on a loop
+    #[serde(default)]
+    address: Option<LoopAddress>,
+    // Whether the device has a built-in short-circuit isolator
+    #[serde(default)]
+    isolator: bool,
+    // Commissioning details, all optional
+    #[serde(default)]
+    location: Location,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    install_date: Option<InstallDate>,
+    #[serde(default)]
+    serial_number: String,
+    #[serde(default)]
+    attributes: Attributes,
 }
 
 impl Unit {
-    pub fn new(id: u64, name: &str) -> Self {
+    pub fn new(id: u64, kind: UnitKind, name: &str) -> Self {
         Self {
             id,
+            kind,
             name: name.to_string(),
+            parent: None,
+            properties: UnitProperties::default_for(kind),
+            address: None,
+            isolator: false,
+            location: Location::default(),
+            description: String::new(),
+            install_date: None,
+            serial_number: String::new(),
+            attributes: Attributes::new(),
         }
     }
 
@@ -16,6 +56,10 @@ impl Unit {
         self.id
     }
 
+    pub fn kind(&self) -> UnitKind {
+        self.kind
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -23,4 +67,141 @@ impl Unit {
     pub fn set_name(&mut self, name: &str) {
         self.name = name.to_string();
     }
+
+    /// The kind-specific settings. Falls back to the defaults for the kind if none were stored.
+    pub fn properties(&self) -> UnitProperties {
+        if self.properties.fits(self.kind) {
+            self.properties
+        } else {
+            UnitProperties::default_for(self.kind)
+        }
+    }
+
+    pub fn set_properties(&mut self, properties: UnitProperties) -> Result<(), PropertyError> {
+        properties.validate(self.kind)?;
+        self.properties = properties;
+        Ok(())
+    }
+
+    pub fn address(&self) -> Option<LoopAddress> {
+        self.address
+    }
+
+    // Only the store may address units, so it can keep addresses unique
+    pub(super) fn set_address(&mut self, address: Option<LoopAddress>) {
+        self.address = address;
+    }
+
+    /// Whether the device has a built-in short-circuit isolator, limiting what a single loop fault takes out.
+    pub fn has_isolator(&self) -> bool {
+        self.isolator
+    }
+
+    pub fn set_isolator(&mut self, isolator: bool) {
+        self.isolator = isolator;
+    }
+
+    /// Where the unit is installed.
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+
+    pub fn set_location(&mut self, location: Location) -> Result<(), DetailError> {
+        location.validate()?;
+        self.location = location;
+        Ok(())
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Sets the free-text description. Trailing whitespace is dropped.
+    pub fn set_description(&mut self, description: &str) -> Result<(), DetailError> {
+        let description = description.trim_end();
+        check_description(description)?;
+        self.description = description.to_string();
+        Ok(())
+    }
+
+    pub fn install_date(&self) -> Option<InstallDate> {
+        self.install_date
+    }
+
+    pub fn set_install_date(&mut self, install_date: Option<InstallDate>) {
+        self.install_date = install_date;
+    }
+
+    pub fn serial_number(&self) -> &str {
+        &self.serial_number
+    }
+
+    /// Sets the manufacturer's serial number. Surrounding whitespace is dropped.
+    pub fn set_serial_number(&mut self, serial_number: &str) -> Result<(), DetailError> {
+        let serial_number = serial_number.trim();
+        check_serial_number(serial_number)?;
+        self.serial_number = serial_number.to_string();
+        Ok(())
+    }
+
+    /// Custom key/value pairs, in key order.
+    pub fn attributes(&self) -> &Attributes {
+        &self.attributes
+    }
+
+    /// Sets attribute `key` to `value`, adding it if the unit does not have it yet. Surrounding whitespace is dropped.
+    pub fn set_attribute(&mut self, key: &str, value: &str) -> Result<(), DetailError> {
+        let (key, value) = (key.trim(), value.trim());
+        check_attribute(&self.attributes, key, value)?;
+        self.attributes.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    /// Removes attribute `key`. Returns false if the unit does not have it.
+    pub fn remove_attribute(&mut self, key: &str) -> bool {
+        self.attributes.remove(key).is_some()
+    }
+
+    /// Replaces every attribute, checking each one.
+    pub fn set_attributes(&mut self, attributes: Attributes) -> Result<(), DetailError> {
+        let mut checked = Attributes::new();
+        for (key, value) in attributes {
+            check_attribute(&checked, &key, &value)?;
+            checked.insert(key, value);
+        }
+        self.attributes = checked;
+        Ok(())
+    }
+
+    /// Checks the commissioning details, which the setters guarantee but a file read from elsewhere may not.
+    pub fn check_details(&self) -> Result<(), DetailError> {
+        self.location.validate()?;
+        check_description(&self.description)?;
+        check_serial_number(&self.serial_number)?;
+        let mut checked = Attributes::new();
+        for (key, value) in &self.attributes {
+            check_attribute(&checked, key, value)?;
+            checked.insert(key.clone(), value.clone());
+        }
+        Ok(())
+    }
+
+    pub fn parent(&self) -> Option<u64> {
+        self.parent
+    }
+
+    // Only the store may re-parent units, so it can keep the tree free of cycles
+    pub(super) fn set_parent(&mut self, parent: Option<u64>) {
+        self.parent = parent;
+    }
+
+    // Only the store may change kinds, so it can drop memberships and rule references the new kind cannot have
+    pub(super) fn set_kind(&mut self, kind: UnitKind) {
+        self.kind = kind;
+        self.properties = UnitProperties::default_for(kind);
+        if !kind.is_addressable() {
+            self.address = None;
+            self.isolator = false;
+        }
+    }
 }
@@ -0,0 +1,932 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use super::{
+    check_description, check_serial_number, format_attributes, parse_attributes, Attributes,
+    DetailError, InstallDate, Location, LoopAddress, Unit, UnitKind, UnitStore,
+    DEFAULT_LOOP_CAPACITY, MAX_LOOP_ADDRESS, MAX_NAME_LENGTH,
+};
+
+/// The columns written by [`export_csv`], in order.
+pub const CSV_COLUMNS: [&str; 11] = [
+    "id",
+    "kind",
+    "name",
+    "zone",
+    "loop",
+    "address",
+    "location",
+    "description",
+    "install_date",
+    "serial_number",
+    "attributes",
+];
+
+/// Writes every unit except the Root as CSV, one row per unit in store order.
+///
+/// Zones are written by name, locations as "building / floor / room" and attributes as "key=value; key=value".
+pub fn export_csv(store: &UnitStore) -> String {
+    let mut csv = CSV_COLUMNS.join(",");
+    csv.push('\n');
+    for unit in store.get_all_units().iter().skip(1) {
+        let zone = store
+            .zone_of(unit.id())
+            .and_then(|zone| store.get_unit(zone))
+            .map(|zone| zone.name().to_string())
+            .unwrap_or_default();
+        let (loop_number, address) = match unit.address() {
+            Some(address) => (address.loop_number.to_string(), address.address.to_string()),
+            None => (String::new(), String::new()),
+        };
+        let fields = [
+            unit.id().to_string(),
+            unit.kind().label().to_string(),
+            unit.name().to_string(),
+            zone,
+            loop_number,
+            address,
+            unit.location().to_string(),
+            unit.description().to_string(),
+            unit.install_date()
+                .map(|date| date.to_string())
+                .unwrap_or_default(),
+            unit.serial_number().to_string(),
+            format_attributes(unit.attributes()),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| quote(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+// Quotes a field if it contains a separator, a quote or a line break
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Why a CSV row cannot be imported.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CsvProblem {
+    UnterminatedQuote,
+    MissingHeader,
+    MissingColumn(&'static str),
+    WrongFieldCount {
+        expected: usize,
+        found: usize,
+    },
+    InvalidId(String),
+    UnknownUnit(u64),
+    UnknownKind(String),
+    /// Rows may not change the kind of an existing unit.
+    KindMismatch {
+        id: u64,
+        existing: UnitKind,
+        found: UnitKind,
+    },
+    EmptyName,
+    NameTooLong {
+        max: usize,
+    },
+    DuplicateName(String),
+    NotZoned(UnitKind),
+    UnknownZone(String),
+    NotAddressable(UnitKind),
+    /// Loop and address must both be given or both be empty.
+    IncompleteAddress,
+    InvalidNumber(String),
+    /// The loop number is a gap between existing loops. Loops above the highest one are added on import.
+    UnknownLoop(u16),
+    AddressOutOfRange {
+        address: u16,
+        capacity: u16,
+    },
+    DuplicateAddress(LoopAddress),
+    InvalidDetail(DetailError),
+}
+
+impl fmt::Display for CsvProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvProblem::UnterminatedQuote => write!(f, "a quoted field is never closed"),
+            CsvProblem::MissingHeader => write!(f, "the file has no header row"),
+            CsvProblem::MissingColumn(column) => {
+                write!(f, "the header has no \"{}\" column", column)
+            }
+            CsvProblem::WrongFieldCount { expected, found } => {
+                write!(f, "expected {} fields but found {}", expected, found)
+            }
+            CsvProblem::InvalidId(id) => write!(f, "\"{}\" is not a unit id", id),
+            CsvProblem::UnknownUnit(id) => write!(f, "unit {} does not exist", id),
+            CsvProblem::UnknownKind(kind) => write!(f, "\"{}\" is not a unit kind", kind),
+            CsvProblem::KindMismatch {
+                id,
+                existing,
+                found,
+            } => write!(f, "unit {} is a {}, not a {}", id, existing, found),
+            CsvProblem::EmptyName => write!(f, "name must not be empty"),
+            CsvProblem::NameTooLong { max } => {
+                write!(f, "name must be at most {} characters", max)
+            }
+            CsvProblem::DuplicateName(name) => {
+                write!(
+                    f,
+                    "another unit of the same kind is already named \"{}\"",
+                    name
+                )
+            }
+            CsvProblem::NotZoned(kind) => write!(f, "a {} cannot be in a zone", kind),
+            CsvProblem::UnknownZone(zone) => write!(f, "there is no zone named \"{}\"", zone),
+            CsvProblem::NotAddressable(kind) => write!(f, "a {} has no loop address", kind),
+            CsvProblem::IncompleteAddress => {
+                write!(f, "loop and address must both be given or both be empty")
+            }
+            CsvProblem::InvalidNumber(value) => write!(f, "\"{}\" is not a valid number", value),
+            CsvProblem::UnknownLoop(number) => write!(f, "loop {} does not exist", number),
+            CsvProblem::AddressOutOfRange { address, capacity } => {
+                write!(f, "address {} is outside 1 to {}", address, capacity)
+            }
+            CsvProblem::DuplicateAddress(address) => {
+                write!(f, "address {} is used by another unit", address)
+            }
+            CsvProblem::InvalidDetail(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+/// A [`CsvProblem`] with the line of the file it was found on, counting from 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvRowError {
+    pub line: usize,
+    pub problem: CsvProblem,
+}
+
+impl fmt::Display for CsvRowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.problem)
+    }
+}
+
+impl std::error::Error for CsvRowError {}
+
+/// One unit to create or update.
+///
+/// Columns missing from the file are `None` and leave that part of an existing unit as it is; empty cells clear it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvRow {
+    pub line: usize,
+    /// The unit to update, or `None` to create one.
+    pub id: Option<u64>,
+    pub kind: UnitKind,
+    pub name: String,
+    pub zone: Option<Option<String>>,
+    pub address: Option<Option<LoopAddress>>,
+    pub details: CsvDetails,
+}
+
+/// The commissioning details of a [`CsvRow`]. Each is `None` when its column is missing from the file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CsvDetails {
+    pub location: Option<Location>,
+    pub description: Option<String>,
+    pub install_date: Option<Option<InstallDate>>,
+    pub serial_number: Option<String>,
+    pub attributes: Option<Attributes>,
+}
+
+/// A parsed CSV file, checked against a store and ready to preview and apply.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CsvImport {
+    rows: Vec<CsvRow>,
+    errors: Vec<CsvRowError>,
+    unchanged: usize,
+}
+
+// Header positions of the columns the import reads
+struct Columns {
+    count: usize,
+    id: Option<usize>,
+    kind: usize,
+    name: usize,
+    zone: Option<usize>,
+    loop_number: Option<usize>,
+    address: Option<usize>,
+    location: Option<usize>,
+    description: Option<usize>,
+    install_date: Option<usize>,
+    serial_number: Option<usize>,
+    attributes: Option<usize>,
+}
+
+impl CsvImport {
+    /// Parses `text` and checks every row against `store`.
+    ///
+    /// The header decides which columns are read, in any order; `kind` and `name` are required, unknown columns are
+    /// ignored. Rows with an id update that unit, rows without one create a unit. Rows that would not change anything
+    /// are dropped.
+    pub fn parse(store: &UnitStore, text: &str) -> Self {
+        let mut import = Self::default();
+        let records = match parse_records(text) {
+            Ok(records) => records,
+            Err(err) => {
+                import.errors.push(err);
+                return import;
+            }
+        };
+        let mut records = records.into_iter();
+        let Some((header_line, header)) = records.next() else {
+            import.errors.push(CsvRowError {
+                line: 1,
+                problem: CsvProblem::MissingHeader,
+            });
+            return import;
+        };
+        let columns = match Columns::from_header(&header) {
+            Ok(columns) => columns,
+            Err(problem) => {
+                import.errors.push(CsvRowError {
+                    line: header_line,
+                    problem,
+                });
+                return import;
+            }
+        };
+
+        // First pass: what each row is and what it will be called, so zones can be referred to before their row
+        let mut basics = Vec::new();
+        let mut names: HashMap<(UnitKind, String), Option<u64>> = store
+            .get_all_units()
+            .iter()
+            .map(|unit| ((unit.kind(), unit.name().to_string()), Some(unit.id())))
+            .collect();
+        for (line, fields) in records {
+            if fields.iter().all(|field| field.trim().is_empty()) {
+                continue;
+            }
+            match columns.basics(store, &fields) {
+                Ok((id, kind, name)) => {
+                    let owner = names.get(&(kind, name.clone())).copied();
+                    if owner.is_some_and(|owner| owner.is_none() || owner != id) {
+                        import.errors.push(CsvRowError {
+                            line,
+                            problem: CsvProblem::DuplicateName(name),
+                        });
+                        continue;
+                    }
+                    if let Some(unit) = id.and_then(|id| store.get_unit(id)) {
+                        names.remove(&(kind, unit.name().to_string()));
+                    }
+                    names.insert((kind, name.clone()), id);
+                    basics.push((line, fields, id, kind, name));
+                }
+                Err(problem) => import.errors.push(CsvRowError { line, problem }),
+            }
+        }
+
+        // Second pass: zones and addresses, now that every final name is known
+        let highest_loop = store.loops().iter().map(|l| l.number()).max().unwrap_or(0);
+        let mut taken: HashSet<LoopAddress> = store
+            .get_all_units()
+            .iter()
+            .filter(|unit| {
+                columns.address.is_none()
+                    || !basics.iter().any(|(.., id, _, _)| *id == Some(unit.id()))
+            })
+            .filter_map(|unit| unit.address())
+            .collect();
+        for (line, fields, id, kind, name) in basics {
+            let zone = match columns.zone(&fields, kind, &names) {
+                Ok(zone) => zone,
+                Err(problem) => {
+                    import.errors.push(CsvRowError { line, problem });
+                    continue;
+                }
+            };
+            let address = match columns.address(store, &fields, kind, highest_loop) {
+                Ok(address) => address,
+                Err(problem) => {
+                    import.errors.push(CsvRowError { line, problem });
+                    continue;
+                }
+            };
+            let details = match columns.details(&fields) {
+                Ok(details) => details,
+                Err(problem) => {
+                    import.errors.push(CsvRowError { line, problem });
+                    continue;
+                }
+            };
+            if let Some(Some(address)) = address {
+                if !taken.insert(address) {
+                    import.errors.push(CsvRowError {
+                        line,
+                        problem: CsvProblem::DuplicateAddress(address),
+                    });
+                    continue;
+                }
+            }
+
+            let row = CsvRow {
+                line,
+                id,
+                kind,
+                name,
+                zone,
+                address,
+                details,
+            };
+            if row.is_unchanged(store) {
+                import.unchanged += 1;
+            } else {
+                import.rows.push(row);
+            }
+        }
+
+        import.errors.sort_by_key(|err| err.line);
+        import
+    }
+
+    /// The rows that will change the store, in file order.
+    pub fn rows(&self) -> &[CsvRow] {
+        &self.rows
+    }
+
+    /// Problems found while parsing, by line. Nothing is imported while there are any.
+    pub fn errors(&self) -> &[CsvRowError] {
+        &self.errors
+    }
+
+    /// How many rows create a unit.
+    pub fn creations(&self) -> usize {
+        self.rows.iter().filter(|row| row.id.is_none()).count()
+    }
+
+    /// How many rows change an existing unit.
+    pub fn updates(&self) -> usize {
+        self.rows.iter().filter(|row| row.id.is_some()).count()
+    }
+
+    /// How many rows matched their unit exactly and were dropped.
+    pub fn unchanged(&self) -> usize {
+        self.unchanged
+    }
+
+    /// Whether the import has changes and no errors.
+    pub fn can_apply(&self) -> bool {
+        self.errors.is_empty() && !self.rows.is_empty()
+    }
+
+    /// Creates and updates the units. Returns false, changing nothing, if the import has errors.
+    ///
+    /// Units are created, renamed and given their details first, then zones and addresses are set, so rows may refer to
+    /// zones further down the file. Loops above the highest existing loop are added with [`DEFAULT_LOOP_CAPACITY`].
+    pub fn apply(&self, store: &mut UnitStore) -> bool {
+        if !self.errors.is_empty() {
+            return false;
+        }
+
+        let mut ids = Vec::with_capacity(self.rows.len());
+        for row in &self.rows {
+            let id = match row.id {
+                Some(id) => {
+                    if let Some(unit) = store.get_unit_mut(id) {
+                        unit.set_name(&row.name);
+                    }
+                    id
+                }
+                None => store.add_unit(row.kind, &row.name),
+            };
+            if let Some(unit) = store.get_unit_mut(id) {
+                row.details.apply(unit);
+            }
+            ids.push(id);
+        }
+
+        for (row, id) in self.rows.iter().zip(&ids) {
+            match &row.zone {
+                Some(Some(zone_name)) => {
+                    let zone = row.kind.zone_kind().and_then(|zone_kind| {
+                        store
+                            .units_of_kind(zone_kind)
+                            .find(|zone| zone.name() == zone_name)
+                            .map(|zone| zone.id())
+                    });
+                    if let Some(zone) = zone {
+                        let _ = store.assign_to_zone(*id, zone);
+                    }
+                }
+                Some(None) => {
+                    store.unassign_from_zone(*id);
+                }
+                None => {}
+            }
+        }
+
+        // Free every address first so units can swap addresses
+        for (row, id) in self.rows.iter().zip(&ids) {
+            if row.address.is_some() && row.kind.is_addressable() {
+                let _ = store.set_address(*id, None);
+            }
+        }
+        let highest_loop = self
+            .rows
+            .iter()
+            .filter_map(|row| row.address.flatten())
+            .map(|address| address.loop_number)
+            .max()
+            .unwrap_or(0);
+        while highest_loop > 0 && store.loops().iter().all(|l| l.number() < highest_loop) {
+            if store.add_loop(DEFAULT_LOOP_CAPACITY).is_err() {
+                break;
+            }
+        }
+        for (row, id) in self.rows.iter().zip(&ids) {
+            if let Some(Some(address)) = row.address {
+                let _ = store.set_address(*id, Some(address));
+            }
+        }
+        true
+    }
+}
+
+impl CsvRow {
+    fn is_unchanged(&self, store: &UnitStore) -> bool {
+        let Some(unit) = self.id.and_then(|id| store.get_unit(id)) else {
+            return false;
+        };
+        let zone_name = store
+            .zone_of(unit.id())
+            .and_then(|zone| store.get_unit(zone))
+            .map(|zone| zone.name().to_string());
+        unit.name() == self.name
+            && self.zone.as_ref().is_none_or(|zone| *zone == zone_name)
+            && self.address.is_none_or(|address| address == unit.address())
+            && self.details.is_unchanged(unit)
+    }
+}
+
+impl CsvDetails {
+    fn is_unchanged(&self, unit: &Unit) -> bool {
+        self.location
+            .as_ref()
+            .is_none_or(|location| location == unit.location())
+            && self
+                .description
+                .as_ref()
+                .is_none_or(|description| description == unit.description())
+            && self
+                .install_date
+                .is_none_or(|date| date == unit.install_date())
+            && self
+                .serial_number
+                .as_ref()
+                .is_none_or(|serial_number| serial_number == unit.serial_number())
+            && self
+                .attributes
+                .as_ref()
+                .is_none_or(|attributes| attributes == unit.attributes())
+    }
+
+    // Every detail was checked while parsing, so the setters cannot refuse them
+    fn apply(&self, unit: &mut Unit) {
+        if let Some(location) = &self.location {
+            let _ = unit.set_location(location.clone());
+        }
+        if let Some(description) = &self.description {
+            let _ = unit.set_description(description);
+        }
+        if let Some(install_date) = self.install_date {
+            unit.set_install_date(install_date);
+        }
+        if let Some(serial_number) = &self.serial_number {
+            let _ = unit.set_serial_number(serial_number);
+        }
+        if let Some(attributes) = &self.attributes {
+            let _ = unit.set_attributes(attributes.clone());
+        }
+    }
+}
+
+impl Columns {
+    fn from_header(header: &[String]) -> Result<Self, CsvProblem> {
+        let position = |name: &str| {
+            header
+                .iter()
+                .position(|column| column.trim().eq_ignore_ascii_case(name))
+        };
+        Ok(Self {
+            count: header.len(),
+            id: position("id"),
+            kind: position("kind").ok_or(CsvProblem::MissingColumn("kind"))?,
+            name: position("name").ok_or(CsvProblem::MissingColumn("name"))?,
+            zone: position("zone"),
+            loop_number: position("loop"),
+            address: position("address"),
+            location: position("location"),
+            description: position("description"),
+            install_date: position("install_date"),
+            serial_number: position("serial_number"),
+            attributes: position("attributes"),
+        })
+    }
+
+    fn basics(
+        &self,
+        store: &UnitStore,
+        fields: &[String],
+    ) -> Result<(Option<u64>, UnitKind, String), CsvProblem> {
+        if fields.len() != self.count {
+            return Err(CsvProblem::WrongFieldCount {
+                expected: self.count,
+                found: fields.len(),
+            });
+        }
+
+        let kind_label = fields[self.kind].trim();
+        let kind = UnitKind::CONFIGURABLE
+            .into_iter()
+            .find(|kind| kind.label().eq_ignore_ascii_case(kind_label))
+            .ok_or_else(|| CsvProblem::UnknownKind(kind_label.to_string()))?;
+
+        let id = match self.id.map(|column| fields[column].trim()) {
+            None | Some("") => None,
+            Some(text) => {
+                let id: u64 = text
+                    .parse()
+                    .map_err(|_| CsvProblem::InvalidId(text.to_string()))?;
+                let existing = store.get_unit(id).ok_or(CsvProblem::UnknownUnit(id))?;
+                if existing.kind() != kind {
+                    return Err(CsvProblem::KindMismatch {
+                        id,
+                        existing: existing.kind(),
+                        found: kind,
+                    });
+                }
+                Some(id)
+            }
+        };
+
+        let name = fields[self.name].trim().to_string();
+        if name.is_empty() {
+            return Err(CsvProblem::EmptyName);
+        }
+        if name.chars().count() > MAX_NAME_LENGTH {
+            return Err(CsvProblem::NameTooLong {
+                max: MAX_NAME_LENGTH,
+            });
+        }
+        Ok((id, kind, name))
+    }
+
+    fn zone(
+        &self,
+        fields: &[String],
+        kind: UnitKind,
+        names: &HashMap<(UnitKind, String), Option<u64>>,
+    ) -> Result<Option<Option<String>>, CsvProblem> {
+        let Some(column) = self.zone else {
+            return Ok(None);
+        };
+        let zone = fields[column].trim();
+        if zone.is_empty() {
+            return Ok(Some(None));
+        }
+        let zone_kind = kind.zone_kind().ok_or(CsvProblem::NotZoned(kind))?;
+        if !names.contains_key(&(zone_kind, zone.to_string())) {
+            return Err(CsvProblem::UnknownZone(zone.to_string()));
+        }
+        Ok(Some(Some(zone.to_string())))
+    }
+
+    fn details(&self, fields: &[String]) -> Result<CsvDetails, CsvProblem> {
+        let field = |column: Option<usize>| column.map(|column| fields[column].as_str());
+        let invalid = CsvProblem::InvalidDetail;
+        let description = field(self.description).map(|text| text.trim_end().to_string());
+        if let Some(description) = &description {
+            check_description(description).map_err(invalid)?;
+        }
+        let serial_number = field(self.serial_number).map(|text| text.trim().to_string());
+        if let Some(serial_number) = &serial_number {
+            check_serial_number(serial_number).map_err(invalid)?;
+        }
+        Ok(CsvDetails {
+            location: field(self.location)
+                .map(str::parse)
+                .transpose()
+                .map_err(invalid)?,
+            description,
+            install_date: field(self.install_date)
+                .map(|text| match text.trim() {
+                    "" => Ok(None),
+                    text => text.parse().map(Some),
+                })
+                .transpose()
+                .map_err(invalid)?,
+            serial_number,
+            attributes: field(self.attributes)
+                .map(parse_attributes)
+                .transpose()
+                .map_err(invalid)?,
+        })
+    }
+
+    fn address(
+        &self,
+        store: &UnitStore,
+        fields: &[String],
+        kind: UnitKind,
+        highest_loop: u16,
+    ) -> Result<Option<Option<LoopAddress>>, CsvProblem> {
+        let (Some(loop_column), Some(address_column)) = (self.loop_number, self.address) else {
+            return Ok(None);
+        };
+        let loop_text = fields[loop_column].trim();
+        let address_text = fields[address_column].trim();
+        match (loop_text.is_empty(), address_text.is_empty()) {
+            (true, true) => return Ok(Some(None)),
+            (false, false) => {}
+            _ => return Err(CsvProblem::IncompleteAddress),
+        }
+        if !kind.is_addressable() {
+            return Err(CsvProblem::NotAddressable(kind));
+        }
+
+        let parse = |text: &str| {
+            text.parse::<u16>()
+                .map_err(|_| CsvProblem::InvalidNumber(text.to_string()))
+        };
+        let loop_number = parse(loop_text)?;
+        let address = parse(address_text)?;
+        let capacity = match store.get_loop(loop_number) {
+            Some(l) => l.capacity(),
+            None if loop_number > highest_loop => DEFAULT_LOOP_CAPACITY,
+            None => return Err(CsvProblem::UnknownLoop(loop_number)),
+        };
+        if address == 0 || address > capacity.min(MAX_LOOP_ADDRESS) {
+            return Err(CsvProblem::AddressOutOfRange { address, capacity });
+        }
+        Ok(Some(Some(LoopAddress::new(loop_number, address))))
+    }
+}
+
+// Splits CSV text into records of fields, each with the line it starts on. Quoted fields may contain separators,
+// doubled quotes and line breaks.
+fn parse_records(text: &str) -> Result<Vec<(usize, Vec<String>)>, CsvRowError> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => in_quotes = true,
+            ',' => fields.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                fields.push(std::mem::take(&mut field));
+                records.push((record_line, std::mem::take(&mut fields)));
+                line += 1;
+                record_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(CsvRowError {
+            line: record_line,
+            problem: CsvProblem::UnterminatedQuote,
+        });
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((record_line, fields));
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_store() -> UnitStore {
+        let mut store = UnitStore::new();
+        let zone = store.add_unit(UnitKind::DetectionZone, "Ground Floor");
+        let det = store.add_unit(UnitKind::Detector, "Kitchen, \"north\"");
+        store.assign_to_zone(det, zone).unwrap();
+        store.add_loop(10).unwrap();
+        store.auto_address(det).unwrap();
+        let unit = store.get_unit_mut(det).unwrap();
+        unit.set_location(Location::new("Block A", "2", "201"))
+            .unwrap();
+        unit.set_description("Above the door\nfacing north")
+            .unwrap();
+        unit.set_install_date("2024-05-01".parse().ok());
+        unit.set_serial_number("AP-123").unwrap();
+        unit.set_attribute("Manufacturer", "Apollo").unwrap();
+        unit.set_attribute("Sensitivity", "High").unwrap();
+        store
+    }
+
+    #[test]
+    fn test_export_quotes_and_names_zones() {
+        let csv = export_csv(&sample_store());
+
+        assert_eq!(
+            csv,
+            "id,kind,name,zone,loop,address,location,description,install_date,serial_number,attributes\n\
+             1,Detection Zone,Ground Floor,,,,,,,,\n\
+             2,Detector,\"Kitchen, \"\"north\"\"\",Ground Floor,1,1,Block A / 2 / 201,\"Above the door\nfacing north\",2024-05-01,AP-123,Manufacturer=Apollo; Sensitivity=High\n"
+        );
+    }
+
+    #[test]
+    fn test_export_then_import_changes_nothing() {
+        let store = sample_store();
+
+        let import = CsvImport::parse(&store, &export_csv(&store));
+
+        assert!(import.errors().is_empty());
+        assert!(import.rows().is_empty());
+        assert_eq!(import.unchanged(), 2);
+    }
+
+    #[test]
+    fn test_import_creates_and_updates() {
+        let mut store = sample_store();
+        let csv = "kind,name,zone,id,loop,address\n\
+                   Detector,Hall,First Floor,,2,5\n\
+                   detection zone,First Floor,,,,\n\
+                   Detector,Kitchen,Ground Floor,2,1,1\n";
+
+        let import = CsvImport::parse(&store, csv);
+
+        assert_eq!(import.errors(), &[]);
+        assert_eq!((import.creations(), import.updates()), (2, 1));
+        assert!(import.apply(&mut store));
+        let hall = store
+            .units_of_kind(UnitKind::Detector)
+            .find(|unit| unit.name() == "Hall")
+            .unwrap();
+        let first_floor = store
+            .units_of_kind(UnitKind::DetectionZone)
+            .find(|unit| unit.name() == "First Floor")
+            .unwrap();
+        assert_eq!(store.zone_of(hall.id()), Some(first_floor.id()));
+        assert_eq!(hall.address(), Some(LoopAddress::new(2, 5)));
+        assert_eq!(store.get_unit(2).unwrap().name(), "Kitchen");
+    }
+
+    #[test]
+    fn test_row_errors_have_line_numbers() {
+        let store = sample_store();
+        let csv = "id,kind,name,zone,loop,address\n\
+                   ,Sprinkler,Hall,,,\n\
+                   ,Detector,Hall,Nowhere,,\n\
+                   1,Detector,Hall 2,,,\n\
+                   ,Output,\"Bell\n\
+                   Relay\",,1,1\n\
+                   ,Detector,Hall 3,,1,\n\
+                   ,Alarm Zone,Upstairs,,,,\n";
+
+        let import = CsvImport::parse(&store, csv);
+
+        let errors: Vec<(usize, CsvProblem)> = import
+            .errors()
+            .iter()
+            .map(|err| (err.line, err.problem.clone()))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (2, CsvProblem::UnknownKind("Sprinkler".to_string())),
+                (3, CsvProblem::UnknownZone("Nowhere".to_string())),
+                (
+                    4,
+                    CsvProblem::KindMismatch {
+                        id: 1,
+                        existing: UnitKind::DetectionZone,
+                        found: UnitKind::Detector
+                    }
+                ),
+                (5, CsvProblem::DuplicateAddress(LoopAddress::new(1, 1))),
+                (7, CsvProblem::IncompleteAddress),
+                (
+                    8,
+                    CsvProblem::WrongFieldCount {
+                        expected: 6,
+                        found: 7
+                    }
+                ),
+            ]
+        );
+        assert!(!import.can_apply());
+    }
+
+    #[test]
+    fn test_import_sets_and_checks_details() {
+        let mut store = sample_store();
+        let csv = "id,kind,name,location,install_date,serial_number,attributes\n\
+                   2,Detector,\"Kitchen, \"\"north\"\"\",Block B / 1 / ,,,Colour=White\n\
+                   ,Output,Bell,,2024-02-30,,\n\
+                   ,Output,Relay,Block B / 1,,,\n\
+                   ,Output,Siren,,,SN 1,\n";
+
+        let import = CsvImport::parse(&store, csv);
+        let problems: Vec<(usize, &CsvProblem)> = import
+            .errors()
+            .iter()
+            .map(|err| (err.line, &err.problem))
+            .collect();
+        assert_eq!(problems.len(), 3);
+        assert!(matches!(
+            problems[0],
+            (3, CsvProblem::InvalidDetail(DetailError::InvalidDate(_)))
+        ));
+        assert!(matches!(
+            problems[1],
+            (
+                4,
+                CsvProblem::InvalidDetail(DetailError::InvalidLocation(_))
+            )
+        ));
+        assert!(matches!(
+            problems[2],
+            (
+                5,
+                CsvProblem::InvalidDetail(DetailError::ForbiddenCharacter { .. })
+            )
+        ));
+
+        let import = CsvImport::parse(
+            &store,
+            csv.lines().take(2).collect::<Vec<_>>().join("\n").as_str(),
+        );
+        assert!(import.apply(&mut store));
+        let unit = store.get_unit(2).unwrap();
+        assert_eq!(unit.location(), &Location::new("Block B", "1", ""));
+        assert_eq!(unit.install_date(), None);
+        assert_eq!(unit.serial_number(), "");
+        assert_eq!(unit.attributes().len(), 1);
+        // The description column was not in the file, so it is kept
+        assert_eq!(unit.description(), "Above the door\nfacing north");
+    }
+
+    #[test]
+    fn test_duplicate_names_within_file_are_refused() {
+        let store = UnitStore::new();
+        let csv = "kind,name\nDetector,Hall\nDetector,Hall\nOutput,Hall\n";
+
+        let import = CsvImport::parse(&store, csv);
+
+        assert_eq!(
+            import.errors(),
+            &[CsvRowError {
+                line: 3,
+                problem: CsvProblem::DuplicateName("Hall".to_string())
+            }]
+        );
+    }
+
+    #[test]
+    fn test_missing_columns_and_quotes() {
+        let store = UnitStore::new();
+
+        let import = CsvImport::parse(&store, "id,name\n1,Hall\n");
+        assert_eq!(
+            import.errors()[0].problem,
+            CsvProblem::MissingColumn("kind")
+        );
+
+        let import = CsvImport::parse(&store, "kind,name\nDetector,\"Hall\n");
+        assert_eq!(
+            import.errors(),
+            &[CsvRowError {
+                line: 2,
+                problem: CsvProblem::UnterminatedQuote
+            }]
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// What a unit is, independent of the name the user gives it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum UnitKind {
+    Root,
+    ManualCallPoint,
+    Detector,
+    DetectionZone,
+    AlarmZone,
+    FireAlarmDevice,
+    Output,
+}
+
+impl UnitKind {
+    /// All kinds that can be created from the Config page, in display order.
+    pub const CONFIGURABLE: [UnitKind; 6] = [
+        UnitKind::ManualCallPoint,
+        UnitKind::Detector,
+        UnitKind::DetectionZone,
+        UnitKind::AlarmZone,
+        UnitKind::FireAlarmDevice,
+        UnitKind::Output,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            UnitKind::Root => "Root",
+            UnitKind::ManualCallPoint => "Manual Call Point",
+            UnitKind::Detector => "Detector",
+            UnitKind::DetectionZone => "Detection Zone",
+            UnitKind::AlarmZone => "Alarm Zone",
+            UnitKind::FireAlarmDevice => "Fire Alarm Device",
+            UnitKind::Output => "Output",
+        }
+    }
+
+    pub fn plural_label(&self) -> &'static str {
+        match self {
+            UnitKind::Root => "Root",
+            UnitKind::ManualCallPoint => "Manual Call Points",
+            UnitKind::Detector => "Detectors",
+            UnitKind::DetectionZone => "Detection Zones",
+            UnitKind::AlarmZone => "Alarm Zones",
+            UnitKind::FireAlarmDevice => "Fire Alarm Devices",
+            UnitKind::Output => "Outputs",
+        }
+    }
+
+    /// The kind of zone a unit of this kind can be a member of, if any.
+    pub fn zone_kind(&self) -> Option<UnitKind> {
+        match self {
+            UnitKind::ManualCallPoint | UnitKind::Detector => Some(UnitKind::DetectionZone),
+            UnitKind::DetectionZone | UnitKind::FireAlarmDevice => Some(UnitKind::AlarmZone),
+            UnitKind::Root | UnitKind::AlarmZone | UnitKind::Output => None,
+        }
+    }
+
+    /// Whether units of this kind are zones. Zones group units through zone membership, so in the unit tree they sit
+    /// directly under the Root and have no units below them.
+    pub fn is_zone(&self) -> bool {
+        matches!(self, UnitKind::DetectionZone | UnitKind::AlarmZone)
+    }
+
+    /// Whether units of this kind sit on a loop and need a loop address.
+    pub fn is_addressable(&self) -> bool {
+        matches!(
+            self,
+            UnitKind::ManualCallPoint
+                | UnitKind::Detector
+                | UnitKind::FireAlarmDevice
+                | UnitKind::Output
+        )
+    }
+
+    /// Whether units of this kind can be used as inputs of cause-and-effect rules.
+    pub fn is_cause(&self) -> bool {
+        matches!(
+            self,
+            UnitKind::ManualCallPoint | UnitKind::Detector | UnitKind::DetectionZone
+        )
+    }
+
+    /// Whether units of this kind can be activated by cause-and-effect rules.
+    pub fn is_effect(&self) -> bool {
+        matches!(
+            self,
+            UnitKind::AlarmZone | UnitKind::FireAlarmDevice | UnitKind::Output
+        )
+    }
+}
+
+impl fmt::Display for UnitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
@@ -0,0 +1,404 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::iter::Flatten;
+use std::slice;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{LoopAddress, Unit};
+
+/// Holes below this many are never compacted, so small stores do not rebuild their index on every removal.
+const MIN_HOLES_TO_COMPACT: usize = 64;
+
+/// Units indexed by id, iterated in the order they were inserted.
+///
+/// Lookups and removals are O(1). A removed unit leaves a hole that iteration skips; holes are compacted away once they
+/// outnumber the units, which keeps removal amortised O(1). The children of every unit and the units on every loop are
+/// indexed too, so parents must be changed through [`UnitMap::set_parent`] and addresses through
+/// [`UnitMap::set_address`]. Serializes as a plain list of units.
+#[derive(Debug, Clone, Default)]
+pub struct UnitMap {
+    slots: Vec<Option<Unit>>,
+    // Id to slot. A list holding the same id twice indexes its first slot; see `from`.
+    index: HashMap<u64, usize>,
+    // Parent id to child ids, mirroring the units' parent fields
+    children: HashMap<u64, BTreeSet<u64>>,
+    // Loop number to the used addresses on it and the ids at each, mirroring the units' address fields. Addresses are
+    // unique in the store, but a file from elsewhere may put several units at one.
+    loops: HashMap<u16, BTreeMap<u16, BTreeSet<u64>>>,
+    len: usize,
+}
+
+impl UnitMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `unit` after every other unit, replacing any unit with the same id.
+    pub fn insert(&mut self, unit: Unit) {
+        self.remove(unit.id());
+        if let Some(parent) = unit.parent() {
+            self.children.entry(parent).or_default().insert(unit.id());
+        }
+        if let Some(address) = unit.address() {
+            self.index_address(unit.id(), address);
+        }
+        self.index.insert(unit.id(), self.slots.len());
+        self.slots.push(Some(unit));
+        self.len += 1;
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Unit> {
+        let slot = *self.index.get(&id)?;
+        self.slots[slot].as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut Unit> {
+        let slot = *self.index.get(&id)?;
+        self.slots[slot].as_mut()
+    }
+
+    pub fn remove(&mut self, id: u64) -> Option<Unit> {
+        let slot = self.index.remove(&id)?;
+        let unit = self.slots[slot].take()?;
+        self.len -= 1;
+        if let Some(siblings) = unit
+            .parent()
+            .and_then(|parent| self.children.get_mut(&parent))
+        {
+            siblings.remove(&unit.id());
+        }
+        if let Some(address) = unit.address() {
+            self.unindex_address(unit.id(), address);
+        }
+        let holes = self.slots.len() - self.len;
+        if holes >= MIN_HOLES_TO_COMPACT && holes > self.len {
+            self.compact();
+        }
+        Some(unit)
+    }
+
+    /// Moves unit `id` under `parent`. Returns false if there is no such unit.
+    pub fn set_parent(&mut self, id: u64, parent: Option<u64>) -> bool {
+        let Some(unit) = self.get_mut(id) else {
+            return false;
+        };
+        let old_parent = unit.parent();
+        unit.set_parent(parent);
+        if let Some(siblings) = old_parent.and_then(|old| self.children.get_mut(&old)) {
+            siblings.remove(&id);
+        }
+        if let Some(parent) = parent {
+            self.children.entry(parent).or_default().insert(id);
+        }
+        true
+    }
+
+    /// Puts unit `id` at `address`, or takes it off its loop. Returns false if there is no such unit.
+    ///
+    /// Nothing is checked here; [`UnitStore::set_address`](super::UnitStore::set_address) keeps addresses unique.
+    pub fn set_address(&mut self, id: u64, address: Option<LoopAddress>) -> bool {
+        let Some(unit) = self.get_mut(id) else {
+            return false;
+        };
+        let old_address = unit.address();
+        unit.set_address(address);
+        if let Some(old_address) = old_address {
+            self.unindex_address(id, old_address);
+        }
+        if let Some(address) = address {
+            self.index_address(id, address);
+        }
+        true
+    }
+
+    /// The units on loop `number`, in address order.
+    pub fn on_loop(&self, number: u16) -> impl Iterator<Item = &Unit> {
+        self.loops
+            .get(&number)
+            .into_iter()
+            .flat_map(|addresses| addresses.values().flatten())
+            .filter_map(|id| self.get(*id))
+    }
+
+    /// The units at `address`, in id order. There is at most one unless the units came from a damaged file.
+    pub fn at(&self, address: LoopAddress) -> impl Iterator<Item = &Unit> {
+        self.loops
+            .get(&address.loop_number)
+            .and_then(|addresses| addresses.get(&address.address))
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.get(*id))
+    }
+
+    /// How many different addresses are used on loop `number`.
+    pub fn used_on_loop(&self, number: u16) -> usize {
+        self.loops.get(&number).map_or(0, BTreeMap::len)
+    }
+
+    /// The lowest address from 1 to `capacity` that no unit on loop `number` uses.
+    pub fn first_free_address(&self, number: u16, capacity: u16) -> Option<u16> {
+        let Some(used) = self.loops.get(&number) else {
+            return (capacity > 0).then_some(1);
+        };
+        // A loop whose addresses are all in range is full without looking at each of them
+        let in_range =
+            |entry: Option<(&u16, _)>| entry.is_some_and(|(a, _)| (1..=capacity).contains(a));
+        if used.len() >= capacity as usize
+            && in_range(used.first_key_value())
+            && in_range(used.last_key_value())
+        {
+            return None;
+        }
+        let mut free = 1;
+        for address in used.keys() {
+            if *address > free {
+                break;
+            }
+            if *address == free {
+                free += 1;
+            }
+        }
+        (free <= capacity).then_some(free)
+    }
+
+    fn index_address(&mut self, id: u64, address: LoopAddress) {
+        self.loops
+            .entry(address.loop_number)
+            .or_default()
+            .entry(address.address)
+            .or_default()
+            .insert(id);
+    }
+
+    fn unindex_address(&mut self, id: u64, address: LoopAddress) {
+        let Some(addresses) = self.loops.get_mut(&address.loop_number) else {
+            return;
+        };
+        if let Some(ids) = addresses.get_mut(&address.address) {
+            ids.remove(&id);
+            if ids.is_empty() {
+                addresses.remove(&address.address);
+            }
+        }
+        if addresses.is_empty() {
+            self.loops.remove(&address.loop_number);
+        }
+    }
+
+    /// The units whose parent is `id`, in id order.
+    pub fn children(&self, id: u64) -> impl Iterator<Item = &Unit> {
+        self.children
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .filter_map(|child| self.get(*child))
+    }
+
+    fn compact(&mut self) {
+        self.slots.retain(Option::is_some);
+        self.index.clear();
+        for (slot, unit) in self.slots.iter().flatten().enumerate() {
+            self.index.entry(unit.id()).or_insert(slot);
+        }
+    }
+
+    pub fn first(&self) -> Option<&Unit> {
+        self.iter().next()
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Unit> + Clone {
+        self.slots.iter().flatten()
+    }
+
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut Unit> {
+        self.slots.iter_mut().flatten()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.index.clear();
+        self.children.clear();
+        self.loops.clear();
+        self.len = 0;
+    }
+}
+
+/// Keeps every unit of the list, duplicates included, so project validation can still report them.
+impl From<Vec<Unit>> for UnitMap {
+    fn from(units: Vec<Unit>) -> Self {
+        let mut index = HashMap::with_capacity(units.len());
+        let mut children: HashMap<u64, BTreeSet<u64>> = HashMap::new();
+        for (slot, unit) in units.iter().enumerate() {
+            index.entry(unit.id()).or_insert(slot);
+            if let Some(parent) = unit.parent() {
+                children.entry(parent).or_default().insert(unit.id());
+            }
+        }
+        let mut map = Self {
+            len: units.len(),
+            slots: Vec::new(),
+            index,
+            children,
+            loops: HashMap::new(),
+        };
+        for unit in &units {
+            if let Some(address) = unit.address() {
+                map.index_address(unit.id(), address);
+            }
+        }
+        map.slots = units.into_iter().map(Some).collect();
+        map
+    }
+}
+
+impl<'a> IntoIterator for &'a UnitMap {
+    type Item = &'a Unit;
+    type IntoIter = Flatten<slice::Iter<'a, Option<Unit>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.slots.iter().flatten()
+    }
+}
+
+impl PartialEq for UnitMap {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl Serialize for UnitMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for UnitMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<Unit>::deserialize(deserializer).map(Self::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::UnitKind;
+
+    fn ids(map: &UnitMap) -> Vec<u64> {
+        map.iter().map(|unit| unit.id()).collect()
+    }
+
+    #[test]
+    fn test_insertion_order_survives_removal_and_compaction() {
+        let mut map = UnitMap::new();
+        for id in 0..200 {
+            map.insert(Unit::new(
+                id,
+                UnitKind::Detector,
+                &format!("Detector {}", id),
+            ));
+        }
+        for id in (0..200).filter(|id| id % 3 != 0) {
+            assert_eq!(map.remove(id).map(|unit| unit.id()), Some(id));
+        }
+
+        assert_eq!(map.len(), 67);
+        assert!(map.slots.len() < 200, "holes were compacted");
+        assert_eq!(ids(&map), (0..200).step_by(3).collect::<Vec<_>>());
+        assert_eq!(map.get(99).map(|unit| unit.name()), Some("Detector 99"));
+        assert!(map.get(98).is_none());
+        assert!(map.remove(98).is_none());
+    }
+
+    #[test]
+    fn test_children_follow_parent_changes() {
+        let mut map = UnitMap::new();
+        map.insert(Unit::new(0, UnitKind::Root, "Root"));
+        for id in 1..4 {
+            let mut unit = Unit::new(id, UnitKind::Detector, &format!("Detector {}", id));
+            unit.set_parent(Some(0));
+            map.insert(unit);
+        }
+        let children =
+            |map: &UnitMap, id| map.children(id).map(|unit| unit.id()).collect::<Vec<_>>();
+
+        assert!(map.set_parent(3, Some(1)));
+        assert_eq!(children(&map, 0), vec![1, 2]);
+        assert_eq!(children(&map, 1), vec![3]);
+
+        map.remove(2);
+        assert_eq!(children(&map, 0), vec![1]);
+        assert!(!map.set_parent(2, Some(0)));
+    }
+
+    #[test]
+    fn test_loops_follow_address_changes() {
+        let mut map = UnitMap::new();
+        for id in 1..=3 {
+            map.insert(Unit::new(
+                id,
+                UnitKind::Detector,
+                &format!("Detector {}", id),
+            ));
+        }
+        let on_loop = |map: &UnitMap, number| {
+            map.on_loop(number)
+                .map(|unit| unit.id())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(map.first_free_address(1, 3), Some(1));
+        map.set_address(1, Some(LoopAddress::new(1, 2)));
+        map.set_address(2, Some(LoopAddress::new(1, 1)));
+        assert_eq!(on_loop(&map, 1), vec![2, 1]);
+        assert_eq!(map.first_free_address(1, 3), Some(3));
+        map.set_address(3, Some(LoopAddress::new(1, 3)));
+        assert_eq!(map.first_free_address(1, 3), None);
+        assert_eq!(map.used_on_loop(1), 3);
+
+        map.set_address(2, Some(LoopAddress::new(2, 1)));
+        assert_eq!(map.first_free_address(1, 3), Some(1));
+        assert_eq!(on_loop(&map, 2), vec![2]);
+        map.remove(1);
+        assert_eq!(on_loop(&map, 1), vec![3]);
+        assert_eq!(map.at(LoopAddress::new(1, 2)).count(), 0);
+
+        let json = serde_json::to_value(&map).unwrap();
+        let restored: UnitMap = serde_json::from_value(json).unwrap();
+        assert_eq!(on_loop(&restored, 1), vec![3]);
+        assert_eq!(
+            restored
+                .at(LoopAddress::new(2, 1))
+                .map(|unit| unit.id())
+                .collect::<Vec<_>>(),
+            vec![2]
+        );
+    }
+
+    #[test]
+    fn test_serializes_as_list_and_keeps_duplicates() {
+        let mut map = UnitMap::new();
+        map.insert(Unit::new(0, UnitKind::Root, "Root"));
+        map.insert(Unit::new(1, UnitKind::Detector, "Kitchen"));
+        map.insert(Unit::new(2, UnitKind::Detector, "Hall"));
+        map.remove(1);
+
+        let json = serde_json::to_value(&map).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 2);
+        assert_eq!(serde_json::from_value::<UnitMap>(json).unwrap(), map);
+
+        let duplicated = UnitMap::from(vec![
+            Unit::new(1, UnitKind::Detector, "First"),
+            Unit::new(1, UnitKind::Detector, "Second"),
+        ]);
+        assert_eq!(duplicated.len(), 2);
+        assert_eq!(duplicated.get(1).map(|unit| unit.name()), Some("First"));
+    }
+}
@@ -1,60 +1,853 @@
-use super::Unit;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
 
-#[derive(Debug, Clone)]
+use serde::{Deserialize, Serialize};
+
+use super::{
+    AddressError, CauseEffectError, CauseEffectMatrix, Loop, LoopAddress, LoopUtilisation,
+    Membership, MembershipError, Network, NetworkError, Unit, UnitKind, UnitMap, ZoningThresholds,
+    DEFAULT_LOOP_CAPACITY, MAX_LOOP_ADDRESS,
+};
+
+/// The longest name a unit may have, in characters.
+pub const MAX_NAME_LENGTH: usize = 64;
+
+/// Reasons a unit name is refused.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NameError {
+    Empty,
+    TooLong { max: usize },
+    Duplicate { other: u64 },
+}
+
+impl fmt::Display for NameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameError::Empty => write!(f, "name must not be empty"),
+            NameError::TooLong { max } => write!(f, "name must be at most {} characters", max),
+            NameError::Duplicate { other } => {
+                write!(f, "unit {} of the same kind already has this name", other)
+            }
+        }
+    }
+}
+
+impl std::error::Error for NameError {}
+
+/// Reasons a change to the unit tree can be refused.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HierarchyError {
+    UnitNotFound(u64),
+    ParentNotFound(u64),
+    RootIsFixed,
+    WouldCreateCycle { unit: u64, new_parent: u64 },
+    ZoneAsParent(u64),
+    ZoneBelowUnit(u64),
+}
+
+impl fmt::Display for HierarchyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HierarchyError::UnitNotFound(id) => write!(f, "unit {} does not exist", id),
+            HierarchyError::ParentNotFound(id) => write!(f, "parent unit {} does not exist", id),
+            HierarchyError::RootIsFixed => write!(f, "the Root unit cannot be moved"),
+            HierarchyError::WouldCreateCycle { unit, new_parent } => write!(
+                f,
+                "moving unit {} under unit {} would create a cycle",
+                unit, new_parent
+            ),
+            HierarchyError::ZoneAsParent(zone) => write!(
+                f,
+                "zone {} cannot have units below it; units join zones through zone membership",
+                zone
+            ),
+            HierarchyError::ZoneBelowUnit(zone) => {
+                write!(f, "zone {} must sit directly under the Root unit", zone)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HierarchyError {}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UnitStore {
-    units: Vec<Unit>,
+    units: UnitMap,
     next_id: u64,
+    membership: Membership,
+    cause_effect: CauseEffectMatrix,
+    #[serde(default)]
+    loops: Vec<Loop>,
+    #[serde(default)]
+    thresholds: ZoningThresholds,
+    #[serde(default)]
+    network: Network,
 }
 
 impl UnitStore {
     pub fn new() -> Self {
         let mut store = Self {
-            units: Vec::new(),
+            units: UnitMap::new(),
             next_id: 0,
+            membership: Membership::new(),
+            cause_effect: CauseEffectMatrix::new(),
+            loops: Vec::new(),
+            thresholds: ZoningThresholds::default(),
+            network: Network::new(),
         };
 
         // Always create the Root unit with id = 0
-        store.units.push(Unit::new(0, "Root"));
+        store.units.insert(Unit::new(0, UnitKind::Root, "Root"));
         store.next_id = 1;
 
         store
     }
 
-    pub fn add_unit(&mut self, name: &str) -> u64 {
+    /// Adds a unit directly under the Root unit.
+    pub fn add_unit(&mut self, kind: UnitKind, name: &str) -> u64 {
+        self.push_unit(0, kind, name)
+    }
+
+    /// Adds a unit under `parent_id` on the parent's panel, or returns `None` if the parent does not exist or is a zone,
+    /// or if a zone would be added below anything but the Root.
+    pub fn add_child(&mut self, parent_id: u64, kind: UnitKind, name: &str) -> Option<u64> {
+        let parent_kind = self.get_unit(parent_id)?.kind();
+        if parent_kind.is_zone() || (kind.is_zone() && parent_id != 0) {
+            return None;
+        }
+        let id = self.push_unit(parent_id, kind, name);
+        self.follow_parent_panel(id);
+        Some(id)
+    }
+
+    /// Takes back unit `id` if it is the most recently added unit and nothing was added below it, so the next unit
+    /// added gets its id again. Returns false otherwise.
+    pub fn remove_last_added(&mut self, id: u64) -> bool {
+        if id + 1 != self.next_id || self.children_of(id).next().is_some() || !self.remove_unit(id)
+        {
+            return false;
+        }
+        self.next_id = id;
+        true
+    }
+
+    fn push_unit(&mut self, parent_id: u64, kind: UnitKind, name: &str) -> u64 {
         let id = self.next_id;
-        self.units.push(Unit::new(id, name));
+        let mut unit = Unit::new(id, kind, name);
+        unit.set_parent(Some(parent_id));
+        self.units.insert(unit);
         self.next_id += 1;
         id
     }
 
     pub fn get_unit(&self, id: u64) -> Option<&Unit> {
-        self.units.iter().find(|unit| unit.id() == id)
+        self.units.get(id)
     }
 
     pub fn get_unit_mut(&mut self, id: u64) -> Option<&mut Unit> {
-        self.units.iter_mut().find(|unit| unit.id() == id)
+        self.units.get_mut(id)
     }
 
-    pub fn get_all_units(&self) -> &Vec<Unit> {
+    /// Every unit, Root first, in the order they were added.
+    pub fn get_all_units(&self) -> &UnitMap {
         &self.units
     }
 
+    pub fn units_of_kind(&self, kind: UnitKind) -> impl Iterator<Item = &Unit> {
+        self.units.iter().filter(move |unit| unit.kind() == kind)
+    }
+
+    pub fn count_of_kind(&self, kind: UnitKind) -> usize {
+        self.units_of_kind(kind).count()
+    }
+
+    pub fn counts_by_kind(&self) -> BTreeMap<UnitKind, usize> {
+        // Every configurable kind is present, even with a count of zero
+        let mut counts: BTreeMap<UnitKind, usize> = UnitKind::CONFIGURABLE
+            .iter()
+            .map(|kind| (*kind, 0))
+            .collect();
+        for unit in self.units.iter().skip(1) {
+            *counts.entry(unit.kind()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Checks whether unit `id` may be called `name`: it must not be blank, must fit in [`MAX_NAME_LENGTH`] and must not
+    /// be used by another unit of the same kind.
+    pub fn check_name(&self, id: u64, name: &str) -> Result<(), NameError> {
+        let kind = self.get_unit(id).map(|unit| unit.kind());
+        self.check_name_for(Some(id), kind, name)
+    }
+
+    /// Like [`UnitStore::check_name`], for a unit of `kind` that is yet to be added.
+    pub fn check_new_name(&self, kind: UnitKind, name: &str) -> Result<(), NameError> {
+        self.check_name_for(None, Some(kind), name)
+    }
+
+    /// The first "<Kind> <n>" name no unit of `kind` uses, counting on from the number of units of that kind.
+    pub fn free_name(&self, kind: UnitKind) -> String {
+        let mut number = self.count_of_kind(kind);
+        loop {
+            number += 1;
+            let name = format!("{} {}", kind, number);
+            if self.check_new_name(kind, &name).is_ok() {
+                return name;
+            }
+        }
+    }
+
+    fn check_name_for(
+        &self,
+        id: Option<u64>,
+        kind: Option<UnitKind>,
+        name: &str,
+    ) -> Result<(), NameError> {
+        if name.trim().is_empty() {
+            return Err(NameError::Empty);
+        }
+        if name.chars().count() > MAX_NAME_LENGTH {
+            return Err(NameError::TooLong {
+                max: MAX_NAME_LENGTH,
+            });
+        }
+        if let Some(other) = self
+            .units
+            .iter()
+            .find(|unit| Some(unit.id()) != id && Some(unit.kind()) == kind && unit.name() == name)
+        {
+            return Err(NameError::Duplicate { other: other.id() });
+        }
+        Ok(())
+    }
+
+    pub fn children_of(&self, id: u64) -> impl Iterator<Item = &Unit> {
+        self.units.children(id)
+    }
+
+    /// Returns the ids of every unit below `id`, not including `id` itself.
+    pub fn descendants(&self, id: u64) -> Vec<u64> {
+        let mut descendants = Vec::new();
+        let mut pending = vec![id];
+        while let Some(current) = pending.pop() {
+            for child in self.children_of(current) {
+                descendants.push(child.id());
+                pending.push(child.id());
+            }
+        }
+        descendants
+    }
+
+    /// Returns the units below any of `ids` that are not in `ids` themselves, each once.
+    pub fn units_below(&self, ids: &[u64]) -> Vec<u64> {
+        let mut seen: HashSet<u64> = ids.iter().copied().collect();
+        ids.iter()
+            .flat_map(|id| self.descendants(*id))
+            .filter(|id| seen.insert(*id))
+            .collect()
+    }
+
+    /// Returns the ids from the parent of `id` up to and including the Root unit.
+    pub fn ancestors(&self, id: u64) -> Vec<u64> {
+        let mut ancestors = Vec::new();
+        let mut current = self.get_unit(id).and_then(|unit| unit.parent());
+        while let Some(parent) = current {
+            ancestors.push(parent);
+            current = self.get_unit(parent).and_then(|unit| unit.parent());
+        }
+        ancestors
+    }
+
+    /// Re-parents `id` (and its subtree) under `new_parent`. Zones cannot be moved below other units, and nothing can be
+    /// moved below a zone. Below a unit other than the Root, the subtree moves to the panel of its new parent.
+    pub fn move_unit(&mut self, id: u64, new_parent: u64) -> Result<(), HierarchyError> {
+        if id == 0 {
+            return Err(HierarchyError::RootIsFixed);
+        }
+        let kind = self
+            .get_unit(id)
+            .ok_or(HierarchyError::UnitNotFound(id))?
+            .kind();
+        let parent_kind = self
+            .get_unit(new_parent)
+            .ok_or(HierarchyError::ParentNotFound(new_parent))?
+            .kind();
+        if parent_kind.is_zone() {
+            return Err(HierarchyError::ZoneAsParent(new_parent));
+        }
+        if kind.is_zone() && new_parent != 0 {
+            return Err(HierarchyError::ZoneBelowUnit(id));
+        }
+        if new_parent == id || self.ancestors(new_parent).contains(&id) {
+            return Err(HierarchyError::WouldCreateCycle {
+                unit: id,
+                new_parent,
+            });
+        }
+
+        self.units.set_parent(id, Some(new_parent));
+        self.follow_parent_panel(id);
+        Ok(())
+    }
+
+    // Units mounted below another unit belong to its panel, whole subtree included
+    fn follow_parent_panel(&mut self, id: u64) {
+        let Some(parent) = self.get_unit(id).and_then(|unit| unit.parent()) else {
+            return;
+        };
+        let panel = self.network.panel_of(parent);
+        if parent == 0 || self.network.panel_of(id) == panel {
+            return;
+        }
+        for unit in std::iter::once(id).chain(self.descendants(id)) {
+            // The parent's panel exists, so assigning to it cannot fail
+            let _ = self.network.assign(unit, panel);
+        }
+    }
+
+    /// Turns unit `id` into a unit of `kind`.
+    ///
+    /// Its settings go back to the defaults for the new kind, and any address, zone, zone members and rule references
+    /// the new kind cannot have are dropped. A unit that becomes a zone moves directly under the Root, and the units that
+    /// were below it move up to its old parent. Returns false for the Root unit, missing units and kinds that cannot be
+    /// configured. The name is not checked against units of the new kind; see [`UnitStore::check_name`].
+    pub fn set_kind(&mut self, id: u64, kind: UnitKind) -> bool {
+        if id == 0 || !UnitKind::CONFIGURABLE.contains(&kind) {
+            return false;
+        }
+        let Some((old_kind, parent)) = self.get_unit(id).map(|unit| (unit.kind(), unit.parent()))
+        else {
+            return false;
+        };
+        if old_kind == kind {
+            return true;
+        }
+        if kind.is_zone() {
+            let children: Vec<u64> = self.children_of(id).map(|child| child.id()).collect();
+            for child in children {
+                self.units.set_parent(child, parent);
+            }
+            self.units.set_parent(id, Some(0));
+        }
+        if !kind.is_addressable() {
+            self.units.set_address(id, None);
+        }
+        if let Some(unit) = self.units.get_mut(id) {
+            unit.set_kind(kind);
+        }
+
+        if old_kind.zone_kind() != kind.zone_kind() {
+            self.membership.unassign(id);
+        }
+        let members: Vec<u64> = self.membership.members_of(id).collect();
+        for member in members {
+            self.membership.unassign(member);
+        }
+        let rules: Vec<u64> = self
+            .cause_effect
+            .rules()
+            .iter()
+            .map(|rule| rule.id())
+            .collect();
+        for rule in rules {
+            if !kind.is_cause() {
+                let _ = self.cause_effect.set_input(rule, id, false);
+            }
+            if !kind.is_effect() {
+                let _ = self.cause_effect.set_output(rule, id, false);
+            }
+        }
+        true
+    }
+
+    /// Puts `member` in `zone`, replacing any zone it was in before.
+    ///
+    /// Only detectors and manual call points can join a detection zone, and only detection zones and
+    /// fire alarm devices can join an alarm zone.
+    pub fn assign_to_zone(&mut self, member: u64, zone: u64) -> Result<(), MembershipError> {
+        let member_kind = self
+            .get_unit(member)
+            .ok_or(MembershipError::UnitNotFound(member))?
+            .kind();
+        let zone_kind = self
+            .get_unit(zone)
+            .ok_or(MembershipError::ZoneNotFound(zone))?
+            .kind();
+        if member_kind.zone_kind() != Some(zone_kind) {
+            return Err(MembershipError::NotAssignable {
+                member: member_kind,
+                zone: zone_kind,
+            });
+        }
+
+        self.membership.assign(member, zone);
+        Ok(())
+    }
+
+    /// Takes `member` out of its zone. Returns false if it was not in one.
+    pub fn unassign_from_zone(&mut self, member: u64) -> bool {
+        self.membership.unassign(member).is_some()
+    }
+
+    pub fn zone_of(&self, member: u64) -> Option<u64> {
+        self.membership.zone_of(member)
+    }
+
+    /// Returns the members of `zone` in store order.
+    pub fn members_of(&self, zone: u64) -> impl Iterator<Item = &Unit> {
+        self.units
+            .iter()
+            .filter(move |unit| self.membership.zone_of(unit.id()) == Some(zone))
+    }
+
+    /// The members of every zone that has any, each in store order. Built in one pass over the units, for checks that
+    /// look at every zone.
+    pub fn members_by_zone(&self) -> BTreeMap<u64, Vec<&Unit>> {
+        let mut members: BTreeMap<u64, Vec<&Unit>> = BTreeMap::new();
+        for unit in self.units.iter() {
+            if let Some(zone) = self.membership.zone_of(unit.id()) {
+                members.entry(zone).or_default().push(unit);
+            }
+        }
+        members
+    }
+
+    pub fn cause_effect(&self) -> &CauseEffectMatrix {
+        &self.cause_effect
+    }
+
+    pub fn cause_effect_mut(&mut self) -> &mut CauseEffectMatrix {
+        &mut self.cause_effect
+    }
+
+    /// Adds or removes `unit` as an input of `rule`. Only detectors, manual call points and detection
+    /// zones can be inputs.
+    pub fn set_rule_input(
+        &mut self,
+        rule: u64,
+        unit: u64,
+        enabled: bool,
+    ) -> Result<(), CauseEffectError> {
+        let kind = self
+            .get_unit(unit)
+            .ok_or(CauseEffectError::UnitNotFound(unit))?
+            .kind();
+        if !kind.is_cause() {
+            return Err(CauseEffectError::NotAnInput(kind));
+        }
+        self.cause_effect.set_input(rule, unit, enabled)
+    }
+
+    /// Adds or removes `unit` as an output of `rule`. Only alarm zones, fire alarm devices and
+    /// outputs can be activated.
+    pub fn set_rule_output(
+        &mut self,
+        rule: u64,
+        unit: u64,
+        enabled: bool,
+    ) -> Result<(), CauseEffectError> {
+        let kind = self
+            .get_unit(unit)
+            .ok_or(CauseEffectError::UnitNotFound(unit))?
+            .kind();
+        if !kind.is_effect() {
+            return Err(CauseEffectError::NotAnOutput(kind));
+        }
+        self.cause_effect.set_output(rule, unit, enabled)
+    }
+
+    /// Returns the outputs to activate for the given set of active inputs.
+    ///
+    /// A rule fires when its active inputs satisfy its logic, but only reaches outputs on panels the network connects
+    /// to the panel of one of its active inputs.
+    pub fn evaluate_rules(&self, active_inputs: &BTreeSet<u64>) -> BTreeSet<u64> {
+        self.cause_effect.evaluate(active_inputs, |input, output| {
+            self.network
+                .is_connected(self.network.panel_of(input), self.network.panel_of(output))
+        })
+    }
+
+    /// The panels the inputs and outputs of `rule` belong to. More than one means the rule crosses the network.
+    pub fn rule_panels(&self, rule: u64) -> BTreeSet<u16> {
+        self.cause_effect
+            .get_rule(rule)
+            .map(|rule| {
+                rule.inputs()
+                    .iter()
+                    .chain(rule.outputs())
+                    .map(|unit| self.network.panel_of(*unit))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The outputs of `rule` on panels the network does not connect to the panel of any of its inputs. A rule without
+    /// inputs has none.
+    pub fn unreachable_outputs(&self, rule: u64) -> Vec<u64> {
+        let Some(rule) = self.cause_effect.get_rule(rule) else {
+            return Vec::new();
+        };
+        let reachable: BTreeSet<u16> = rule
+            .inputs()
+            .iter()
+            .flat_map(|input| self.network.reachable_from(self.network.panel_of(*input)))
+            .collect();
+        if reachable.is_empty() {
+            return Vec::new();
+        }
+        rule.outputs()
+            .iter()
+            .copied()
+            .filter(|output| !reachable.contains(&self.network.panel_of(*output)))
+            .collect()
+    }
+
+    /// The limits the zoning rules are checked against. They are project settings, so [`UnitStore::clear`] keeps them.
+    pub fn thresholds(&self) -> &ZoningThresholds {
+        &self.thresholds
+    }
+
+    pub fn set_thresholds(&mut self, thresholds: ZoningThresholds) {
+        self.thresholds = thresholds;
+    }
+
+    pub fn loops(&self) -> &[Loop] {
+        &self.loops
+    }
+
+    pub fn get_loop(&self, number: u16) -> Option<&Loop> {
+        self.loops.iter().find(|l| l.number() == number)
+    }
+
+    /// Adds a loop numbered one above the highest existing loop. Returns its number.
+    pub fn add_loop(&mut self, capacity: u16) -> Result<u16, AddressError> {
+        if capacity == 0 || capacity > MAX_LOOP_ADDRESS {
+            return Err(AddressError::InvalidCapacity(capacity));
+        }
+        let number = self.loops.iter().map(|l| l.number()).max().unwrap_or(0) + 1;
+        self.loops.push(Loop::new(number, capacity));
+        Ok(number)
+    }
+
+    /// Removes a loop. Devices on it keep existing but lose their address.
+    pub fn remove_loop(&mut self, number: u16) -> bool {
+        let before = self.loops.len();
+        self.loops.retain(|l| l.number() != number);
+        if self.loops.len() == before {
+            return false;
+        }
+        let devices: Vec<u64> = self.units.on_loop(number).map(|unit| unit.id()).collect();
+        for id in devices {
+            self.units.set_address(id, None);
+        }
+        true
+    }
+
+    /// Checks whether loop `number` could be given `capacity` without changing anything.
+    pub fn check_loop_capacity(&self, number: u16, capacity: u16) -> Result<(), AddressError> {
+        if self.get_loop(number).is_none() {
+            return Err(AddressError::LoopNotFound(number));
+        }
+        if capacity == 0 || capacity > MAX_LOOP_ADDRESS {
+            return Err(AddressError::InvalidCapacity(capacity));
+        }
+        let highest = self
+            .devices_on_loop(number)
+            .filter_map(|unit| unit.address())
+            .map(|a| a.address)
+            .max()
+            .unwrap_or(0);
+        if highest > capacity {
+            return Err(AddressError::CapacityBelowUsage { capacity, highest });
+        }
+        Ok(())
+    }
+
+    /// Changes how many addresses a loop has. Refused if a device sits above the new capacity.
+    pub fn set_loop_capacity(&mut self, number: u16, capacity: u16) -> Result<(), AddressError> {
+        self.check_loop_capacity(number, capacity)?;
+        if let Some(l) = self.loops.iter_mut().find(|l| l.number() == number) {
+            l.set_capacity(capacity);
+        }
+        Ok(())
+    }
+
+    /// Returns the devices on a loop in address order.
+    pub fn devices_on_loop(&self, number: u16) -> impl Iterator<Item = &Unit> {
+        self.units.on_loop(number)
+    }
+
+    /// Returns the unit at `address`, if any.
+    pub fn unit_at(&self, address: LoopAddress) -> Option<&Unit> {
+        self.units.at(address).next()
+    }
+
+    /// Checks whether the device `id` could be put at `address` without changing anything.
+    pub fn check_address(&self, id: u64, address: Option<LoopAddress>) -> Result<(), AddressError> {
+        let kind = self
+            .get_unit(id)
+            .ok_or(AddressError::UnitNotFound(id))?
+            .kind();
+        if !kind.is_addressable() {
+            return Err(AddressError::NotAddressable(kind));
+        }
+        if let Some(address) = address {
+            let capacity = self
+                .get_loop(address.loop_number)
+                .ok_or(AddressError::LoopNotFound(address.loop_number))?
+                .capacity();
+            if address.address == 0 || address.address > capacity {
+                return Err(AddressError::AddressOutOfRange {
+                    address: address.address,
+                    capacity,
+                });
+            }
+            if let Some(other) = self.unit_at(address).filter(|unit| unit.id() != id) {
+                return Err(AddressError::AddressInUse {
+                    address,
+                    other: other.id(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Puts an addressable device at `address`, or takes it off its loop when `address` is `None`.
+    pub fn set_address(
+        &mut self,
+        id: u64,
+        address: Option<LoopAddress>,
+    ) -> Result<(), AddressError> {
+        self.check_address(id, address)?;
+        self.units.set_address(id, address);
+        Ok(())
+    }
+
+    /// Returns the lowest free address on a loop.
+    pub fn first_free_address(&self, number: u16) -> Result<LoopAddress, AddressError> {
+        let capacity = self
+            .get_loop(number)
+            .ok_or(AddressError::LoopNotFound(number))?
+            .capacity();
+        self.units
+            .first_free_address(number, capacity)
+            .map(|address| LoopAddress::new(number, address))
+            .ok_or(AddressError::LoopFull(number))
+    }
+
+    /// Gives an addressable device the first free address on the first loop with room, adding a loop of
+    /// [`DEFAULT_LOOP_CAPACITY`] when every loop is full. Devices that already have an address keep it.
+    pub fn auto_address(&mut self, id: u64) -> Result<LoopAddress, AddressError> {
+        let unit = self.get_unit(id).ok_or(AddressError::UnitNotFound(id))?;
+        if !unit.kind().is_addressable() {
+            return Err(AddressError::NotAddressable(unit.kind()));
+        }
+        if let Some(address) = unit.address() {
+            return Ok(address);
+        }
+
+        let free = self.loops.iter().find_map(|l| {
+            self.units
+                .first_free_address(l.number(), l.capacity())
+                .map(|address| LoopAddress::new(l.number(), address))
+        });
+        let address = match free {
+            Some(address) => address,
+            None => {
+                let number = self.add_loop(DEFAULT_LOOP_CAPACITY)?;
+                LoopAddress::new(number, 1)
+            }
+        };
+        self.set_address(id, Some(address))?;
+        Ok(address)
+    }
+
+    /// Renumbers the devices on a loop to 1, 2, 3, ... keeping their order, closing any gaps.
+    ///
+    /// Returns the devices that moved with their old and new address.
+    pub fn readdress_loop(
+        &mut self,
+        number: u16,
+    ) -> Result<Vec<(u64, LoopAddress, LoopAddress)>, AddressError> {
+        if self.get_loop(number).is_none() {
+            return Err(AddressError::LoopNotFound(number));
+        }
+        let devices: Vec<(u64, LoopAddress)> = self
+            .devices_on_loop(number)
+            .filter_map(|unit| unit.address().map(|address| (unit.id(), address)))
+            .collect();
+
+        let mut moved = Vec::new();
+        for (index, (id, old)) in devices.into_iter().enumerate() {
+            let new = LoopAddress::new(number, index as u16 + 1);
+            if new != old {
+                self.units.set_address(id, Some(new));
+                moved.push((id, old, new));
+            }
+        }
+        Ok(moved)
+    }
+
+    /// How many addresses each loop uses, in loop order.
+    pub fn loop_utilisation(&self) -> Vec<LoopUtilisation> {
+        self.loops
+            .iter()
+            .map(|l| LoopUtilisation {
+                loop_number: l.number(),
+                used: self.units.used_on_loop(l.number()),
+                capacity: l.capacity(),
+            })
+            .collect()
+    }
+
+    /// Addresses used by more than one device, with the devices that share them.
+    ///
+    /// The store refuses colliding addresses, so collisions only show up in data from elsewhere, such as an edited file.
+    pub fn address_collisions(&self) -> Vec<(LoopAddress, Vec<u64>)> {
+        let mut by_address: BTreeMap<LoopAddress, Vec<u64>> = BTreeMap::new();
+        for unit in &self.units {
+            if let Some(address) = unit.address() {
+                by_address.entry(address).or_default().push(unit.id());
+            }
+        }
+        by_address
+            .into_iter()
+            .filter(|(_, ids)| ids.len() > 1)
+            .collect()
+    }
+
+    pub fn network(&self) -> &Network {
+        &self.network
+    }
+
+    /// Adds a panel without units. Returns its number.
+    pub fn add_panel(&mut self, name: &str) -> Result<u16, NetworkError> {
+        self.network.add_panel(name)
+    }
+
+    pub fn rename_panel(&mut self, number: u16, name: &str) -> Result<(), NetworkError> {
+        self.network.rename_panel(number, name)
+    }
+
+    /// Checks whether panel `number` could be removed: it must exist, must not be the last panel and must own no units.
+    pub fn check_remove_panel(&self, number: u16) -> Result<(), NetworkError> {
+        self.network
+            .get_panel(number)
+            .ok_or(NetworkError::PanelNotFound(number))?;
+        if self.network.panels().len() == 1 {
+            return Err(NetworkError::LastPanel);
+        }
+        let units = self.units_on_panel(number).count();
+        if units > 0 {
+            return Err(NetworkError::PanelNotEmpty {
+                panel: number,
+                units,
+            });
+        }
+        Ok(())
+    }
+
+    /// Removes a panel that owns no units, with its network links.
+    pub fn remove_panel(&mut self, number: u16) -> Result<(), NetworkError> {
+        self.check_remove_panel(number)?;
+        self.network.remove_panel(number)
+    }
+
+    /// The panel that owns unit `id`, or `None` for the Root unit and missing units.
+    pub fn panel_of(&self, id: u64) -> Option<u16> {
+        if id == 0 || self.get_unit(id).is_none() {
+            return None;
+        }
+        Some(self.network.panel_of(id))
+    }
+
+    /// Gives unit `id` and its whole subtree to `panel`. Units mounted below a unit other than the Root stay with the
+    /// panel of that unit.
+    pub fn set_panel(&mut self, id: u64, panel: u16) -> Result<(), NetworkError> {
+        if id == 0 {
+            return Err(NetworkError::RootHasNoPanel);
+        }
+        let parent = self
+            .get_unit(id)
+            .ok_or(NetworkError::UnitNotFound(id))?
+            .parent()
+            .unwrap_or(0);
+        if parent != 0 && self.network.panel_of(parent) != panel {
+            return Err(NetworkError::Mounted { unit: id, parent });
+        }
+        self.network
+            .get_panel(panel)
+            .ok_or(NetworkError::PanelNotFound(panel))?;
+        for unit in std::iter::once(id).chain(self.descendants(id)) {
+            self.network.assign(unit, panel)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the units owned by `panel` in store order.
+    pub fn units_on_panel(&self, panel: u16) -> impl Iterator<Item = &Unit> {
+        self.units
+            .iter()
+            .skip(1)
+            .filter(move |unit| self.network.panel_of(unit.id()) == panel)
+    }
+
+    /// Like [`UnitStore::counts_by_kind`], for the units of one panel.
+    pub fn counts_on_panel(&self, panel: u16) -> BTreeMap<UnitKind, usize> {
+        let mut counts: BTreeMap<UnitKind, usize> = UnitKind::CONFIGURABLE
+            .iter()
+            .map(|kind| (*kind, 0))
+            .collect();
+        for unit in self.units_on_panel(panel) {
+            *counts.entry(unit.kind()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    pub fn add_network_link(&mut self, a: u16, b: u16) -> Result<(), NetworkError> {
+        self.network.add_link(a, b)
+    }
+
+    pub fn remove_network_link(&mut self, a: u16, b: u16) -> Result<(), NetworkError> {
+        self.network.remove_link(a, b)
+    }
+
+    /// Removes the unit and its whole subtree.
+    ///
+    /// Zone memberships and rule references of removed units are dropped, and members of a removed
+    /// zone are left unassigned rather than removed.
     pub fn remove_unit(&mut self, id: u64) -> bool {
         if id == 0 {
             // Don't allow removing the Root unit
             return false;
         }
 
-        if let Some(pos) = self.units.iter().position(|unit| unit.id() == id) {
-            self.units.remove(pos);
-            true
-        } else {
-            false
+        if self.get_unit(id).is_none() {
+            return false;
+        }
+
+        let mut removed: HashSet<u64> = self.descendants(id).into_iter().collect();
+        removed.insert(id);
+        for removed_id in removed {
+            self.units.remove(removed_id);
+            self.membership.forget(removed_id);
+            self.cause_effect.forget_unit(removed_id);
+            self.network.forget(removed_id);
         }
+        true
+    }
+
+    /// The id the next added unit will get. Ids are never reused, even after removals.
+    pub fn next_id(&self) -> u64 {
+        self.next_id
+    }
+
+    pub fn membership(&self) -> &Membership {
+        &self.membership
     }
 
     pub fn root_unit(&self) -> &Unit {
-        &self.units[0] // Root is always at index 0
+        self.units.get(0).expect("the Root unit is never removed")
     }
 
     pub fn count(&self) -> usize {
@@ -62,14 +855,14 @@ impl UnitStore {
         self.units.len() - 1
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.units.is_empty()
-    }
-
     pub fn clear(&mut self) {
         self.units.clear();
-        self.units.push(Unit::new(0, "Root"));
+        self.units.insert(Unit::new(0, UnitKind::Root, "Root"));
         self.next_id = 1;
+        self.membership.clear();
+        self.cause_effect.clear();
+        self.loops.clear();
+        self.network = Network::new();
     }
 }
 
@@ -82,6 +875,7 @@ impl Default for UnitStore {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::InputLogic;
 
     #[test]
     fn test_new_store_has_root_unit() {
@@ -109,8 +903,8 @@ mod tests {
     fn test_add_unit_increments_id() {
         let mut store = UnitStore::new();
 
-        let id1 = store.add_unit("First Unit");
-        let id2 = store.add_unit("Second Unit");
+        let id1 = store.add_unit(UnitKind::Detector, "First Unit");
+        let id2 = store.add_unit(UnitKind::Detector, "Second Unit");
 
         assert_eq!(id1, 1);
         assert_eq!(id2, 2);
@@ -121,7 +915,7 @@ mod tests {
     #[test]
     fn test_get_unit_returns_correct_unit() {
         let mut store = UnitStore::new();
-        store.add_unit("Test Unit");
+        store.add_unit(UnitKind::Detector, "Test Unit");
 
         let unit = store.get_unit(1);
         assert!(unit.is_some());
@@ -134,7 +928,7 @@ mod tests {
     #[test]
     fn test_get_unit_mut_allows_modification() {
         let mut store = UnitStore::new();
-        store.add_unit("Original Name");
+        store.add_unit(UnitKind::Detector, "Original Name");
 
         {
             let unit = store.get_unit_mut(1);
@@ -149,11 +943,11 @@ mod tests {
     #[test]
     fn test_get_all_units_returns_all() {
         let mut store = UnitStore::new();
-        store.add_unit("Unit 1");
-        store.add_unit("Unit 2");
+        store.add_unit(UnitKind::Detector, "Unit 1");
+        store.add_unit(UnitKind::Detector, "Unit 2");
 
-        let units = store.get_all_units();
-        assert_eq!(units.len(), 3); // Root + 2 added
+        assert_eq!(store.get_all_units().len(), 3); // Root + 2 added
+        let units: Vec<&Unit> = store.get_all_units().iter().collect();
         assert_eq!(units[0].name(), "Root");
         assert_eq!(units[1].name(), "Unit 1");
         assert_eq!(units[2].name(), "Unit 2");
@@ -162,7 +956,7 @@ mod tests {
     #[test]
     fn test_remove_unit_success() {
         let mut store = UnitStore::new();
-        let id = store.add_unit("To Remove");
+        let id = store.add_unit(UnitKind::Detector, "To Remove");
 
         assert_eq!(store.units.len(), 2);
 
@@ -194,14 +988,14 @@ mod tests {
     #[test]
     fn test_root_unit_always_accessible() {
         let mut store = UnitStore::new();
-        store.add_unit("Some Unit");
+        store.add_unit(UnitKind::Detector, "Some Unit");
 
         let root = store.root_unit();
         assert_eq!(root.id(), 0);
         assert_eq!(root.name(), "Root");
 
         // Root should still be accessible after adding more units
-        store.add_unit("Another Unit");
+        store.add_unit(UnitKind::Detector, "Another Unit");
         let root_again = store.root_unit();
         assert_eq!(root_again.id(), 0);
     }
@@ -210,15 +1004,15 @@ mod tests {
     fn test_id_sequence_after_removals() {
         let mut store = UnitStore::new();
 
-        let id1 = store.add_unit("Unit 1");
-        let id2 = store.add_unit("Unit 2");
-        let id3 = store.add_unit("Unit 3");
+        let id1 = store.add_unit(UnitKind::Detector, "Unit 1");
+        let id2 = store.add_unit(UnitKind::Detector, "Unit 2");
+        let id3 = store.add_unit(UnitKind::Detector, "Unit 3");
 
         // Remove middle unit
         store.remove_unit(id2);
 
         // Next ID should still increment from last used
-        let id4 = store.add_unit("Unit 4");
+        let id4 = store.add_unit(UnitKind::Detector, "Unit 4");
         assert_eq!(id4, 4); // Should be 4, not 2
 
         // Verify we have Root, Unit 1, Unit 3, Unit 4
@@ -228,4 +1022,780 @@ mod tests {
         assert!(store.get_unit(id3).is_some());
         assert!(store.get_unit(id4).is_some());
     }
+
+    #[test]
+    fn test_root_unit_has_root_kind() {
+        let store = UnitStore::new();
+
+        assert_eq!(store.root_unit().kind(), UnitKind::Root);
+    }
+
+    #[test]
+    fn test_units_of_kind_filters_by_kind() {
+        let mut store = UnitStore::new();
+        store.add_unit(UnitKind::Detector, "Detector 1");
+        store.add_unit(UnitKind::ManualCallPoint, "Manual Call Point 1");
+        store.add_unit(UnitKind::Detector, "Detector 2");
+
+        let names: Vec<&str> = store
+            .units_of_kind(UnitKind::Detector)
+            .map(|unit| unit.name())
+            .collect();
+        assert_eq!(names, vec!["Detector 1", "Detector 2"]);
+        assert_eq!(store.count_of_kind(UnitKind::ManualCallPoint), 1);
+        assert_eq!(store.count_of_kind(UnitKind::Output), 0);
+    }
+
+    #[test]
+    fn test_rename_does_not_change_kind() {
+        let mut store = UnitStore::new();
+        let id = store.add_unit(UnitKind::Detector, "Detector 1");
+
+        store.get_unit_mut(id).unwrap().set_name("Output 1");
+
+        assert_eq!(store.get_unit(id).unwrap().kind(), UnitKind::Detector);
+        assert_eq!(store.count_of_kind(UnitKind::Output), 0);
+    }
+
+    #[test]
+    fn test_counts_by_kind_excludes_root() {
+        let mut store = UnitStore::new();
+        store.add_unit(UnitKind::AlarmZone, "Alarm Zone 1");
+        store.add_unit(UnitKind::AlarmZone, "Alarm Zone 2");
+        store.add_unit(UnitKind::Output, "Output 1");
+
+        let counts = store.counts_by_kind();
+        assert_eq!(counts.get(&UnitKind::AlarmZone), Some(&2));
+        assert_eq!(counts.get(&UnitKind::Output), Some(&1));
+        assert_eq!(counts.get(&UnitKind::Detector), Some(&0));
+        assert_eq!(counts.get(&UnitKind::Root), None);
+        assert_eq!(counts.values().sum::<usize>(), store.count());
+    }
+
+    #[test]
+    fn test_add_unit_places_unit_under_root() {
+        let mut store = UnitStore::new();
+        let id = store.add_unit(UnitKind::Detector, "Detector 1");
+
+        assert_eq!(store.get_unit(id).unwrap().parent(), Some(0));
+        assert_eq!(store.root_unit().parent(), None);
+    }
+
+    #[test]
+    fn test_add_child_and_children_of() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let sounder = store
+            .add_child(det, UnitKind::Output, "Base Sounder")
+            .unwrap();
+        let beacon = store
+            .add_child(det, UnitKind::FireAlarmDevice, "Base Beacon")
+            .unwrap();
+
+        let children: Vec<u64> = store.children_of(det).map(|unit| unit.id()).collect();
+        assert_eq!(children, vec![sounder, beacon]);
+        assert_eq!(store.children_of(0).count(), 1);
+        assert_eq!(store.units_below(&[det, sounder]), vec![beacon]);
+    }
+
+    #[test]
+    fn test_zones_sit_under_the_root() {
+        let mut store = UnitStore::new();
+        let zone = store.add_unit(UnitKind::DetectionZone, "Detection Zone 1");
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let out = store.add_child(det, UnitKind::Output, "Output 1").unwrap();
+
+        assert_eq!(
+            store.add_child(zone, UnitKind::Detector, "Detector 2"),
+            None
+        );
+        assert_eq!(
+            store.add_child(det, UnitKind::AlarmZone, "Alarm Zone 1"),
+            None
+        );
+        assert_eq!(
+            store.move_unit(out, zone),
+            Err(HierarchyError::ZoneAsParent(zone))
+        );
+        assert_eq!(
+            store.move_unit(zone, det),
+            Err(HierarchyError::ZoneBelowUnit(zone))
+        );
+
+        assert!(store.set_kind(out, UnitKind::Detector));
+        assert!(store.set_kind(det, UnitKind::AlarmZone));
+        assert_eq!(store.get_unit(out).unwrap().parent(), Some(0));
+        assert_eq!(store.get_unit(det).unwrap().parent(), Some(0));
+        assert_eq!(store.children_of(det).count(), 0);
+    }
+
+    #[test]
+    fn test_free_names_and_taking_back_the_last_unit() {
+        let mut store = UnitStore::new();
+        store.add_unit(UnitKind::Detector, "Detector 2");
+        assert_eq!(store.free_name(UnitKind::Detector), "Detector 3");
+        assert_eq!(store.free_name(UnitKind::Output), "Output 1");
+
+        let det = store.add_unit(UnitKind::Detector, "Detector 3");
+        let child = store.add_child(det, UnitKind::Output, "Output 1").unwrap();
+        assert!(!store.remove_last_added(det));
+        assert!(store.remove_last_added(child));
+        assert_eq!(store.next_id(), child);
+        assert!(store.remove_last_added(det));
+        assert_eq!(store.free_name(UnitKind::Detector), "Detector 3");
+    }
+
+    #[test]
+    fn test_add_child_to_missing_parent() {
+        let mut store = UnitStore::new();
+
+        assert_eq!(store.add_child(42, UnitKind::Detector, "Orphan"), None);
+        assert_eq!(store.count(), 0);
+        assert_eq!(store.next_id, 1);
+    }
+
+    #[test]
+    fn test_ancestors_walks_up_to_root() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let base = store
+            .add_child(det, UnitKind::Output, "Base Sounder")
+            .unwrap();
+
+        assert_eq!(store.ancestors(base), vec![det, 0]);
+        assert!(store.ancestors(0).is_empty());
+        assert!(store.ancestors(999).is_empty());
+    }
+
+    #[test]
+    fn test_move_unit_changes_parent() {
+        let mut store = UnitStore::new();
+        let det1 = store.add_unit(UnitKind::Detector, "Detector 1");
+        let det2 = store.add_unit(UnitKind::Detector, "Detector 2");
+        let base = store
+            .add_child(det1, UnitKind::Output, "Base Sounder")
+            .unwrap();
+
+        assert_eq!(store.move_unit(base, det2), Ok(()));
+        assert_eq!(store.get_unit(base).unwrap().parent(), Some(det2));
+        assert_eq!(store.children_of(det1).count(), 0);
+    }
+
+    #[test]
+    fn test_move_unit_rejects_cycles() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let base = store
+            .add_child(det, UnitKind::Output, "Base Sounder")
+            .unwrap();
+
+        assert_eq!(
+            store.move_unit(det, base),
+            Err(HierarchyError::WouldCreateCycle {
+                unit: det,
+                new_parent: base
+            })
+        );
+        assert_eq!(
+            store.move_unit(det, det),
+            Err(HierarchyError::WouldCreateCycle {
+                unit: det,
+                new_parent: det
+            })
+        );
+        assert_eq!(store.get_unit(det).unwrap().parent(), Some(0));
+    }
+
+    #[test]
+    fn test_move_unit_rejects_root_and_missing_units() {
+        let mut store = UnitStore::new();
+        let zone = store.add_unit(UnitKind::DetectionZone, "Detection Zone 1");
+
+        assert_eq!(store.move_unit(0, zone), Err(HierarchyError::RootIsFixed));
+        assert_eq!(
+            store.move_unit(999, zone),
+            Err(HierarchyError::UnitNotFound(999))
+        );
+        assert_eq!(
+            store.move_unit(zone, 999),
+            Err(HierarchyError::ParentNotFound(999))
+        );
+    }
+
+    #[test]
+    fn test_remove_unit_removes_subtree() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let base = store
+            .add_child(det, UnitKind::Output, "Base Sounder")
+            .unwrap();
+        let beacon = store
+            .add_child(base, UnitKind::FireAlarmDevice, "Beacon")
+            .unwrap();
+        let other = store.add_unit(UnitKind::Detector, "Detector 2");
+
+        assert!(store.remove_unit(det));
+        assert!(store.get_unit(beacon).is_none());
+        assert!(store.get_unit(base).is_none());
+        assert!(store.get_unit(other).is_some());
+        assert_eq!(store.count(), 1);
+    }
+
+    #[test]
+    fn test_assign_devices_and_zones() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let mcp = store.add_unit(UnitKind::ManualCallPoint, "Manual Call Point 1");
+        let dz = store.add_unit(UnitKind::DetectionZone, "Detection Zone 1");
+        let az = store.add_unit(UnitKind::AlarmZone, "Alarm Zone 1");
+        let fad = store.add_unit(UnitKind::FireAlarmDevice, "Fire Alarm Device 1");
+
+        assert_eq!(store.assign_to_zone(det, dz), Ok(()));
+        assert_eq!(store.assign_to_zone(mcp, dz), Ok(()));
+        assert_eq!(store.assign_to_zone(dz, az), Ok(()));
+        assert_eq!(store.assign_to_zone(fad, az), Ok(()));
+
+        assert_eq!(store.zone_of(det), Some(dz));
+        let dz_members: Vec<u64> = store.members_of(dz).map(|unit| unit.id()).collect();
+        assert_eq!(dz_members, vec![det, mcp]);
+        let az_members: Vec<u64> = store.members_of(az).map(|unit| unit.id()).collect();
+        assert_eq!(az_members, vec![dz, fad]);
+        let by_zone: BTreeMap<u64, Vec<u64>> = store
+            .members_by_zone()
+            .into_iter()
+            .map(|(zone, members)| (zone, members.iter().map(|unit| unit.id()).collect()))
+            .collect();
+        assert_eq!(
+            by_zone,
+            BTreeMap::from([(dz, dz_members), (az, az_members)])
+        );
+    }
+
+    #[test]
+    fn test_assign_rejects_wrong_kinds() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let az = store.add_unit(UnitKind::AlarmZone, "Alarm Zone 1");
+        let out = store.add_unit(UnitKind::Output, "Output 1");
+
+        assert_eq!(
+            store.assign_to_zone(det, az),
+            Err(MembershipError::NotAssignable {
+                member: UnitKind::Detector,
+                zone: UnitKind::AlarmZone
+            })
+        );
+        assert!(store.assign_to_zone(out, az).is_err());
+        assert_eq!(
+            store.assign_to_zone(999, az),
+            Err(MembershipError::UnitNotFound(999))
+        );
+        assert_eq!(
+            store.assign_to_zone(det, 999),
+            Err(MembershipError::ZoneNotFound(999))
+        );
+        assert_eq!(store.zone_of(det), None);
+    }
+
+    #[test]
+    fn test_reassign_moves_member() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let dz1 = store.add_unit(UnitKind::DetectionZone, "Detection Zone 1");
+        let dz2 = store.add_unit(UnitKind::DetectionZone, "Detection Zone 2");
+
+        store.assign_to_zone(det, dz1).unwrap();
+        store.assign_to_zone(det, dz2).unwrap();
+
+        assert_eq!(store.zone_of(det), Some(dz2));
+        assert_eq!(store.members_of(dz1).count(), 0);
+    }
+
+    #[test]
+    fn test_unassign_from_zone() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let dz = store.add_unit(UnitKind::DetectionZone, "Detection Zone 1");
+        store.assign_to_zone(det, dz).unwrap();
+
+        assert!(store.unassign_from_zone(det));
+        assert!(!store.unassign_from_zone(det));
+        assert_eq!(store.zone_of(det), None);
+    }
+
+    #[test]
+    fn test_removing_zone_leaves_members_unassigned() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let dz = store.add_unit(UnitKind::DetectionZone, "Detection Zone 1");
+        let az = store.add_unit(UnitKind::AlarmZone, "Alarm Zone 1");
+        store.assign_to_zone(det, dz).unwrap();
+        store.assign_to_zone(dz, az).unwrap();
+
+        assert!(store.remove_unit(dz));
+
+        assert!(store.get_unit(det).is_some());
+        assert_eq!(store.zone_of(det), None);
+        assert_eq!(store.members_of(az).count(), 0);
+    }
+
+    #[test]
+    fn test_removing_member_drops_membership() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let dz = store.add_unit(UnitKind::DetectionZone, "Detection Zone 1");
+        store.assign_to_zone(det, dz).unwrap();
+
+        store.remove_unit(det);
+
+        assert_eq!(store.members_of(dz).count(), 0);
+        assert_eq!(store.membership.zone_of(det), None);
+    }
+
+    #[test]
+    fn test_clear_drops_memberships() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let dz = store.add_unit(UnitKind::DetectionZone, "Detection Zone 1");
+        store.assign_to_zone(det, dz).unwrap();
+
+        store.clear();
+
+        assert_eq!(store.membership, Membership::new());
+    }
+
+    #[test]
+    fn test_rule_inputs_and_outputs_check_kinds() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let out = store.add_unit(UnitKind::Output, "Output 1");
+        let rule = store
+            .cause_effect_mut()
+            .add_rule("Rule 1", InputLogic::Any)
+            .unwrap();
+
+        assert_eq!(store.set_rule_input(rule, det, true), Ok(()));
+        assert_eq!(store.set_rule_output(rule, out, true), Ok(()));
+        assert_eq!(
+            store.set_rule_input(rule, out, true),
+            Err(CauseEffectError::NotAnInput(UnitKind::Output))
+        );
+        assert_eq!(
+            store.set_rule_output(rule, det, true),
+            Err(CauseEffectError::NotAnOutput(UnitKind::Detector))
+        );
+        assert_eq!(
+            store.set_rule_input(rule, 999, true),
+            Err(CauseEffectError::UnitNotFound(999))
+        );
+        assert_eq!(
+            store.set_rule_input(99, det, true),
+            Err(CauseEffectError::RuleNotFound(99))
+        );
+    }
+
+    #[test]
+    fn test_removing_unit_drops_rule_references() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let out = store.add_unit(UnitKind::Output, "Output 1");
+        let rule = store
+            .cause_effect_mut()
+            .add_rule("Rule 1", InputLogic::Any)
+            .unwrap();
+        store.set_rule_input(rule, det, true).unwrap();
+        store.set_rule_output(rule, out, true).unwrap();
+
+        store.remove_unit(det);
+
+        let rule = store.cause_effect().get_rule(rule).unwrap();
+        assert!(rule.inputs().is_empty());
+        assert_eq!(rule.outputs().len(), 1);
+    }
+
+    #[test]
+    fn test_clear_drops_rules() {
+        let mut store = UnitStore::new();
+        store
+            .cause_effect_mut()
+            .add_rule("Rule 1", InputLogic::Any)
+            .unwrap();
+
+        store.clear();
+
+        assert!(store.cause_effect().rules().is_empty());
+    }
+
+    #[test]
+    fn test_check_name() {
+        let mut store = UnitStore::new();
+        let det1 = store.add_unit(UnitKind::Detector, "Detector 1");
+        store.add_unit(UnitKind::Detector, "Detector 2");
+        store.add_unit(UnitKind::Output, "Output 1");
+
+        assert_eq!(store.check_name(det1, "Kitchen"), Ok(()));
+        assert_eq!(store.check_name(det1, "Detector 1"), Ok(()));
+        assert_eq!(store.check_name(det1, "Output 1"), Ok(()));
+        assert_eq!(store.check_name(det1, "  "), Err(NameError::Empty));
+        assert_eq!(
+            store.check_name(det1, &"x".repeat(MAX_NAME_LENGTH + 1)),
+            Err(NameError::TooLong {
+                max: MAX_NAME_LENGTH
+            })
+        );
+        assert_eq!(
+            store.check_name(det1, "Detector 2"),
+            Err(NameError::Duplicate { other: 2 })
+        );
+    }
+
+    #[test]
+    fn test_set_address_checks_range_and_collisions() {
+        let mut store = UnitStore::new();
+        let number = store.add_loop(10).unwrap();
+        let det1 = store.add_unit(UnitKind::Detector, "Detector 1");
+        let det2 = store.add_unit(UnitKind::Detector, "Detector 2");
+        let zone = store.add_unit(UnitKind::DetectionZone, "Detection Zone 1");
+
+        assert_eq!(
+            store.set_address(det1, Some(LoopAddress::new(number, 5))),
+            Ok(())
+        );
+        assert_eq!(
+            store.set_address(det2, Some(LoopAddress::new(number, 5))),
+            Err(AddressError::AddressInUse {
+                address: LoopAddress::new(number, 5),
+                other: det1
+            })
+        );
+        assert_eq!(
+            store.set_address(det2, Some(LoopAddress::new(number, 11))),
+            Err(AddressError::AddressOutOfRange {
+                address: 11,
+                capacity: 10
+            })
+        );
+        assert_eq!(
+            store.set_address(det2, Some(LoopAddress::new(number, 0))),
+            Err(AddressError::AddressOutOfRange {
+                address: 0,
+                capacity: 10
+            })
+        );
+        assert_eq!(
+            store.set_address(det2, Some(LoopAddress::new(9, 1))),
+            Err(AddressError::LoopNotFound(9))
+        );
+        assert_eq!(
+            store.set_address(zone, Some(LoopAddress::new(number, 1))),
+            Err(AddressError::NotAddressable(UnitKind::DetectionZone))
+        );
+    }
+
+    #[test]
+    fn test_auto_address_fills_loops_and_adds_new_ones() {
+        let mut store = UnitStore::new();
+        store.add_loop(2).unwrap();
+        let ids: Vec<u64> = (1..=3)
+            .map(|i| store.add_unit(UnitKind::Detector, &format!("Detector {}", i)))
+            .collect();
+
+        let addresses: Vec<LoopAddress> = ids
+            .iter()
+            .map(|id| store.auto_address(*id).unwrap())
+            .collect();
+
+        assert_eq!(
+            addresses,
+            vec![
+                LoopAddress::new(1, 1),
+                LoopAddress::new(1, 2),
+                LoopAddress::new(2, 1)
+            ]
+        );
+        assert_eq!(store.loops()[1].capacity(), DEFAULT_LOOP_CAPACITY);
+        // Already addressed devices keep their address
+        assert_eq!(store.auto_address(ids[0]), Ok(LoopAddress::new(1, 1)));
+    }
+
+    #[test]
+    fn test_readdress_loop_closes_gaps_in_order() {
+        let mut store = UnitStore::new();
+        let number = store.add_loop(50).unwrap();
+        let a = store.add_unit(UnitKind::Detector, "A");
+        let b = store.add_unit(UnitKind::ManualCallPoint, "B");
+        let c = store.add_unit(UnitKind::Output, "C");
+        store
+            .set_address(a, Some(LoopAddress::new(number, 1)))
+            .unwrap();
+        store
+            .set_address(b, Some(LoopAddress::new(number, 40)))
+            .unwrap();
+        store
+            .set_address(c, Some(LoopAddress::new(number, 7)))
+            .unwrap();
+
+        let moved = store.readdress_loop(number).unwrap();
+
+        assert_eq!(
+            moved,
+            vec![
+                (c, LoopAddress::new(number, 7), LoopAddress::new(number, 2)),
+                (b, LoopAddress::new(number, 40), LoopAddress::new(number, 3)),
+            ]
+        );
+        assert_eq!(store.readdress_loop(number), Ok(Vec::new()));
+        assert_eq!(store.readdress_loop(2), Err(AddressError::LoopNotFound(2)));
+    }
+
+    #[test]
+    fn test_loop_capacity_and_utilisation() {
+        let mut store = UnitStore::new();
+        let number = store.add_loop(10).unwrap();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        store
+            .set_address(det, Some(LoopAddress::new(number, 8)))
+            .unwrap();
+
+        assert_eq!(
+            store.set_loop_capacity(number, 5),
+            Err(AddressError::CapacityBelowUsage {
+                capacity: 5,
+                highest: 8
+            })
+        );
+        assert_eq!(
+            store.set_loop_capacity(number, MAX_LOOP_ADDRESS + 1),
+            Err(AddressError::InvalidCapacity(MAX_LOOP_ADDRESS + 1))
+        );
+        assert_eq!(store.set_loop_capacity(number, 8), Ok(()));
+        assert_eq!(
+            store.loop_utilisation(),
+            vec![LoopUtilisation {
+                loop_number: number,
+                used: 1,
+                capacity: 8
+            }]
+        );
+        assert_eq!(store.loop_utilisation()[0].percent(), 12);
+    }
+
+    #[test]
+    fn test_removing_loop_and_units_frees_addresses() {
+        let mut store = UnitStore::new();
+        let number = store.add_loop(10).unwrap();
+        let det1 = store.add_unit(UnitKind::Detector, "Detector 1");
+        let det2 = store.add_unit(UnitKind::Detector, "Detector 2");
+        store.auto_address(det1).unwrap();
+        store.auto_address(det2).unwrap();
+
+        store.remove_unit(det1);
+        assert_eq!(
+            store.first_free_address(number),
+            Ok(LoopAddress::new(number, 1))
+        );
+
+        assert!(store.remove_loop(number));
+        assert_eq!(store.get_unit(det2).unwrap().address(), None);
+        assert!(store.address_collisions().is_empty());
+    }
+
+    #[test]
+    fn test_panels_own_units_and_subtrees() {
+        let mut store = UnitStore::new();
+        let annex = store.add_panel("Annex").unwrap();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let base = store
+            .add_child(det, UnitKind::FireAlarmDevice, "Base Sounder")
+            .unwrap();
+        let out = store.add_unit(UnitKind::Output, "Output 1");
+
+        assert_eq!(store.panel_of(base), Some(1));
+        assert_eq!(store.panel_of(0), None);
+        store.set_panel(det, annex).unwrap();
+        assert_eq!(store.panel_of(base), Some(annex));
+        assert_eq!(store.set_panel(0, annex), Err(NetworkError::RootHasNoPanel));
+        assert_eq!(store.counts_on_panel(annex)[&UnitKind::Detector], 1);
+        assert_eq!(store.counts_on_panel(1)[&UnitKind::Output], 1);
+
+        assert_eq!(
+            store.remove_panel(annex),
+            Err(NetworkError::PanelNotEmpty {
+                panel: annex,
+                units: 2
+            })
+        );
+        store.remove_unit(det);
+        assert_eq!(store.network().assignments().count(), 0);
+        store.remove_panel(annex).unwrap();
+        assert_eq!(
+            store
+                .units_on_panel(1)
+                .map(|unit| unit.id())
+                .collect::<Vec<_>>(),
+            vec![out]
+        );
+    }
+
+    #[test]
+    fn test_mounted_units_follow_their_parent_panel() {
+        let mut store = UnitStore::new();
+        let annex = store.add_panel("Annex").unwrap();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let other = store.add_unit(UnitKind::Detector, "Detector 2");
+        store.set_panel(other, annex).unwrap();
+        let sounder = store
+            .add_child(other, UnitKind::FireAlarmDevice, "Base Sounder")
+            .unwrap();
+        let beacon = store.add_child(det, UnitKind::Output, "Beacon").unwrap();
+        assert_eq!(store.panel_of(sounder), Some(annex));
+
+        store.move_unit(det, other).unwrap();
+        assert_eq!(store.panel_of(det), Some(annex));
+        assert_eq!(store.panel_of(beacon), Some(annex));
+        assert_eq!(
+            store.set_panel(det, 1),
+            Err(NetworkError::Mounted {
+                unit: det,
+                parent: other
+            })
+        );
+        assert_eq!(store.set_panel(det, annex), Ok(()));
+
+        // Back under the Root a unit keeps its panel until it is given another one
+        store.move_unit(det, 0).unwrap();
+        assert_eq!(store.panel_of(det), Some(annex));
+        store.set_panel(det, 1).unwrap();
+        assert_eq!(store.panel_of(beacon), Some(1));
+    }
+
+    #[test]
+    fn test_rules_only_reach_networked_panels() {
+        let mut store = UnitStore::new();
+        let annex = store.add_panel("Annex").unwrap();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let local = store.add_unit(UnitKind::Output, "Output 1");
+        let remote = store.add_unit(UnitKind::Output, "Output 2");
+        store.set_panel(remote, annex).unwrap();
+        let rule = store
+            .cause_effect_mut()
+            .add_rule("Rule 1", InputLogic::Any)
+            .unwrap();
+        store.set_rule_input(rule, det, true).unwrap();
+        store.set_rule_output(rule, local, true).unwrap();
+        store.set_rule_output(rule, remote, true).unwrap();
+        let active = BTreeSet::from([det]);
+
+        assert_eq!(store.rule_panels(rule), BTreeSet::from([1, annex]));
+        assert_eq!(store.unreachable_outputs(rule), vec![remote]);
+        assert_eq!(store.evaluate_rules(&active), BTreeSet::from([local]));
+        store.add_network_link(1, annex).unwrap();
+        assert_eq!(
+            store.evaluate_rules(&active),
+            BTreeSet::from([local, remote])
+        );
+        assert!(store.unreachable_outputs(rule).is_empty());
+    }
+
+    // Benchmarks: run with `cargo test --release bench_ -- --ignored --nocapture`. They print their timings rather than
+    // assert on them, as wall-clock times depend on the machine. The id benchmark compares the store against the linear
+    // scans over a `Vec<Unit>` it used before ids were indexed.
+    const BENCH_UNITS: u64 = 10_000;
+
+    fn timed<T>(label: &str, run: impl FnOnce() -> T) -> (T, std::time::Duration) {
+        let start = std::time::Instant::now();
+        let result = run();
+        let elapsed = start.elapsed();
+        println!("{:<28} {:>10.2?}", label, elapsed);
+        (result, elapsed)
+    }
+
+    fn linear_units() -> Vec<Unit> {
+        (0..=BENCH_UNITS)
+            .map(|id| Unit::new(id, UnitKind::Detector, &format!("Detector {}", id)))
+            .collect()
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_add_get_remove_10k() {
+        let (mut store, _) = timed("store add", || {
+            let mut store = UnitStore::new();
+            for id in 1..=BENCH_UNITS {
+                store.add_unit(UnitKind::Detector, &format!("Detector {}", id));
+            }
+            store
+        });
+        timed("linear add", linear_units);
+
+        let (found, _) = timed("store get", || {
+            (1..=BENCH_UNITS)
+                .filter(|id| store.get_unit(*id).is_some())
+                .count()
+        });
+        assert_eq!(found, BENCH_UNITS as usize);
+        let units = linear_units();
+        let (found, _) = timed("linear get", || {
+            (1..=BENCH_UNITS)
+                .filter(|id| units.iter().any(|unit| unit.id() == *id))
+                .count()
+        });
+        assert_eq!(found, BENCH_UNITS as usize);
+
+        timed("store remove", || {
+            for id in 1..=BENCH_UNITS {
+                assert!(store.remove_unit(id));
+            }
+        });
+        assert_eq!(store.count(), 0);
+        let mut units = linear_units();
+        timed("linear remove", || {
+            for id in 1..=BENCH_UNITS {
+                let index = units.iter().position(|unit| unit.id() == id).unwrap();
+                units.remove(index);
+            }
+        });
+        assert_eq!(units.len(), 1);
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_configure_and_address() {
+        let kinds = UnitKind::CONFIGURABLE.map(|kind| (kind, crate::model::MAX_UNITS_PER_KIND));
+        let (plan, _) = timed("configure plan 6k", || {
+            crate::model::ConfigurePlan::for_panel(&UnitStore::new(), None, &kinds)
+        });
+        let (store, _) = timed("configure apply 6k", || {
+            let mut store = UnitStore::new();
+            plan.apply(&mut store).unwrap();
+            store
+        });
+        assert_eq!(store.count(), 6 * crate::model::MAX_UNITS_PER_KIND);
+        let addressed = store
+            .get_all_units()
+            .iter()
+            .filter(|unit| unit.address().is_some())
+            .count();
+        assert_eq!(addressed, 4 * crate::model::MAX_UNITS_PER_KIND);
+        assert_eq!(store.address_collisions(), vec![]);
+
+        let mut store = UnitStore::new();
+        let ids: Vec<u64> = (1..=BENCH_UNITS)
+            .map(|id| store.add_unit(UnitKind::Detector, &format!("Detector {}", id)))
+            .collect();
+        timed("auto-address 10k", || {
+            for id in &ids {
+                store.auto_address(*id).unwrap();
+            }
+        });
+        assert_eq!(
+            store.loops().len(),
+            (BENCH_UNITS as usize).div_ceil(DEFAULT_LOOP_CAPACITY as usize)
+        );
+        timed("readdress loops 10k", || {
+            for number in 1..=store.loops().len() as u16 {
+                store.readdress_loop(number).unwrap();
+            }
+        });
+    }
 }
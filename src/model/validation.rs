@@ -0,0 +1,439 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use super::{RuleSet, UnitKind, UnitProperties, UnitStore};
+
+/// The most units of one kind a configuration may have.
+pub const MAX_UNITS_PER_KIND: usize = 1000;
+
+/// How serious a [`Finding`] is.
+///
+/// Ordered from most to least serious, so sorting findings puts errors first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The configuration cannot work as intended.
+    Error,
+    /// The configuration works but is probably incomplete.
+    Warning,
+    /// Worth knowing, but often intentional.
+    Info,
+}
+
+impl Severity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Error => "Error",
+            Severity::Warning => "Warning",
+            Severity::Info => "Info",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// One problem found in a configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub severity: Severity,
+    /// A stable identifier of the check that produced the finding, e.g. `empty-detection-zone`.
+    pub code: &'static str,
+    pub message: String,
+    /// The units the finding is about. Empty for findings about the configuration as a whole.
+    pub units: Vec<u64>,
+    /// The standards rule that failed, for findings produced by a [`RuleSet`].
+    pub rule: Option<String>,
+}
+
+impl Finding {
+    pub fn new(severity: Severity, code: &'static str, message: String, units: Vec<u64>) -> Self {
+        Self {
+            severity,
+            code,
+            message,
+            units,
+            rule: None,
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}] {}", self.severity, self.code, self.message)?;
+        if let Some(rule) = &self.rule {
+            write!(f, " ({})", rule)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks a configuration for things that are missing or inconsistent.
+///
+/// Runs the built-in completeness checks and the project's [`RuleSet::standard`] zoning rules. Findings are sorted by
+/// severity, errors first, and otherwise keep the order of the checks.
+pub fn validate(store: &UnitStore) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    check_unit_counts(store, &mut findings);
+    check_empty_detection_zones(store, &mut findings);
+    check_silent_alarm_zones(store, &mut findings);
+    check_undriven_outputs(store, &mut findings);
+    check_incomplete_rules(store, &mut findings);
+    check_unzoned_devices(store, &mut findings);
+    check_addresses(store, &mut findings);
+    check_network(store, &mut findings);
+    findings.extend(RuleSet::standard().check(store));
+    findings.sort_by_key(|finding| finding.severity);
+    findings
+}
+
+fn check_unit_counts(store: &UnitStore, findings: &mut Vec<Finding>) {
+    for (kind, count) in store.counts_by_kind() {
+        if count > MAX_UNITS_PER_KIND {
+            findings.push(Finding::new(
+                Severity::Error,
+                "too-many-units",
+                format!(
+                    "{} {} exceed the limit of {}",
+                    count,
+                    kind.plural_label(),
+                    MAX_UNITS_PER_KIND
+                ),
+                Vec::new(),
+            ));
+        }
+    }
+}
+
+fn check_empty_detection_zones(store: &UnitStore, findings: &mut Vec<Finding>) {
+    let members = store.members_by_zone();
+    for zone in store.units_of_kind(UnitKind::DetectionZone) {
+        let has_detector = members.get(&zone.id()).is_some_and(|members| {
+            members
+                .iter()
+                .any(|member| member.kind() == UnitKind::Detector)
+        });
+        if !has_detector {
+            findings.push(Finding::new(
+                Severity::Warning,
+                "empty-detection-zone",
+                format!("{} has no detectors", zone.name()),
+                vec![zone.id()],
+            ));
+        }
+    }
+}
+
+fn check_silent_alarm_zones(store: &UnitStore, findings: &mut Vec<Finding>) {
+    let members = store.members_by_zone();
+    for zone in store.units_of_kind(UnitKind::AlarmZone) {
+        let has_sounder = members.get(&zone.id()).is_some_and(|members| {
+            members.iter().any(|member| {
+                matches!(
+                    member.properties(),
+                    UnitProperties::FireAlarmDevice { device_type } if device_type.is_audible()
+                )
+            })
+        });
+        if !has_sounder {
+            findings.push(Finding::new(
+                Severity::Warning,
+                "silent-alarm-zone",
+                format!("{} has no sounders", zone.name()),
+                vec![zone.id()],
+            ));
+        }
+    }
+}
+
+fn check_undriven_outputs(store: &UnitStore, findings: &mut Vec<Finding>) {
+    let driven: BTreeSet<u64> = store
+        .cause_effect()
+        .rules()
+        .iter()
+        .flat_map(|rule| rule.outputs().iter().copied())
+        .collect();
+    for output in store.units_of_kind(UnitKind::Output) {
+        if !driven.contains(&output.id()) {
+            findings.push(Finding::new(
+                Severity::Warning,
+                "undriven-output",
+                format!("{} is not driven by any rule", output.name()),
+                vec![output.id()],
+            ));
+        }
+    }
+}
+
+fn check_incomplete_rules(store: &UnitStore, findings: &mut Vec<Finding>) {
+    for rule in store.cause_effect().rules() {
+        let missing = match (rule.inputs().is_empty(), rule.outputs().is_empty()) {
+            (true, true) => "inputs or outputs",
+            (true, false) => "inputs",
+            (false, true) => "outputs",
+            (false, false) => continue,
+        };
+        findings.push(Finding::new(
+            Severity::Warning,
+            "incomplete-rule",
+            format!("Rule \"{}\" has no {}", rule.name(), missing),
+            Vec::new(),
+        ));
+    }
+}
+
+fn check_unzoned_devices(store: &UnitStore, findings: &mut Vec<Finding>) {
+    let unzoned: Vec<u64> = store
+        .get_all_units()
+        .iter()
+        .filter(|unit| unit.kind().zone_kind() == Some(UnitKind::DetectionZone))
+        .filter(|unit| store.zone_of(unit.id()).is_none())
+        .map(|unit| unit.id())
+        .collect();
+    if !unzoned.is_empty() {
+        findings.push(Finding::new(
+            Severity::Info,
+            "unzoned-device",
+            format!(
+                "{} detectors or call points are not in a detection zone",
+                unzoned.len()
+            ),
+            unzoned,
+        ));
+    }
+}
+
+fn check_addresses(store: &UnitStore, findings: &mut Vec<Finding>) {
+    for (address, ids) in store.address_collisions() {
+        findings.push(Finding::new(
+            Severity::Error,
+            "address-collision",
+            format!("{} devices share address {}", ids.len(), address),
+            ids,
+        ));
+    }
+
+    let unaddressed: Vec<u64> = store
+        .get_all_units()
+        .iter()
+        .filter(|unit| unit.kind().is_addressable() && unit.address().is_none())
+        .map(|unit| unit.id())
+        .collect();
+    if !unaddressed.is_empty() {
+        findings.push(Finding::new(
+            Severity::Warning,
+            "unaddressed-device",
+            format!("{} devices have no loop address", unaddressed.len()),
+            unaddressed,
+        ));
+    }
+}
+
+fn check_network(store: &UnitStore, findings: &mut Vec<Finding>) {
+    let network = store.network();
+    let panel_name = |number: u16| {
+        network
+            .get_panel(number)
+            .map(|panel| panel.name().to_string())
+            .unwrap_or_else(|| format!("panel {}", number))
+    };
+
+    for rule in store.cause_effect().rules() {
+        let mut unreachable: BTreeMap<u16, Vec<u64>> = BTreeMap::new();
+        for output in store.unreachable_outputs(rule.id()) {
+            if let Some(panel) = store.panel_of(output) {
+                unreachable.entry(panel).or_default().push(output);
+            }
+        }
+        for (panel, outputs) in unreachable {
+            findings.push(Finding::new(
+                Severity::Error,
+                "unreachable-panel",
+                format!(
+                    "Rule \"{}\" drives units on {}, which the network does not connect to its inputs",
+                    rule.name(),
+                    panel_name(panel)
+                ),
+                outputs,
+            ));
+        }
+    }
+
+    if network.panels().len() > 1 {
+        for panel in network.panels() {
+            if !network
+                .links()
+                .iter()
+                .any(|link| link.other_end(panel.number()).is_some())
+            {
+                findings.push(Finding::new(
+                    Severity::Warning,
+                    "isolated-panel",
+                    format!("{} has no network link to another panel", panel.name()),
+                    Vec::new(),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{AlarmDeviceType, InputLogic};
+
+    fn codes(findings: &[Finding]) -> Vec<&'static str> {
+        findings.iter().map(|finding| finding.code).collect()
+    }
+
+    #[test]
+    fn test_empty_store_has_no_findings() {
+        assert!(validate(&UnitStore::new()).is_empty());
+    }
+
+    #[test]
+    fn test_empty_detection_zone() {
+        let mut store = UnitStore::new();
+        let zone = store.add_unit(UnitKind::DetectionZone, "Detection Zone 1");
+        let mcp = store.add_unit(UnitKind::ManualCallPoint, "Manual Call Point 1");
+        store.assign_to_zone(mcp, zone).unwrap();
+
+        let findings = validate(&store);
+
+        let finding = findings
+            .iter()
+            .find(|finding| finding.code == "empty-detection-zone")
+            .unwrap();
+        assert_eq!(finding.severity, Severity::Warning);
+        assert_eq!(finding.units, vec![zone]);
+
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        store.assign_to_zone(det, zone).unwrap();
+        assert!(!codes(&validate(&store)).contains(&"empty-detection-zone"));
+    }
+
+    #[test]
+    fn test_alarm_zone_with_only_beacons_is_silent() {
+        let mut store = UnitStore::new();
+        let zone = store.add_unit(UnitKind::AlarmZone, "Alarm Zone 1");
+        let fad = store.add_unit(UnitKind::FireAlarmDevice, "Beacon 1");
+        store.assign_to_zone(fad, zone).unwrap();
+        store
+            .get_unit_mut(fad)
+            .unwrap()
+            .set_properties(UnitProperties::FireAlarmDevice {
+                device_type: AlarmDeviceType::Beacon,
+            })
+            .unwrap();
+
+        assert!(codes(&validate(&store)).contains(&"silent-alarm-zone"));
+
+        store
+            .get_unit_mut(fad)
+            .unwrap()
+            .set_properties(UnitProperties::FireAlarmDevice {
+                device_type: AlarmDeviceType::SounderBeacon,
+            })
+            .unwrap();
+        assert!(!codes(&validate(&store)).contains(&"silent-alarm-zone"));
+    }
+
+    #[test]
+    fn test_undriven_output_and_incomplete_rule() {
+        let mut store = UnitStore::new();
+        let out1 = store.add_unit(UnitKind::Output, "Output 1");
+        let out2 = store.add_unit(UnitKind::Output, "Output 2");
+        let rule = store
+            .cause_effect_mut()
+            .add_rule("Rule 1", InputLogic::Any)
+            .unwrap();
+        store.set_rule_output(rule, out1, true).unwrap();
+
+        let findings = validate(&store);
+
+        let undriven: Vec<&Finding> = findings
+            .iter()
+            .filter(|finding| finding.code == "undriven-output")
+            .collect();
+        assert_eq!(undriven.len(), 1);
+        assert_eq!(undriven[0].units, vec![out2]);
+        assert!(findings
+            .iter()
+            .any(|finding| finding.code == "incomplete-rule"
+                && finding.message == "Rule \"Rule 1\" has no inputs"));
+    }
+
+    #[test]
+    fn test_too_many_units_is_an_error_listed_first() {
+        let mut store = UnitStore::new();
+        for i in 0..=MAX_UNITS_PER_KIND {
+            store.add_unit(UnitKind::Output, &format!("Output {}", i));
+        }
+
+        let findings = validate(&store);
+
+        assert_eq!(findings[0].code, "too-many-units");
+        assert_eq!(findings[0].severity, Severity::Error);
+        assert!(findings[1..]
+            .iter()
+            .all(|finding| finding.severity != Severity::Error));
+    }
+
+    #[test]
+    fn test_unzoned_and_unaddressed_devices() {
+        let mut store = UnitStore::new();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let fad = store.add_unit(UnitKind::FireAlarmDevice, "Fire Alarm Device 1");
+
+        let findings = validate(&store);
+
+        let unzoned = findings
+            .iter()
+            .find(|finding| finding.code == "unzoned-device")
+            .unwrap();
+        assert_eq!(unzoned.units, vec![det]);
+        let unaddressed = findings
+            .iter()
+            .find(|finding| finding.code == "unaddressed-device")
+            .unwrap();
+        assert_eq!(unaddressed.units, vec![det, fad]);
+    }
+
+    #[test]
+    fn test_cross_panel_rules_need_a_network_path() {
+        let mut store = UnitStore::new();
+        let annex = store.add_panel("Annex").unwrap();
+        let det = store.add_unit(UnitKind::Detector, "Detector 1");
+        let out = store.add_unit(UnitKind::Output, "Output 1");
+        store.set_panel(out, annex).unwrap();
+        let rule = store
+            .cause_effect_mut()
+            .add_rule("Rule 1", InputLogic::Any)
+            .unwrap();
+        store.set_rule_input(rule, det, true).unwrap();
+        store.set_rule_output(rule, out, true).unwrap();
+
+        let findings = validate(&store);
+
+        let unreachable = findings
+            .iter()
+            .find(|finding| finding.code == "unreachable-panel")
+            .unwrap();
+        assert_eq!(unreachable.severity, Severity::Error);
+        assert_eq!(unreachable.units, vec![out]);
+        assert_eq!(
+            findings
+                .iter()
+                .filter(|finding| finding.code == "isolated-panel")
+                .count(),
+            2
+        );
+
+        store.add_network_link(1, annex).unwrap();
+        let codes = codes(&validate(&store));
+        assert!(!codes.contains(&"unreachable-panel"));
+        assert!(!codes.contains(&"isolated-panel"));
+    }
+}
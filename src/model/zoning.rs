@@ -0,0 +1,413 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Finding, Severity, Unit, UnitKind, UnitProperties, UnitStore};
+
+/// The limits the zoning rules check against.
+///
+/// They are stored with the project so national variants of the standards can be applied. [`ZoningThresholds::presets`]
+/// lists the variants this build knows; any value can be changed afterwards.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ZoningThresholds {
+    /// The standard or variant the thresholds come from, cited in rule results.
+    pub variant: String,
+    /// The most detectors and manual call points one detection zone may hold.
+    pub max_devices_per_zone: usize,
+    /// The largest floor area one detection zone may cover, in square metres.
+    pub max_zone_area_m2: u32,
+    /// How many manual call points each exit of a zone needs.
+    pub call_points_per_exit: usize,
+    /// The most devices a single short circuit on a loop may take out of service.
+    pub max_devices_per_loop_fault: usize,
+}
+
+impl ZoningThresholds {
+    /// Limits following EN 54-2 and CEN/TS 54-14.
+    pub fn en54() -> Self {
+        Self {
+            variant: "EN 54".to_string(),
+            max_devices_per_zone: 32,
+            max_zone_area_m2: 1600,
+            call_points_per_exit: 1,
+            max_devices_per_loop_fault: 32,
+        }
+    }
+
+    /// Limits following BS 5839-1, which allows larger zones.
+    pub fn bs5839() -> Self {
+        Self {
+            variant: "BS 5839-1".to_string(),
+            max_zone_area_m2: 2000,
+            ..Self::en54()
+        }
+    }
+
+    /// The variants that can be picked as a starting point.
+    pub fn presets() -> Vec<Self> {
+        vec![Self::en54(), Self::bs5839()]
+    }
+}
+
+impl Default for ZoningThresholds {
+    fn default() -> Self {
+        Self::en54()
+    }
+}
+
+/// A standards check run over a whole configuration.
+///
+/// Implement this to add a check to a [`RuleSet`]. Findings do not need to cite the rule; the rule set does that.
+pub trait ZoningRule {
+    /// A stable identifier used as the finding code, e.g. `zone-device-limit`.
+    fn code(&self) -> &'static str;
+
+    /// What the rule requires, in a few words.
+    fn title(&self) -> &'static str;
+
+    fn check(&self, store: &UnitStore, thresholds: &ZoningThresholds) -> Vec<Finding>;
+}
+
+/// The zoning rules checked for a project.
+pub struct RuleSet {
+    rules: Vec<Box<dyn ZoningRule>>,
+}
+
+impl RuleSet {
+    /// A rule set without any rules.
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Every rule this build implements.
+    pub fn standard() -> Self {
+        Self::new()
+            .with(MaxDevicesPerZone)
+            .with(MaxZoneArea)
+            .with(CallPointsPerExit)
+            .with(LoopFaultLimit)
+    }
+
+    pub fn with(mut self, rule: impl ZoningRule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Runs every rule against the store's own thresholds. Each finding cites the variant and rule that produced it.
+    pub fn check(&self, store: &UnitStore) -> Vec<Finding> {
+        let thresholds = store.thresholds();
+        let mut findings = Vec::new();
+        for rule in &self.rules {
+            for mut finding in rule.check(store, thresholds) {
+                finding.rule = Some(format!("{}: {}", thresholds.variant, rule.title()));
+                findings.push(finding);
+            }
+        }
+        findings
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// Detection zones must not hold more than [`ZoningThresholds::max_devices_per_zone`] devices.
+pub struct MaxDevicesPerZone;
+
+impl ZoningRule for MaxDevicesPerZone {
+    fn code(&self) -> &'static str {
+        "zone-device-limit"
+    }
+
+    fn title(&self) -> &'static str {
+        "maximum devices per detection zone"
+    }
+
+    fn check(&self, store: &UnitStore, thresholds: &ZoningThresholds) -> Vec<Finding> {
+        let members = store.members_by_zone();
+        store
+            .units_of_kind(UnitKind::DetectionZone)
+            .filter_map(|zone| {
+                let count = members.get(&zone.id()).map_or(0, Vec::len);
+                (count > thresholds.max_devices_per_zone).then(|| {
+                    Finding::new(
+                        Severity::Error,
+                        self.code(),
+                        format!(
+                            "{} has {} devices, more than {}",
+                            zone.name(),
+                            count,
+                            thresholds.max_devices_per_zone
+                        ),
+                        vec![zone.id()],
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+/// Detection zones must not cover more than [`ZoningThresholds::max_zone_area_m2`]. Zones without an area are skipped.
+pub struct MaxZoneArea;
+
+impl ZoningRule for MaxZoneArea {
+    fn code(&self) -> &'static str {
+        "zone-area-limit"
+    }
+
+    fn title(&self) -> &'static str {
+        "maximum detection zone floor area"
+    }
+
+    fn check(&self, store: &UnitStore, thresholds: &ZoningThresholds) -> Vec<Finding> {
+        store
+            .units_of_kind(UnitKind::DetectionZone)
+            .filter_map(|zone| {
+                let UnitProperties::DetectionZone { floor_area_m2, .. } = zone.properties() else {
+                    return None;
+                };
+                (floor_area_m2 > thresholds.max_zone_area_m2).then(|| {
+                    Finding::new(
+                        Severity::Error,
+                        self.code(),
+                        format!(
+                            "{} covers {} m², more than {} m²",
+                            zone.name(),
+                            floor_area_m2,
+                            thresholds.max_zone_area_m2
+                        ),
+                        vec![zone.id()],
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+/// Every exit of a detection zone needs [`ZoningThresholds::call_points_per_exit`] manual call points in that zone.
+pub struct CallPointsPerExit;
+
+impl ZoningRule for CallPointsPerExit {
+    fn code(&self) -> &'static str {
+        "call-points-per-exit"
+    }
+
+    fn title(&self) -> &'static str {
+        "manual call point at every zone exit"
+    }
+
+    fn check(&self, store: &UnitStore, thresholds: &ZoningThresholds) -> Vec<Finding> {
+        let members = store.members_by_zone();
+        store
+            .units_of_kind(UnitKind::DetectionZone)
+            .filter_map(|zone| {
+                let UnitProperties::DetectionZone { exits, .. } = zone.properties() else {
+                    return None;
+                };
+                let required = exits as usize * thresholds.call_points_per_exit;
+                let call_points = members
+                    .get(&zone.id())
+                    .into_iter()
+                    .flatten()
+                    .filter(|member| member.kind() == UnitKind::ManualCallPoint)
+                    .count();
+                (call_points < required).then(|| {
+                    Finding::new(
+                        Severity::Error,
+                        self.code(),
+                        format!(
+                            "{} has {} exits but {} of the {} manual call points needed",
+                            zone.name(),
+                            exits,
+                            call_points,
+                            required
+                        ),
+                        vec![zone.id()],
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+/// A short circuit on a loop must not take out more than [`ZoningThresholds::max_devices_per_loop_fault`] devices.
+///
+/// A short circuit takes out the devices between the two nearest isolators around it. A loop without isolators loses
+/// every device.
+pub struct LoopFaultLimit;
+
+impl ZoningRule for LoopFaultLimit {
+    fn code(&self) -> &'static str {
+        "loop-fault-limit"
+    }
+
+    fn title(&self) -> &'static str {
+        "maximum devices lost to a single loop fault"
+    }
+
+    fn check(&self, store: &UnitStore, thresholds: &ZoningThresholds) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for l in store.loops() {
+            let devices: Vec<&Unit> = store.devices_on_loop(l.number()).collect();
+            for segment in fault_segments(&devices) {
+                if segment.len() > thresholds.max_devices_per_loop_fault {
+                    findings.push(Finding::new(
+                        Severity::Error,
+                        self.code(),
+                        format!(
+                            "A short circuit on loop {} would take out {} devices, more than {}",
+                            l.number(),
+                            segment.len(),
+                            thresholds.max_devices_per_loop_fault
+                        ),
+                        segment,
+                    ));
+                }
+            }
+        }
+        findings
+    }
+}
+
+// Splits the devices of a loop, in address order, into the groups a single short circuit takes out. The loop is a ring,
+// so the devices after the last isolator and those before the first one form one group.
+fn fault_segments(devices: &[&Unit]) -> Vec<Vec<u64>> {
+    let Some(first_isolator) = devices.iter().position(|unit| unit.has_isolator()) else {
+        return if devices.is_empty() {
+            Vec::new()
+        } else {
+            vec![devices.iter().map(|unit| unit.id()).collect()]
+        };
+    };
+
+    let mut segments = Vec::new();
+    let mut current = Vec::new();
+    // Start just after the first isolator and go once around the ring
+    for offset in 1..=devices.len() {
+        let unit = devices[(first_isolator + offset) % devices.len()];
+        if unit.has_isolator() {
+            if !current.is_empty() {
+                segments.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push(unit.id());
+        }
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zone_with(store: &mut UnitStore, floor_area_m2: u32, exits: u32) -> u64 {
+        let zone = store.add_unit(UnitKind::DetectionZone, "Detection Zone 1");
+        store
+            .get_unit_mut(zone)
+            .unwrap()
+            .set_properties(UnitProperties::DetectionZone {
+                floor_area_m2,
+                exits,
+            })
+            .unwrap();
+        zone
+    }
+
+    fn codes(findings: &[Finding]) -> Vec<&'static str> {
+        findings.iter().map(|finding| finding.code).collect()
+    }
+
+    #[test]
+    fn test_device_limit_per_zone() {
+        let mut store = UnitStore::new();
+        let zone = zone_with(&mut store, 0, 0);
+        for i in 0..3 {
+            let det = store.add_unit(UnitKind::Detector, &format!("Detector {}", i));
+            store.assign_to_zone(det, zone).unwrap();
+        }
+        let mut thresholds = ZoningThresholds::en54();
+        thresholds.max_devices_per_zone = 2;
+
+        let findings = MaxDevicesPerZone.check(&store, &thresholds);
+
+        assert_eq!(codes(&findings), vec!["zone-device-limit"]);
+        assert_eq!(findings[0].units, vec![zone]);
+        thresholds.max_devices_per_zone = 3;
+        assert!(MaxDevicesPerZone.check(&store, &thresholds).is_empty());
+    }
+
+    #[test]
+    fn test_zone_area_depends_on_variant() {
+        let mut store = UnitStore::new();
+        zone_with(&mut store, 1800, 0);
+
+        assert_eq!(
+            codes(&MaxZoneArea.check(&store, &ZoningThresholds::en54())),
+            vec!["zone-area-limit"]
+        );
+        assert!(MaxZoneArea
+            .check(&store, &ZoningThresholds::bs5839())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_call_points_per_exit() {
+        let mut store = UnitStore::new();
+        let zone = zone_with(&mut store, 0, 2);
+        let mcp = store.add_unit(UnitKind::ManualCallPoint, "Manual Call Point 1");
+        store.assign_to_zone(mcp, zone).unwrap();
+        let thresholds = ZoningThresholds::en54();
+
+        assert_eq!(
+            codes(&CallPointsPerExit.check(&store, &thresholds)),
+            vec!["call-points-per-exit"]
+        );
+
+        let mcp = store.add_unit(UnitKind::ManualCallPoint, "Manual Call Point 2");
+        store.assign_to_zone(mcp, zone).unwrap();
+        assert!(CallPointsPerExit.check(&store, &thresholds).is_empty());
+    }
+
+    #[test]
+    fn test_loop_fault_segments_wrap_around_isolators() {
+        let mut store = UnitStore::new();
+        store.add_loop(10).unwrap();
+        let ids: Vec<u64> = (1..=6)
+            .map(|i| {
+                let id = store.add_unit(UnitKind::Detector, &format!("Detector {}", i));
+                store.auto_address(id).unwrap();
+                id
+            })
+            .collect();
+        let mut thresholds = ZoningThresholds::en54();
+        thresholds.max_devices_per_loop_fault = 2;
+
+        // Without isolators the whole loop is lost
+        let findings = LoopFaultLimit.check(&store, &thresholds);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].units.len(), 6);
+
+        // Isolators at the 2nd and 4th device leave groups {3} and {5, 6, 1}
+        store.get_unit_mut(ids[1]).unwrap().set_isolator(true);
+        store.get_unit_mut(ids[3]).unwrap().set_isolator(true);
+        let findings = LoopFaultLimit.check(&store, &thresholds);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].units, vec![ids[4], ids[5], ids[0]]);
+    }
+
+    #[test]
+    fn test_rule_set_cites_rule_and_uses_project_thresholds() {
+        let mut store = UnitStore::new();
+        zone_with(&mut store, 1800, 0);
+
+        let findings = RuleSet::standard().check(&store);
+        assert_eq!(
+            findings[0].rule.as_deref(),
+            Some("EN 54: maximum detection zone floor area")
+        );
+
+        store.set_thresholds(ZoningThresholds::bs5839());
+        assert!(RuleSet::standard().check(&store).is_empty());
+        assert!(RuleSet::new().check(&store).is_empty());
+    }
+}
@@ -0,0 +1,208 @@
+use super::unit_details::units_below_note;
+use crate::model::{
+    bulk, BulkAction, BulkEdit, Command, History, Selection, UnitKind, UnitStore,
+    NUMBER_PLACEHOLDER,
+};
+use dioxus::prelude::*;
+
+/// How many renamed units the rename preview shows.
+const RENAME_PREVIEW: usize = 3;
+
+/// The actions shown in the "Selected Unit" panel when several units are selected.
+///
+/// Every action applies to all selected units or, if any one of them refuses it, to none; the reason is shown instead.
+/// Each action is one step of the shared [`History`].
+#[component]
+pub fn BulkEditPanel(selection: Signal<Selection>) -> Element {
+    let mut unit_store = use_context::<Signal<UnitStore>>();
+    let mut history = use_context::<Signal<History>>();
+    let mut pattern = use_signal(|| format!("Detector {} – Floor 2", NUMBER_PLACEHOLDER));
+    let mut confirm_delete = use_signal(|| false);
+    // Why the last action was refused, if it was
+    let mut feedback = use_signal(|| None::<String>);
+
+    let mut run = move |action: BulkAction| {
+        let edit = BulkEdit::new(selection.read().ids().iter().copied(), action);
+        let check = edit.check(&unit_store.read());
+        if let Err(err) = check {
+            feedback.set(Some(err.to_string()));
+            return;
+        }
+        let deletes = *edit.action() == BulkAction::Delete;
+        history
+            .write()
+            .execute(&mut unit_store.write(), Command::Bulk { edit });
+        feedback.set(None);
+        if deletes {
+            selection.write().clear();
+        }
+    };
+
+    let store = unit_store.read();
+    let ids = selection.read().ids().to_vec();
+    let delete_prompt = match units_below_note(&store, &ids) {
+        Some(note) => format!("Delete {} units and {note}? ", ids.len()),
+        None => format!("Delete {} units? ", ids.len()),
+    };
+    let rename_check = BulkEdit::new(
+        ids.iter().copied(),
+        BulkAction::Rename { pattern: pattern() },
+    )
+    .check(&store);
+    let rename_preview: Vec<String> = ids
+        .iter()
+        .take(RENAME_PREVIEW)
+        .enumerate()
+        .filter_map(|(index, id)| {
+            let unit = store.get_unit(*id)?;
+            Some(format!(
+                "{} → {}",
+                unit.name(),
+                bulk::rename(&pattern(), index + 1, unit.name())
+            ))
+        })
+        .collect();
+    let zones: Vec<(u64, String)> = store
+        .get_all_units()
+        .iter()
+        .filter(|unit| unit.kind().is_zone())
+        .map(|unit| (unit.id(), unit.name().to_string()))
+        .collect();
+    let loops: Vec<u16> = store.loops().iter().map(|l| l.number()).collect();
+    let panels: Vec<(u16, String)> = store
+        .network()
+        .panels()
+        .iter()
+        .map(|panel| (panel.number(), panel.name().to_string()))
+        .collect();
+
+    rsx! {
+        div { id: "bulk-edit",
+            p { class: "bulk-hint", "Ctrl-click adds or removes a unit, shift-click selects a range." }
+            div { class: "unit-field",
+                label { "Rename: " }
+                input {
+                    value: "{pattern}",
+                    title: "{{n}} is the number of the unit in the selection, {{name}} its current name",
+                    class: if rename_check.is_err() { "invalid" } else { "" },
+                    oninput: move |evt| pattern.set(evt.value()),
+                }
+                button {
+                    disabled: rename_check.is_err(),
+                    onclick: move |_| run(BulkAction::Rename { pattern: pattern() }),
+                    "Rename"
+                }
+            }
+            match &rename_check {
+                Ok(()) => rsx! {
+                    ul { class: "bulk-preview",
+                        for line in rename_preview {
+                            li { "{line}" }
+                        }
+                        if ids.len() > RENAME_PREVIEW {
+                            li { "…" }
+                        }
+                    }
+                },
+                Err(err) => rsx! { p { class: "unit-error", "Rename: {err}" } },
+            }
+            div { class: "unit-field",
+                label { "Zone: " }
+                select {
+                    onchange: move |evt| {
+                        match evt.value().as_str() {
+                            "" => {}
+                            "none" => run(BulkAction::MoveToZone { zone: None }),
+                            value => {
+                                if let Ok(zone) = value.parse() {
+                                    run(BulkAction::MoveToZone { zone: Some(zone) });
+                                }
+                            }
+                        }
+                    },
+                    option { value: "", selected: true, "Move to zone…" }
+                    option { value: "none", "No zone" }
+                    for (id, name) in zones {
+                        option { value: "{id}", "{name}" }
+                    }
+                }
+            }
+            div { class: "unit-field",
+                label { "Loop: " }
+                select {
+                    onchange: move |evt| {
+                        match evt.value().as_str() {
+                            "" => {}
+                            "none" => run(BulkAction::AssignToLoop { loop_number: None }),
+                            value => {
+                                if let Ok(number) = value.parse() {
+                                    run(BulkAction::AssignToLoop { loop_number: Some(number) });
+                                }
+                            }
+                        }
+                    },
+                    option { value: "", selected: true, "Assign to loop…" }
+                    option { value: "none", "No loop" }
+                    for number in loops {
+                        option { value: "{number}", "Loop {number}" }
+                    }
+                }
+            }
+            if panels.len() > 1 {
+                div { class: "unit-field",
+                    label { "Panel: " }
+                    select {
+                        onchange: move |evt| {
+                            if let Ok(panel) = evt.value().parse() {
+                                run(BulkAction::MoveToPanel { panel });
+                            }
+                        },
+                        option { value: "", selected: true, "Move to panel…" }
+                        for (number, name) in panels {
+                            option { value: "{number}", "{name}" }
+                        }
+                    }
+                }
+            }
+            div { class: "unit-field",
+                label { "Kind: " }
+                select {
+                    onchange: move |evt| {
+                        let value = evt.value();
+                        if let Some(kind) = UnitKind::CONFIGURABLE.into_iter().find(|kind| kind.label() == value) {
+                            run(BulkAction::ChangeKind { kind });
+                        }
+                    },
+                    option { value: "", selected: true, "Change kind to…" }
+                    for kind in UnitKind::CONFIGURABLE {
+                        option { value: "{kind.label()}", "{kind}" }
+                    }
+                }
+            }
+            if let Some(message) = feedback() {
+                p { class: "unit-error", "{message}" }
+            }
+            div { class: "unit-actions",
+                if confirm_delete() {
+                    span { "{delete_prompt}" }
+                    button {
+                        class: "delete",
+                        onclick: move |_| {
+                            confirm_delete.set(false);
+                            run(BulkAction::Delete);
+                        },
+                        "Delete"
+                    }
+                    button { onclick: move |_| confirm_delete.set(false), "Cancel" }
+                } else {
+                    button {
+                        class: "delete",
+                        onclick: move |_| confirm_delete.set(true),
+                        "Delete Selected"
+                    }
+                }
+                button { onclick: move |_| selection.write().clear(), "Clear Selection" }
+            }
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use crate::components::AccessGuard;
+use crate::model::{AccessLevel, CauseEffectError, Command, History, InputLogic, UnitStore};
+use dioxus::prelude::*;
+
+const CAUSE_EFFECT_CSS: Asset = asset!("/assets/styling/cause_effect.css");
+
+/// The cause-and-effect matrix page rendered for the `/cause-effect` route
+///
+/// Each row is one rule. Inputs are detectors, manual call points and detection zones; outputs are alarm zones, fire alarm
+/// devices and outputs. The matrix is part of the configuration, so it needs access level 3 like the Config page. Edits
+/// go through the shared [`History`] so they can be undone.
+///
+/// On sites with several panels every unit shows its panel. Rules that cross panels are marked, and so are outputs the
+/// network does not connect to the inputs, since those would never be driven.
+#[component]
+pub fn CauseEffect() -> Element {
+    rsx! {
+        AccessGuard { required: AccessLevel::Engineer, CauseEffectEditor {} }
+    }
+}
+
+/// The matrix editor behind the access guard of [`CauseEffect`]
+#[component]
+fn CauseEffectEditor() -> Element {
+    let mut unit_store = use_context::<Signal<UnitStore>>();
+    let mut history = use_context::<Signal<History>>();
+
+    let add_rule = move |_| {
+        // Named after the id it gets, so a name is not repeated after a rule is removed
+        let number = unit_store.read().cause_effect().next_id();
+        history.write().execute(
+            &mut unit_store.write(),
+            Command::AddRule {
+                name: format!("Rule {}", number),
+                logic: InputLogic::Any,
+            },
+        );
+    };
+
+    let rule_ids: Vec<u64> = unit_store
+        .read()
+        .cause_effect()
+        .rules()
+        .iter()
+        .map(|rule| rule.id())
+        .collect();
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: CAUSE_EFFECT_CSS }
+        div { id: "cause-effect",
+            h2 { "Cause & Effect" }
+            div { id: "cause-effect-buttons",
+                button { id: "add-rule-btn", onclick: add_rule, "Add Rule" }
+            }
+            if rule_ids.is_empty() {
+                p { class: "cause-effect-empty", "No rules defined" }
+            } else {
+                table { id: "cause-effect-table",
+                    thead {
+                        tr {
+                            th { "Rule" }
+                            th { "Logic" }
+                            th { "Inputs (causes)" }
+                            th { "Outputs (effects)" }
+                            th {}
+                        }
+                    }
+                    tbody {
+                        for rule_id in rule_ids {
+                            RuleRow { key: "{rule_id}", unit_store, rule_id }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// One editable row of the matrix.
+#[component]
+fn RuleRow(unit_store: Signal<UnitStore>, rule_id: u64) -> Element {
+    let mut history = use_context::<Signal<History>>();
+    let mut execute = move |command: Command| {
+        history.write().execute(&mut unit_store.write(), command);
+    };
+    // Why the last coincidence count typed was refused, if it was
+    let mut coincidence_error = use_signal(|| None::<String>);
+
+    let store = unit_store.read();
+    let Some(rule) = store.cause_effect().get_rule(rule_id) else {
+        return rsx! {};
+    };
+    let logic = rule.logic();
+    let networked = store.network().panels().len() > 1;
+    let unit_name = |id: &u64| {
+        let name = store
+            .get_unit(*id)
+            .map(|unit| unit.name().to_string())
+            .unwrap_or_default();
+        match store.panel_of(*id).filter(|_| networked) {
+            Some(panel) => format!("{} (P{})", name, panel),
+            None => name,
+        }
+    };
+    let cross_panel = store.rule_panels(rule_id).len() > 1;
+    let unreachable = store.unreachable_outputs(rule_id);
+
+    rsx! {
+        tr {
+            td {
+                input {
+                    value: "{rule.name()}",
+                    onchange: move |evt| execute(Command::RenameRule {
+                        id: rule_id,
+                        name: evt.value(),
+                    }),
+                }
+                if cross_panel {
+                    span { class: "rule-badge", title: "Inputs and outputs are on different panels", "Cross-panel" }
+                }
+            }
+            td {
+                select {
+                    onchange: move |evt| {
+                        let logic = match evt.value().as_str() {
+                            "all" => InputLogic::All,
+                            "coincidence" => InputLogic::Coincidence(2),
+                            _ => InputLogic::Any,
+                        };
+                        execute(Command::SetRuleLogic { id: rule_id, logic });
+                    },
+                    option { value: "any", selected: logic == InputLogic::Any, "Any" }
+                    option { value: "all", selected: logic == InputLogic::All, "All" }
+                    option {
+                        value: "coincidence",
+                        selected: matches!(logic, InputLogic::Coincidence(_)),
+                        "Coincidence"
+                    }
+                }
+                if let InputLogic::Coincidence(required) = logic {
+                    input {
+                        r#type: "number",
+                        min: "1",
+                        value: "{required}",
+                        class: if coincidence_error.read().is_some() { "invalid" } else { "" },
+                        onchange: move |evt| {
+                            match evt.value().parse::<usize>() {
+                                Ok(required) if required > 0 => {
+                                    coincidence_error.set(None);
+                                    execute(Command::SetRuleLogic {
+                                        id: rule_id,
+                                        logic: InputLogic::Coincidence(required),
+                                    });
+                                }
+                                Ok(_) => {
+                                    coincidence_error
+                                        .set(Some(CauseEffectError::InvalidCoincidence.to_string()));
+                                }
+                                Err(_) => {
+                                    coincidence_error.set(Some("enter a whole number of inputs".to_string()));
+                                }
+                            }
+                        }
+                    }
+                    if let Some(err) = coincidence_error() {
+                        div { class: "rule-error", "{err}" }
+                    }
+                }
+            }
+            td {
+                for input in rule.inputs().iter().copied() {
+                    span { class: "rule-chip",
+                        "{unit_name(&input)}"
+                        button {
+                            onclick: move |_| {
+                                execute(Command::SetRuleInput {
+                                    rule: rule_id,
+                                    unit: input,
+                                    enabled: false,
+                                });
+                            },
+                            "×"
+                        }
+                    }
+                }
+                select {
+                    onchange: move |evt| {
+                        if let Ok(unit) = evt.value().parse::<u64>() {
+                            execute(Command::SetRuleInput {
+                                rule: rule_id,
+                                unit,
+                                enabled: true,
+                            });
+                        }
+                    },
+                    option { value: "", selected: true, "Add input…" }
+                    for unit in store
+                        .get_all_units()
+                        .iter()
+                        .filter(|unit| unit.kind().is_cause() && !rule.inputs().contains(&unit.id()))
+                    {
+                        option { value: "{unit.id()}", "{unit_name(&unit.id())}" }
+                    }
+                }
+            }
+            td {
+                for output in rule.outputs().iter().copied() {
+                    span {
+                        class: if unreachable.contains(&output) { "rule-chip unreachable" } else { "rule-chip" },
+                        title: if unreachable.contains(&output) { "The network does not connect this panel to the inputs" } else { "" },
+                        "{unit_name(&output)}"
+                        button {
+                            onclick: move |_| {
+                                execute(Command::SetRuleOutput {
+                                    rule: rule_id,
+                                    unit: output,
+                                    enabled: false,
+                                });
+                            },
+                            "×"
+                        }
+                    }
+                }
+                select {
+                    onchange: move |evt| {
+                        if let Ok(unit) = evt.value().parse::<u64>() {
+                            execute(Command::SetRuleOutput {
+                                rule: rule_id,
+                                unit,
+                                enabled: true,
+                            });
+                        }
+                    },
+                    option { value: "", selected: true, "Add output…" }
+                    for unit in store
+                        .get_all_units()
+                        .iter()
+                        .filter(|unit| unit.kind().is_effect() && !rule.outputs().contains(&unit.id()))
+                    {
+                        option { value: "{unit.id()}", "{unit_name(&unit.id())}" }
+                    }
+                }
+            }
+            td {
+                button {
+                    class: "remove-rule-btn",
+                    onclick: move |_| execute(Command::RemoveRule { id: rule_id }),
+                    "Delete"
+                }
+            }
+        }
+    }
+}
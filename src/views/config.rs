@@ -1,11 +1,34 @@
-use crate::model::UnitStore;
+use super::bulk_edit::BulkEditPanel;
+use super::csv_import::CsvImportPreview;
+use super::loops::LoopPanel;
+use super::panels::PanelsPanel;
+use super::unit_details::UnitDetails;
+use super::unit_list::UnitList;
+use super::validation::{ValidationPanel, ValidationResults};
+use crate::components::{download_file, AccessGuard, ServerProjects};
+use crate::model::{
+    export_csv, load_project, save_project, AccessLevel, Command, ConfigurePlan, CsvImport,
+    History, PanelFilter, PanelStatus, Report, ReportFormat, Selection, Simulation, UnitKind,
+    UnitStore, MAX_UNITS_PER_KIND,
+};
 use dioxus::prelude::*;
 
 const CONFIG_CSS: Asset = asset!("/assets/styling/config.css");
 
 /// The Config page component rendered for the `/` route
+///
+/// Changing the configuration needs access level 3; anyone else is asked to log in. While a panel is picked in the navbar,
+/// the unit counts and the unit list are those of that panel, and Configure only adds and removes units there.
 #[component]
 pub fn Config() -> Element {
+    rsx! {
+        AccessGuard { required: AccessLevel::Engineer, ConfigEditor {} }
+    }
+}
+
+/// The configuration editor behind the access guard of [`Config`]
+#[component]
+fn ConfigEditor() -> Element {
     // Signals for each input so we can update them programmatically
     let mut manual_call_points = use_signal(|| "0".to_string());
     let mut detectors = use_signal(|| "0".to_string());
@@ -13,8 +36,41 @@ pub fn Config() -> Element {
     let mut alarm_zones = use_signal(|| "0".to_string());
     let mut fire_alarm_devices = use_signal(|| "0".to_string());
     let mut outputs = use_signal(|| "0".to_string());
-    let mut unit_store = use_signal(UnitStore::new);
-    let mut selected_unit_index = use_signal(|| None::<usize>);
+    let mut unit_store = use_context::<Signal<UnitStore>>();
+    let mut history = use_context::<Signal<History>>();
+    let panel_filter = use_context::<Signal<PanelFilter>>();
+    let mut simulation = use_context::<Signal<Simulation>>();
+    let mut panel_status = use_context::<Signal<PanelStatus>>();
+    // The units picked in the unit list. One is shown in the Selected Unit panel, several get the bulk actions.
+    let mut selection = use_signal(Selection::new);
+    // Changes computed by Configure, waiting to be reviewed and applied
+    let mut pending_plan = use_signal(|| None::<ConfigurePlan>);
+    // Removing units needs an explicit confirmation in the preview
+    let mut removals_confirmed = use_signal(|| false);
+    // Why the last Configure was refused, if it was
+    let mut configure_error = use_signal(|| None::<String>);
+    // A CSV file that was read and checked, waiting to be reviewed and applied, with its file name
+    let mut pending_import = use_signal(|| None::<(String, CsvImport)>);
+    // Outcome of the last Save or Open, shown below the project buttons
+    let mut project_status = use_signal(|| None::<Result<String, String>>);
+    // Findings of the last validation run, shown in the Validation section and on the unit list
+    let mut validation = use_signal(ValidationResults::default);
+
+    // Any change to the configuration makes the last validation run out of date
+    use_effect(move || {
+        let _ = unit_store.read();
+        if !validation.peek().stale {
+            validation.write().stale = true;
+        }
+    });
+    // Units removed by an undo, an import or a Configure drop out of the selection
+    use_effect(move || {
+        let store = unit_store.read();
+        let exists = |id: u64| store.get_unit(id).is_some();
+        if !selection.peek().ids().iter().all(|id| exists(*id)) {
+            selection.write().retain(exists);
+        }
+    });
 
     let clear_all = move |_| {
         manual_call_points.set("0".to_string());
@@ -23,8 +79,10 @@ pub fn Config() -> Element {
         alarm_zones.set("0".to_string());
         fire_alarm_devices.set("0".to_string());
         outputs.set("0".to_string());
-        unit_store.write().clear();
-        selected_unit_index.set(None);
+        history
+            .write()
+            .execute(&mut unit_store.write(), Command::Clear);
+        selection.write().clear();
     };
 
     let configure_units = move |_| {
@@ -35,35 +93,200 @@ pub fn Config() -> Element {
         let fad: usize = fire_alarm_devices().parse().unwrap_or(0);
         let out: usize = outputs().parse().unwrap_or(0);
 
-        let mut store = UnitStore::new();
+        let counts = vec![
+            (UnitKind::ManualCallPoint, mcp),
+            (UnitKind::Detector, det),
+            (UnitKind::DetectionZone, dz),
+            (UnitKind::AlarmZone, az),
+            (UnitKind::FireAlarmDevice, fad),
+            (UnitKind::Output, out),
+        ];
+        if let Some((kind, _)) = counts.iter().find(|(_, count)| *count > MAX_UNITS_PER_KIND) {
+            configure_error.set(Some(format!(
+                "At most {} {} can be configured",
+                MAX_UNITS_PER_KIND,
+                kind.plural_label()
+            )));
+            pending_plan.set(None);
+            return;
+        }
+        configure_error.set(None);
+        pending_plan.set(Some(ConfigurePlan::for_panel(
+            &unit_store.read(),
+            panel_filter().panel(),
+            &counts,
+        )));
+        removals_confirmed.set(false);
+    };
 
-        for i in 0..mcp {
-            store.add_unit(&format!("Manual Call Point {}", i + 1));
+    let apply_plan = move |_| {
+        let Some(plan) = pending_plan.take() else {
+            return;
+        };
+        if !plan.removals().is_empty() && !removals_confirmed() {
+            pending_plan.set(Some(plan));
+            return;
         }
-        for i in 0..det {
-            store.add_unit(&format!("Detector {}", i + 1));
+        if let Err(err) = plan.check(&unit_store.read()) {
+            configure_error.set(Some(format!("Configure failed: {}", err)));
+            return;
         }
-        for i in 0..dz {
-            store.add_unit(&format!("Detection Zone {}", i + 1));
+        history
+            .write()
+            .execute(&mut unit_store.write(), Command::Configure { plan });
+        selection.write().clear();
+    };
+
+    // Keeps the count inputs in step with a store that was replaced or restored, or another panel that was picked
+    let mut sync_count_inputs = move |store: &UnitStore| {
+        let counts = match panel_filter().panel() {
+            Some(panel) => store.counts_on_panel(panel),
+            None => store.counts_by_kind(),
+        };
+        let count_of = |kind| counts.get(&kind).copied().unwrap_or(0).to_string();
+        manual_call_points.set(count_of(UnitKind::ManualCallPoint));
+        detectors.set(count_of(UnitKind::Detector));
+        detection_zones.set(count_of(UnitKind::DetectionZone));
+        alarm_zones.set(count_of(UnitKind::AlarmZone));
+        fire_alarm_devices.set(count_of(UnitKind::FireAlarmDevice));
+        outputs.set(count_of(UnitKind::Output));
+    };
+
+    use_effect(move || {
+        sync_count_inputs(&unit_store.peek());
+        pending_plan.set(None);
+    });
+
+    // Replaces the unit store with one opened from a file or the server. Undo history, the simulation and the panel status
+    // belong to the previous project, whose unit ids the new one reuses.
+    let mut apply_loaded_store = move |name: String, store: UnitStore| {
+        sync_count_inputs(&store);
+        unit_store.set(store);
+        history.write().project_opened(&name);
+        simulation.write().clear();
+        panel_status.write().clear();
+        pending_plan.set(None);
+        selection.write().clear();
+    };
+
+    let mut undo = move || {
+        if history.write().undo(&mut unit_store.write()) {
+            sync_count_inputs(&unit_store.read());
+            selection.write().clear();
         }
-        for i in 0..az {
-            store.add_unit(&format!("Alarm Zone {}", i + 1));
+    };
+
+    let mut redo = move || {
+        if history.write().redo(&mut unit_store.write()) {
+            sync_count_inputs(&unit_store.read());
+            selection.write().clear();
         }
-        for i in 0..fad {
-            store.add_unit(&format!("Fire Alarm Device {}", i + 1));
+    };
+
+    // Ctrl+Z undoes and Ctrl+Shift+Z redoes (Cmd instead of Ctrl on macOS), except in form fields, which keep their own
+    // undo
+    let handle_shortcuts = move |evt: KeyboardEvent| {
+        let modifiers = evt.modifiers();
+        if !(modifiers.ctrl() || modifiers.meta()) || evt.code() != Code::KeyZ || editing_field() {
+            return;
         }
-        for i in 0..out {
-            store.add_unit(&format!("Output {}", i + 1));
+        evt.prevent_default();
+        if modifiers.shift() {
+            redo();
+        } else {
+            undo();
         }
+    };
 
-        unit_store.set(store);
-        selected_unit_index.set(None);
+    let save_project_file = move |_| {
+        download_file(
+            "project.json",
+            "application/json",
+            &save_project(&unit_store.read()),
+        );
+        project_status.set(Some(Ok("Project saved".to_string())));
+    };
+
+    let export_csv_file = move |_| {
+        download_file("units.csv", "text/csv", &export_csv(&unit_store.read()));
+        project_status.set(Some(Ok("Units exported".to_string())));
+    };
+
+    let download_report = move |format: ReportFormat| {
+        let report = Report::new("Device Schedule", &unit_store.read()).render(format);
+        download_file(
+            &format!("device-schedule.{}", format.extension()),
+            format.mime(),
+            &report,
+        );
+    };
+
+    let import_csv_file = move |evt: FormEvent| async move {
+        let Some(files) = evt.files() else {
+            return;
+        };
+        let Some(file_name) = files.files().into_iter().next() else {
+            return;
+        };
+        let Some(contents) = files.read_file_to_string(&file_name).await else {
+            project_status.set(Some(Err(format!("Could not read {}", file_name))));
+            return;
+        };
+        let import = CsvImport::parse(&unit_store.read(), &contents);
+        project_status.set(None);
+        pending_import.set(Some((file_name, import)));
+    };
+
+    let apply_import = move |_| {
+        let Some((file_name, import)) = pending_import.take() else {
+            return;
+        };
+        if history
+            .write()
+            .execute(&mut unit_store.write(), Command::ImportCsv { import })
+        {
+            sync_count_inputs(&unit_store.read());
+            selection.write().clear();
+            project_status.set(Some(Ok(format!("Imported {}", file_name))));
+        }
+    };
+
+    let open_project_file = move |evt: FormEvent| async move {
+        let Some(files) = evt.files() else {
+            return;
+        };
+        let Some(file_name) = files.files().into_iter().next() else {
+            return;
+        };
+        let Some(contents) = files.read_file_to_string(&file_name).await else {
+            project_status.set(Some(Err(format!("Could not read {}", file_name))));
+            return;
+        };
+
+        match load_project(&contents) {
+            Ok(store) => {
+                project_status.set(Some(Ok(format!(
+                    "Opened {} ({} units)",
+                    file_name,
+                    store.count()
+                ))));
+                apply_loaded_store(file_name, store);
+            }
+            Err(err) => {
+                project_status.set(Some(Err(format!("Could not open {}: {}", file_name, err))))
+            }
+        }
     };
 
     rsx! {
         document::Link { rel: "stylesheet", href: CONFIG_CSS }
-        div { id: "config",
-            h2 { "Configured Units" }
+        div { id: "config", tabindex: "0", onkeydown: handle_shortcuts,
+            h2 {
+                "Configured Units"
+                if let Some(panel) = panel_filter().panel().and_then(|panel| unit_store.read().network().get_panel(panel).cloned()) {
+                    " on {panel.name()}"
+                }
+            }
             table { id: "config-table",
                 tbody {
                     tr {
@@ -71,7 +294,7 @@ pub fn Config() -> Element {
                             input {
                                 r#type: "number",
                                 min: "0",
-                                max: "1000",
+                                max: "{MAX_UNITS_PER_KIND}",
                                 value: "{manual_call_points}",
                                 oninput: move |evt| manual_call_points.set(evt.value().clone())
                             }
@@ -83,7 +306,7 @@ pub fn Config() -> Element {
                             input {
                                 r#type: "number",
                                 min: "0",
-                                max: "1000",
+                                max: "{MAX_UNITS_PER_KIND}",
                                 value: "{detectors}",
                                 oninput: move |evt| detectors.set(evt.value().clone())
                             }
@@ -95,7 +318,7 @@ pub fn Config() -> Element {
                             input {
                                 r#type: "number",
                                 min: "0",
-                                max: "1000",
+                                max: "{MAX_UNITS_PER_KIND}",
                                 value: "{detection_zones}",
                                 oninput: move |evt| detection_zones.set(evt.value().clone())
                             }
@@ -107,7 +330,7 @@ pub fn Config() -> Element {
                             input {
                                 r#type: "number",
                                 min: "0",
-                                max: "1000",
+                                max: "{MAX_UNITS_PER_KIND}",
                                 value: "{alarm_zones}",
                                 oninput: move |evt| alarm_zones.set(evt.value().clone())
                             }
@@ -119,7 +342,7 @@ pub fn Config() -> Element {
                             input {
                                 r#type: "number",
                                 min: "0",
-                                max: "1000",
+                                max: "{MAX_UNITS_PER_KIND}",
                                 value: "{fire_alarm_devices}",
                                 oninput: move |evt| fire_alarm_devices.set(evt.value().clone())
                             }
@@ -131,7 +354,7 @@ pub fn Config() -> Element {
                             input {
                                 r#type: "number",
                                 min: "0",
-                                max: "1000",
+                                max: "{MAX_UNITS_PER_KIND}",
                                 value: "{outputs}",
                                 oninput: move |evt| outputs.set(evt.value().clone())
                             }
@@ -144,41 +367,112 @@ pub fn Config() -> Element {
                 button { id: "configure-btn", onclick: configure_units, "Configure" }
                 button { id: "clear-btn", onclick: clear_all, "Clear" }
             }
+            if let Some(message) = configure_error() {
+                p { id: "configure-error", "{message}" }
+            }
+            if let Some(plan) = pending_plan() {
+                ConfigurePreview {
+                    plan,
+                    removals_confirmed,
+                    on_apply: apply_plan,
+                    on_cancel: move |_| pending_plan.set(None),
+                }
+            }
+            PanelsPanel {}
+            LoopPanel {}
+            ValidationPanel { results: validation, on_select: move |id| selection.write().select_only(id) }
+            div { id: "history",
+                div { id: "history-buttons",
+                    button {
+                        id: "undo-btn",
+                        disabled: !history.read().can_undo(),
+                        title: "Undo (Ctrl+Z)",
+                        onclick: move |_| undo(),
+                        "Undo"
+                    }
+                    button {
+                        id: "redo-btn",
+                        disabled: !history.read().can_redo(),
+                        title: "Redo (Ctrl+Shift+Z)",
+                        onclick: move |_| redo(),
+                        "Redo"
+                    }
+                }
+                ul { id: "history-list",
+                    for label in history.read().redo_labels().collect::<Vec<_>>().into_iter().rev() {
+                        li { class: "history-redo", "{label}" }
+                    }
+                    for (index, label) in history.read().undo_labels().enumerate() {
+                        li { class: if index == 0 { "history-current" } else { "" }, "{label}" }
+                    }
+                }
+            }
+            div { id: "project-buttons",
+                button { id: "save-btn", onclick: save_project_file, "Save" }
+                label { id: "open-btn",
+                    "Open"
+                    input {
+                        r#type: "file",
+                        accept: ".json,application/json",
+                        onchange: open_project_file,
+                    }
+                }
+                button { id: "export-csv-btn", onclick: export_csv_file, "Export CSV" }
+                label { id: "import-csv-btn",
+                    "Import CSV"
+                    input {
+                        r#type: "file",
+                        accept: ".csv,text/csv",
+                        onchange: import_csv_file,
+                    }
+                }
+            }
+            div { id: "report-buttons",
+                for format in ReportFormat::ALL {
+                    button { onclick: move |_| download_report(format), "Report ({format.label()})" }
+                }
+            }
+            if let Some((file_name, import)) = pending_import() {
+                CsvImportPreview {
+                    file_name,
+                    import,
+                    on_apply: apply_import,
+                    on_cancel: move |_| pending_import.set(None),
+                }
+            }
+            ServerProjects {
+                on_open: move |(name, store): (String, UnitStore)| {
+                    project_status.set(None);
+                    apply_loaded_store(name, store);
+                }
+            }
+            match project_status() {
+                Some(Ok(message)) => rsx! { p { id: "project-status", "{message}" } },
+                Some(Err(message)) => rsx! { p { id: "project-status", class: "error", "{message}" } },
+                None => rsx! {},
+            }
             div {
                 style: "display: flex; justify-content: center; gap: 20px; margin-top: 20px;",
                 // Left side - Unit List
-                div {
-                    style: "flex: 1; max-width: 600px;",
-                    h3 { style: "text-align: center;", "Units ({unit_store().count()})" }
-                    ul {
-                        style: "max-height: 300px; overflow-y: auto; border: 1px solid white; padding: 15px; list-style: none; margin: 0;",
-                        for (index, unit) in unit_store().get_all_units().iter().skip(1).enumerate() {
-                            li {
-                                style: if selected_unit_index() == Some(index) {
-                                    "background-color: rgba(255, 255, 255, 0.2); padding: 8px; cursor: pointer; margin: 3px 0;"
-                                } else {
-                                    "padding: 8px; cursor: pointer; margin: 3px 0;"
-                                },
-                                onclick: move |_| selected_unit_index.set(Some(index)),
-                                "{unit.name()}"
-                            }
-                        }
-                    }
-                }
+                UnitList { selection, validation }
                 // Right side - Selected Unit
                 div {
                     style: "flex: 1; max-width: 600px;",
-                    h3 { style: "text-align: center;", "Selected Unit" }
+                    if selection.read().len() > 1 {
+                        h3 { style: "text-align: center;", "{selection.read().len()} Units Selected" }
+                    } else {
+                        h3 { style: "text-align: center;", "Selected Unit" }
+                    }
                     div {
                         style: "min-height: 300px; border: 1px solid white; padding: 15px; background-color: rgba(255, 255, 255, 0.05);",
-                        if let Some(selected_idx) = selected_unit_index() {
-                            if let Some(selected_unit) = unit_store().get_all_units().iter().skip(1).nth(selected_idx) {
-                                div {
-                                    h4 { "Unit Details:" }
-                                    p { "Name: {selected_unit.name()}" }
-                                    // Add more unit details here as needed
-                                }
+                        if let Some(id) = selection.read().single().filter(|id| unit_store.read().get_unit(*id).is_some()) {
+                            UnitDetails {
+                                key: "{id}",
+                                unit_id: id,
+                                on_deleted: move |_| selection.write().clear(),
                             }
+                        } else if selection.read().len() > 1 {
+                            BulkEditPanel { selection }
                         } else {
                             div {
                                 style: "color: rgba(255, 255, 255, 0.5); text-align: center; padding-top: 50px;",
@@ -191,3 +485,102 @@ pub fn Config() -> Element {
         }
     }
 }
+
+/// Shows what a Configure will add and remove before it is applied.
+#[component]
+fn ConfigurePreview(
+    plan: ConfigurePlan,
+    removals_confirmed: Signal<bool>,
+    on_apply: EventHandler<MouseEvent>,
+    on_cancel: EventHandler<MouseEvent>,
+) -> Element {
+    let unit_store = use_context::<Signal<UnitStore>>();
+    let store = unit_store.read();
+    let additions_of = |kind: UnitKind| {
+        plan.additions()
+            .iter()
+            .filter(|(added, _)| *added == kind)
+            .count()
+    };
+    let removed_names: Vec<String> = plan
+        .removals()
+        .iter()
+        .filter_map(|id| store.get_unit(*id).map(|unit| unit.name().to_string()))
+        .collect();
+    let lifted_names: Vec<String> = plan
+        .lifted(&store)
+        .iter()
+        .filter_map(|id| store.get_unit(*id).map(|unit| unit.name().to_string()))
+        .collect();
+    let needs_confirmation = !removed_names.is_empty();
+    let panel_name = plan
+        .panel()
+        .and_then(|number| store.network().get_panel(number))
+        .map(|panel| panel.name().to_string());
+
+    rsx! {
+        div { id: "configure-preview",
+            if let Some(panel_name) = panel_name {
+                h4 { "Configure preview for {panel_name}" }
+            } else {
+                h4 { "Configure preview" }
+            }
+            if plan.is_empty() {
+                p { "Nothing to change" }
+            }
+            ul {
+                for kind in UnitKind::CONFIGURABLE {
+                    if additions_of(kind) > 0 {
+                        li { class: "preview-add", "+{additions_of(kind)} {kind.plural_label()}" }
+                    }
+                }
+            }
+            if needs_confirmation {
+                p { class: "preview-remove", "{removed_names.len()} units will be removed:" }
+                ul { id: "preview-removals",
+                    for name in removed_names.iter() {
+                        li { class: "preview-remove", "{name}" }
+                    }
+                }
+                if !lifted_names.is_empty() {
+                    p { "{lifted_names.len()} units below them are kept and move up a level:" }
+                    ul { id: "preview-lifted",
+                        for name in lifted_names.iter() {
+                            li { "{name}" }
+                        }
+                    }
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: removals_confirmed(),
+                        onchange: move |evt| removals_confirmed.set(evt.checked()),
+                    }
+                    " Remove these units"
+                }
+            }
+            div { id: "configure-preview-buttons",
+                button {
+                    id: "apply-configure-btn",
+                    disabled: plan.is_empty() || (needs_confirmation && !removals_confirmed()),
+                    onclick: move |evt| on_apply.call(evt),
+                    "Apply"
+                }
+                button { id: "cancel-configure-btn", onclick: move |evt| on_cancel.call(evt), "Cancel" }
+            }
+        }
+    }
+}
+
+// Whether the focused element, which key events come from, is an input, textarea or select
+fn editing_field() -> bool {
+    #[cfg(target_arch = "wasm32")]
+    let tag = js_sys::Reflect::get(&js_sys::global(), &"document".into())
+        .and_then(|document| js_sys::Reflect::get(&document, &"activeElement".into()))
+        .and_then(|element| js_sys::Reflect::get(&element, &"tagName".into()))
+        .ok()
+        .and_then(|tag| tag.as_string());
+    #[cfg(not(target_arch = "wasm32"))]
+    let tag: Option<String> = None;
+    matches!(tag.as_deref(), Some("INPUT" | "TEXTAREA" | "SELECT"))
+}
@@ -0,0 +1,60 @@
+use crate::model::{CsvImport, UnitStore};
+use dioxus::prelude::*;
+
+/// Shows what a CSV import will create and change, and the rows that cannot be imported, before it is applied.
+///
+/// Nothing can be applied while any row has an error, so a file is imported completely or not at all.
+#[component]
+pub fn CsvImportPreview(
+    file_name: String,
+    import: CsvImport,
+    on_apply: EventHandler<MouseEvent>,
+    on_cancel: EventHandler<MouseEvent>,
+) -> Element {
+    let unit_store = use_context::<Signal<UnitStore>>();
+    let store = unit_store.read();
+
+    rsx! {
+        div { id: "csv-preview",
+            h4 { "Import {file_name}" }
+            p {
+                span { class: "preview-add", "{import.creations()} new" }
+                ", {import.updates()} changed, {import.unchanged()} unchanged"
+            }
+            if !import.errors().is_empty() {
+                p { class: "preview-remove",
+                    "{import.errors().len()} rows cannot be imported. Fix them and import the file again."
+                }
+                ul { id: "csv-errors",
+                    for err in import.errors() {
+                        li { class: "preview-remove", "{err}" }
+                    }
+                }
+            }
+            ul { id: "csv-changes",
+                for row in import.rows() {
+                    match row.id.and_then(|id| store.get_unit(id)) {
+                        Some(unit) if unit.name() != row.name => rsx! {
+                            li { "Line {row.line}: {unit.name()} → {row.name}" }
+                        },
+                        Some(unit) => rsx! {
+                            li { "Line {row.line}: update {unit.name()}" }
+                        },
+                        None => rsx! {
+                            li { class: "preview-add", "Line {row.line}: add {row.kind} {row.name}" }
+                        },
+                    }
+                }
+            }
+            div { id: "csv-preview-buttons",
+                button {
+                    id: "apply-import-btn",
+                    disabled: !import.can_apply(),
+                    onclick: move |evt| on_apply.call(evt),
+                    "Apply"
+                }
+                button { id: "cancel-import-btn", onclick: move |evt| on_cancel.call(evt), "Cancel" }
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+
+use crate::components::{download_file, VirtualList};
+use crate::model::{log_to_csv, log_to_json, EventLog, LogCategory, LogEntry, LogFilter};
+use dioxus::prelude::*;
+
+const LOG_CSS: Asset = asset!("/assets/styling/log.css");
+
+/// Height in pixels of one row of the log; `.log-row` in log.css must match.
+const ROW_HEIGHT: f64 = 28.0;
+/// Height in pixels of the scrolling part of the log.
+const LOG_HEIGHT: f64 = 500.0;
+
+/// The event log page rendered for the `/log` route
+///
+/// Entries are shown newest first and can be filtered by type, unit and time. The entries shown can be exported as CSV
+/// or JSON. Entries are saved on the server, and the saved ones shown, while someone with access level 2 is logged in.
+#[component]
+pub fn Log() -> Element {
+    let event_log = use_context::<Signal<EventLog>>();
+    let mut filter = use_signal(LogFilter::default);
+    // The time inputs as typed, so a half-typed time does not clear the field
+    let mut from_text = use_signal(String::new);
+    let mut until_text = use_signal(String::new);
+
+    // Positions in the log of the matching entries, newest first
+    let rows = use_memo(move || {
+        let filter = filter.read();
+        let log = event_log.read();
+        let mut rows: Vec<usize> = log
+            .entries()
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| filter.matches(entry))
+            .map(|(index, _)| index)
+            .collect();
+        rows.reverse();
+        rows
+    });
+    // Every unit that appears in the log, by its most recent name
+    let units = use_memo(move || {
+        let mut units: BTreeMap<u64, String> = BTreeMap::new();
+        for entry in event_log.read().entries() {
+            if let (Some(id), Some(name)) = (entry.unit, &entry.unit_name) {
+                units.insert(id, name.clone());
+            }
+        }
+        units
+    });
+
+    let export = move |as_json: bool| {
+        let log = event_log.read();
+        let filter = filter.read();
+        let entries: Vec<&LogEntry> = log.filter(&filter).collect();
+        if as_json {
+            download_file("event_log.json", "application/json", &log_to_json(entries));
+        } else {
+            download_file("event_log.csv", "text/csv", &log_to_csv(entries));
+        }
+    };
+
+    let log = event_log.read();
+    let unsaved = log.unsynced().len();
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: LOG_CSS }
+        div { id: "log",
+            h2 { "Event Log" }
+            p { id: "log-operator", "New entries are recorded for {log.operator()}" }
+            div { id: "log-filters",
+                select {
+                    onchange: move |evt| {
+                        filter.write().category = LogCategory::ALL
+                            .into_iter()
+                            .find(|category| category.label() == evt.value());
+                    },
+                    option { value: "", "All types" }
+                    for category in LogCategory::ALL {
+                        option { value: "{category.label()}", "{category}" }
+                    }
+                }
+                select {
+                    onchange: move |evt| filter.write().unit = evt.value().parse().ok(),
+                    option { value: "", "All units" }
+                    for (id, name) in units.read().iter() {
+                        option { value: "{id}", "{name}" }
+                    }
+                }
+                label { "From " }
+                input {
+                    r#type: "datetime-local",
+                    value: "{from_text}",
+                    class: if filter.read().from.is_none() && !from_text.read().is_empty() { "invalid" } else { "" },
+                    oninput: move |evt| {
+                        filter.write().from = evt.value().parse().ok();
+                        from_text.set(evt.value());
+                    },
+                }
+                label { " until " }
+                input {
+                    r#type: "datetime-local",
+                    value: "{until_text}",
+                    class: if filter.read().until.is_none() && !until_text.read().is_empty() { "invalid" } else { "" },
+                    oninput: move |evt| {
+                        filter.write().until = evt.value().parse().ok();
+                        until_text.set(evt.value());
+                    },
+                }
+            }
+            div { id: "log-actions",
+                span { "{rows.read().len()} of {log.len()} entries, times in UTC" }
+                if unsaved > 0 || !log.is_restored() {
+                    span { class: "log-unsaved",
+                        if log.is_restored() {
+                            " ({unsaved} not yet saved on the server)"
+                        } else {
+                            " (not saved on the server until someone with access level 2 logs in)"
+                        }
+                    }
+                }
+                button { onclick: move |_| export(false), "Export CSV" }
+                button { onclick: move |_| export(true), "Export JSON" }
+            }
+            div { class: "log-row log-header",
+                span { "Time" }
+                span { "Operator" }
+                span { "Type" }
+                span { "Unit" }
+                span { "Event" }
+            }
+            VirtualList {
+                row_count: rows.read().len(),
+                row_height: ROW_HEIGHT,
+                height: LOG_HEIGHT,
+                render_row: move |index: usize| {
+                    let position = rows.read()[index];
+                    let log = event_log.read();
+                    let Some(entry) = log.entries().get(position) else {
+                        return rsx! {};
+                    };
+                    let unit = match (entry.unit, &entry.unit_name) {
+                        (_, Some(name)) => name.clone(),
+                        (Some(id), None) => format!("Unit {}", id),
+                        (None, None) => String::new(),
+                    };
+                    let category = entry.event.category();
+                    rsx! {
+                        div {
+                            key: "{entry.sequence}",
+                            class: "log-row log-{category.label().to_lowercase()}",
+                            title: "{entry}",
+                            span { "{entry.timestamp}" }
+                            span { "{entry.operator}" }
+                            span { "{category}" }
+                            span { "{unit}" }
+                            span { "{entry.event}" }
+                        }
+                    }
+                },
+            }
+            if log.is_empty() {
+                p { class: "log-empty", "Nothing has been logged yet" }
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use crate::model::{Command, History, UnitStore, DEFAULT_LOOP_CAPACITY, MAX_LOOP_ADDRESS};
+use dioxus::prelude::*;
+
+/// The Loops section of the Config page.
+///
+/// Lists every loop with its utilisation and capacity, and lets the user add and remove loops, change a capacity and
+/// re-address a loop so its devices use addresses 1 to n without gaps. All changes go through the shared [`History`].
+#[component]
+pub fn LoopPanel() -> Element {
+    let mut unit_store = use_context::<Signal<UnitStore>>();
+    let mut history = use_context::<Signal<History>>();
+    // Why the last change was refused, if it was
+    let mut feedback = use_signal(|| None::<String>);
+
+    let mut execute = move |command: Command| {
+        history.write().execute(&mut unit_store.write(), command);
+    };
+
+    let store = unit_store.read();
+    let utilisation = store.loop_utilisation();
+    let collisions = store.address_collisions();
+
+    rsx! {
+        div { id: "loops",
+            h3 { "Loops" }
+            if utilisation.is_empty() {
+                p { class: "loops-empty", "No loops yet. Configure adds one when devices need addresses." }
+            }
+            table { id: "loop-table",
+                tbody {
+                    for usage in utilisation {
+                        tr { key: "{usage.loop_number}",
+                            td { "Loop {usage.loop_number}" }
+                            td {
+                                div { class: "loop-bar",
+                                    div {
+                                        class: if usage.percent() >= 100 { "loop-bar-fill full" } else { "loop-bar-fill" },
+                                        style: "width: {usage.percent()}%;",
+                                    }
+                                }
+                            }
+                            td { "{usage.used} / {usage.capacity}" }
+                            td {
+                                input {
+                                    r#type: "number",
+                                    min: "1",
+                                    max: "{MAX_LOOP_ADDRESS}",
+                                    value: "{usage.capacity}",
+                                    title: "Capacity",
+                                    onchange: move |evt| {
+                                        let number = usage.loop_number;
+                                        let Ok(capacity) = evt.value().parse::<u16>() else {
+                                            feedback.set(Some(format!("Loop {}: capacity must be a whole number", number)));
+                                            return;
+                                        };
+                                        let check = unit_store.read().check_loop_capacity(number, capacity);
+                                        match check {
+                                            Ok(()) => {
+                                                execute(Command::SetLoopCapacity { number, capacity });
+                                                feedback.set(None);
+                                            }
+                                            Err(err) => feedback.set(Some(format!("Loop {}: {}", number, err))),
+                                        }
+                                    },
+                                }
+                            }
+                            td {
+                                button {
+                                    title: "Renumber devices from 1 without gaps",
+                                    disabled: usage.used == 0,
+                                    onclick: move |_| execute(Command::ReaddressLoop { number: usage.loop_number }),
+                                    "Re-address"
+                                }
+                                button {
+                                    class: "delete",
+                                    title: "Remove the loop; its devices lose their address",
+                                    onclick: move |_| execute(Command::RemoveLoop { number: usage.loop_number }),
+                                    "Remove"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            button {
+                id: "add-loop-btn",
+                onclick: move |_| execute(Command::AddLoop { capacity: DEFAULT_LOOP_CAPACITY }),
+                "Add Loop"
+            }
+            if let Some(message) = feedback() {
+                p { class: "unit-error", "{message}" }
+            }
+            for (address, ids) in collisions {
+                p { class: "unit-error",
+                    "Address {address} is used by {ids.len()} devices"
+                }
+            }
+        }
+    }
+}
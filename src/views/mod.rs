@@ -2,7 +2,9 @@
 //! enum will render one of these components.
 //!
 //!
-//! The [`Config`] and [`Blog`] components will be rendered when the current route is [`Route::Config`] or [`Route::Blog`] respectively.
+//! The [`Config`], [`CauseEffect`], [`Simulator`], [`Status`], [`Log`] and [`Blog`] components will be rendered when the
+//! current route is [`Route::Config`], [`Route::CauseEffect`], [`Route::Simulator`], [`Route::Status`], [`Route::Log`]
+//! or [`Route::Blog`] respectively.
 //!
 //!
 //! The [`Navbar`] component will be rendered on all pages of our app since every page is under the layout. The layout defines
@@ -10,6 +12,31 @@
 
 mod config;
 pub use config::Config;
+// The unit list and editor shown on the Config page
+mod unit_details;
+mod unit_list;
+// Actions on several selected units at once
+mod bulk_edit;
+// Loop capacities and utilisation shown on the Config page
+mod loops;
+// Panels, their unit counts and the network between them, shown on the Config page
+mod panels;
+// Configuration findings shown on the Config page
+mod validation;
+// The review step of a CSV import on the Config page
+mod csv_import;
+
+mod cause_effect;
+pub use cause_effect::CauseEffect;
+
+mod simulator;
+pub use simulator::Simulator;
+
+mod status;
+pub use status::Status;
+
+mod log;
+pub use log::Log;
 
 mod blog;
 pub use blog::Blog;
@@ -1,3 +1,6 @@
+use crate::api::{logout, Session};
+use crate::components::LoginForm;
+use crate::model::{EventLog, PanelFilter, PanelStatus, UnitStore, DEFAULT_OPERATOR};
 use crate::Route;
 use dioxus::prelude::*;
 
@@ -6,10 +9,36 @@ const NAVBAR_CSS: Asset = asset!("/assets/styling/navbar.css");
 /// The Navbar component that will be rendered on all pages of our app since every page is under the layout.
 ///
 ///
-/// This layout component wraps the UI of [Route::Config] and [Route::Blog] in a common navbar. The contents of the Config and Blog
-/// routes will be rendered under the outlet inside this component
+/// This layout component wraps the UI of [Route::Config], [Route::CauseEffect], [Route::Simulator], [Route::Status], [Route::Log] and
+/// [Route::Blog] in a common navbar. The contents of those routes will be rendered under the outlet inside this component
+///
+/// The navbar also counts the units in fire and in fault, so an alarm is seen from every page, and shows who is logged in
+/// with which access level. On sites with several panels it offers a panel selector; the counters and the pages then only
+/// cover the picked panel.
 #[component]
 pub fn Navbar() -> Element {
+    let unit_store = use_context::<Signal<UnitStore>>();
+    let panel_status = use_context::<Signal<PanelStatus>>();
+    let mut panel_filter = use_context::<Signal<PanelFilter>>();
+    let counts = use_memo(move || {
+        panel_status
+            .read()
+            .counts_for(&unit_store.read(), panel_filter())
+    });
+    let (fires, faults) = (counts.read().fires(), counts.read().faults());
+    let mut session = use_context::<Signal<Option<Session>>>();
+    let mut event_log = use_context::<Signal<EventLog>>();
+    let mut show_login = use_signal(|| false);
+
+    let log_out = move |_| async move {
+        let Some(ended) = session.take() else {
+            return;
+        };
+        event_log.write().set_operator(DEFAULT_OPERATOR);
+        // The session expires on the server anyway if this does not get through
+        let _ = logout(ended.token).await;
+    };
+
     rsx! {
         document::Link { rel: "stylesheet", href: NAVBAR_CSS }
 
@@ -19,14 +48,77 @@ pub fn Navbar() -> Element {
                 to: Route::Config {},
                 "Config"
             }
+            Link {
+                to: Route::CauseEffect {},
+                "Cause & Effect"
+            }
+            Link {
+                to: Route::Simulator {},
+                "Simulator"
+            }
+            Link {
+                to: Route::Status {},
+                "Status"
+            }
+            Link {
+                to: Route::Log {},
+                "Log"
+            }
             Link {
                 to: Route::Blog { id: 1 },
                 "Blog"
             }
+            if unit_store.read().network().panels().len() > 1 {
+                select {
+                    id: "navbar-panel",
+                    onchange: move |evt| {
+                        panel_filter.set(match evt.value().parse() {
+                            Ok(number) => PanelFilter::Panel(number),
+                            Err(_) => PanelFilter::All,
+                        });
+                    },
+                    option { value: "", selected: panel_filter() == PanelFilter::All, "All panels" }
+                    for panel in unit_store.read().network().panels() {
+                        option {
+                            value: "{panel.number()}",
+                            selected: panel_filter() == PanelFilter::Panel(panel.number()),
+                            "{panel}"
+                        }
+                    }
+                }
+            }
+            div {
+                id: "navbar-counters",
+                Link {
+                    to: Route::Status {},
+                    class: if fires > 0 { "navbar-counter fire" } else { "navbar-counter" },
+                    "Fire {fires}"
+                }
+                Link {
+                    to: Route::Status {},
+                    class: if faults > 0 { "navbar-counter fault" } else { "navbar-counter" },
+                    "Fault {faults}"
+                }
+            }
+            div {
+                id: "navbar-session",
+                if let Some(current) = session() {
+                    span { "{current.user}, {current.level}" }
+                    button { onclick: log_out, "Log out" }
+                } else {
+                    button { onclick: move |_| show_login.toggle(), "Log in" }
+                }
+            }
+        }
+        if show_login() && session.read().is_none() {
+            div {
+                id: "navbar-login",
+                LoginForm { on_login: move |_| show_login.set(false) }
+            }
         }
 
-        // The `Outlet` component is used to render the next component inside the layout. In this case, it will render either
-        // the [`Config`] or [`Blog`] component depending on the current route.
+        // The `Outlet` component is used to render the next component inside the layout. In this case, it will render the
+        // [`Config`], [`CauseEffect`], [`Simulator`], [`Status`], [`Log`] or [`Blog`] component depending on the current route.
         Outlet::<Route> {}
     }
 }
@@ -0,0 +1,191 @@
+use crate::model::{Command, History, NetworkError, UnitKind, UnitStore};
+use dioxus::prelude::*;
+
+/// The Panels section of the Config page.
+///
+/// Lists every panel with how many units of each kind it owns, and lets the user add, rename and remove panels and link
+/// them into a network. Cause-and-effect rules only reach panels the network connects. All changes go through the shared
+/// [`History`].
+#[component]
+pub fn PanelsPanel() -> Element {
+    let mut unit_store = use_context::<Signal<UnitStore>>();
+    let mut history = use_context::<Signal<History>>();
+    let mut new_name = use_signal(String::new);
+    let mut link_from = use_signal(|| None::<u16>);
+    let mut link_to = use_signal(|| None::<u16>);
+    // Why the last change was refused, if it was
+    let mut feedback = use_signal(|| None::<String>);
+
+    // Runs a command whose check passed, or shows why it was refused instead of silently doing nothing
+    let mut execute = move |command: Command, check: Result<(), String>| match check {
+        Ok(()) => {
+            history.write().execute(&mut unit_store.write(), command);
+            feedback.set(None);
+        }
+        Err(err) => feedback.set(Some(err)),
+    };
+
+    let add_panel = move |_| {
+        let name = new_name();
+        let check = unit_store
+            .read()
+            .clone()
+            .add_panel(&name)
+            .map(|_| ())
+            .map_err(|err| err.to_string());
+        let added = check.is_ok();
+        execute(Command::AddPanel { name }, check);
+        if added {
+            new_name.set(String::new());
+        }
+    };
+
+    let add_link = move |_| {
+        let (Some(a), Some(b)) = (link_from(), link_to()) else {
+            feedback.set(Some("Pick two panels to link".to_string()));
+            return;
+        };
+        let check = unit_store
+            .read()
+            .clone()
+            .add_network_link(a, b)
+            .map_err(|err| err.to_string());
+        execute(Command::AddNetworkLink { a, b }, check);
+    };
+
+    let store = unit_store.read();
+    let network = store.network();
+    let panels: Vec<(u16, String)> = network
+        .panels()
+        .iter()
+        .map(|panel| (panel.number(), panel.name().to_string()))
+        .collect();
+    let panel_name = |number: u16| {
+        network
+            .get_panel(number)
+            .map(|panel| panel.name().to_string())
+            .unwrap_or_else(|| format!("Panel {}", number))
+    };
+
+    rsx! {
+        div { id: "panels",
+            h3 { "Panels" }
+            table { id: "panel-table",
+                thead {
+                    tr {
+                        th { "#" }
+                        th { "Name" }
+                        for kind in UnitKind::CONFIGURABLE {
+                            th { title: "{kind.plural_label()}", "{abbreviation(kind)}" }
+                        }
+                        th { "Total" }
+                        th {}
+                    }
+                }
+                tbody {
+                    for (number, name) in panels.clone() {
+                        tr { key: "{number}",
+                            td { "P{number}" }
+                            td {
+                                input {
+                                    value: "{name}",
+                                    onchange: move |evt| {
+                                        let name = evt.value();
+                                        let check = unit_store
+                                            .read()
+                                            .network()
+                                            .check_panel_name(number, &name)
+                                            .map_err(|err| err.to_string());
+                                        execute(Command::RenamePanel { number, name }, check);
+                                    },
+                                }
+                            }
+                            for (_, count) in store.counts_on_panel(number) {
+                                td { class: "panel-count", "{count}" }
+                            }
+                            td { class: "panel-count", "{store.units_on_panel(number).count()}" }
+                            td {
+                                button {
+                                    class: "delete",
+                                    title: "Remove the panel; it must not own any units",
+                                    onclick: move |_| {
+                                        let check = unit_store
+                                            .read()
+                                            .check_remove_panel(number)
+                                            .map_err(|err| err.to_string());
+                                        execute(Command::RemovePanel { number }, check);
+                                    },
+                                    "Remove"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            div { class: "panel-add",
+                input {
+                    placeholder: "New panel name",
+                    value: "{new_name}",
+                    oninput: move |evt| new_name.set(evt.value()),
+                }
+                button { onclick: add_panel, "Add Panel" }
+            }
+            h4 { "Network" }
+            if network.links().is_empty() {
+                p { class: "loops-empty", "No network links. Rules only reach units on the panel of their inputs." }
+            }
+            ul { id: "network-links",
+                for link in network.links().to_vec() {
+                    li { key: "{link.a}-{link.b}",
+                        "{panel_name(link.a)} – {panel_name(link.b)} "
+                        button {
+                            class: "delete",
+                            onclick: move |_| {
+                                let check = if unit_store.read().network().is_linked(link.a, link.b) {
+                                    Ok(())
+                                } else {
+                                    Err(NetworkError::LinkNotFound { a: link.a, b: link.b }.to_string())
+                                };
+                                execute(Command::RemoveNetworkLink { a: link.a, b: link.b }, check);
+                            },
+                            "Unlink"
+                        }
+                    }
+                }
+            }
+            if panels.len() > 1 {
+                div { class: "panel-add",
+                    select { onchange: move |evt| link_from.set(evt.value().parse().ok()),
+                        option { value: "", selected: link_from().is_none(), "Link panel…" }
+                        for (number, name) in panels.clone() {
+                            option { value: "{number}", selected: link_from() == Some(number), "{name}" }
+                        }
+                    }
+                    select { onchange: move |evt| link_to.set(evt.value().parse().ok()),
+                        option { value: "", selected: link_to().is_none(), "with panel…" }
+                        for (number, name) in panels {
+                            option { value: "{number}", selected: link_to() == Some(number), "{name}" }
+                        }
+                    }
+                    button { onclick: add_link, "Link" }
+                }
+            }
+            if let Some(message) = feedback() {
+                p { class: "unit-error", "{message}" }
+            }
+        }
+    }
+}
+
+// Short column headers so the counts of all kinds fit in one row
+fn abbreviation(kind: UnitKind) -> &'static str {
+    match kind {
+        UnitKind::Root => "",
+        UnitKind::ManualCallPoint => "MCP",
+        UnitKind::Detector => "Det",
+        UnitKind::DetectionZone => "DZ",
+        UnitKind::AlarmZone => "AZ",
+        UnitKind::FireAlarmDevice => "FAD",
+        UnitKind::Output => "Out",
+    }
+}
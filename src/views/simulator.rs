@@ -0,0 +1,140 @@
+use crate::components::AccessGuard;
+use crate::model::{AccessLevel, Simulation, UnitKind, UnitStore};
+use dioxus::prelude::*;
+
+const SIMULATOR_CSS: Asset = asset!("/assets/styling/simulator.css");
+
+/// The simulator page rendered for the `/simulator` route
+///
+/// Detectors and manual call points can be triggered and reset to check which alarm zones, fire alarm devices and outputs
+/// the configuration activates. Simulated alarms drive the panel status, so the page needs access level 2.
+#[component]
+pub fn Simulator() -> Element {
+    rsx! {
+        AccessGuard { required: AccessLevel::Operator, SimulatorPanel {} }
+    }
+}
+
+/// The simulator behind the access guard of [`Simulator`]
+#[component]
+fn SimulatorPanel() -> Element {
+    let unit_store = use_context::<Signal<UnitStore>>();
+    // Shared so the Status page shows what is in alarm
+    let mut simulation = use_context::<Signal<Simulation>>();
+
+    let store = unit_store.read();
+    let sim = simulation();
+    let state = sim.state();
+    let unit_name = |id: &u64| {
+        store
+            .get_unit(*id)
+            .map(|unit| unit.name().to_string())
+            .unwrap_or_default()
+    };
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: SIMULATOR_CSS }
+        div { id: "simulator",
+            h2 { "Simulator" }
+            div { id: "simulator-buttons",
+                button {
+                    id: "reset-all-btn",
+                    onclick: move |_| simulation.write().reset_all(&unit_store.read()),
+                    "Reset All"
+                }
+                button {
+                    id: "clear-log-btn",
+                    onclick: move |_| simulation.write().clear(),
+                    "Clear"
+                }
+            }
+            div { id: "simulator-panels",
+                // Left side - inputs that can be triggered
+                div { class: "simulator-panel",
+                    h3 { "Inputs" }
+                    ul {
+                        for unit in store
+                            .get_all_units()
+                            .iter()
+                            .filter(|unit| matches!(unit.kind(), UnitKind::Detector | UnitKind::ManualCallPoint))
+                        {
+                            SimulatorInput {
+                                key: "{unit.id()}",
+                                unit_store,
+                                simulation,
+                                id: unit.id(),
+                                name: unit.name().to_string(),
+                            }
+                        }
+                    }
+                }
+                // Middle - what the configuration activated
+                div { class: "simulator-panel",
+                    h3 { "Activated" }
+                    h4 { "Detection Zones ({state.detection_zones.len()})" }
+                    ul {
+                        for id in state.detection_zones.iter() {
+                            li { class: "simulator-active", "{unit_name(id)}" }
+                        }
+                    }
+                    h4 { "Alarm Zones ({state.alarm_zones.len()})" }
+                    ul {
+                        for id in state.alarm_zones.iter() {
+                            li { class: "simulator-active", "{unit_name(id)}" }
+                        }
+                    }
+                    h4 { "Fire Alarm Devices ({state.fire_alarm_devices.len()})" }
+                    ul {
+                        for id in state.fire_alarm_devices.iter() {
+                            li { class: "simulator-active", "{unit_name(id)}" }
+                        }
+                    }
+                    h4 { "Outputs ({state.outputs.len()})" }
+                    ul {
+                        for id in state.outputs.iter() {
+                            li { class: "simulator-active", "{unit_name(id)}" }
+                        }
+                    }
+                }
+                // Right side - timeline
+                div { class: "simulator-panel",
+                    h3 { "Timeline" }
+                    ul { id: "simulator-timeline",
+                        for entry in sim.timeline().iter().rev() {
+                            li { "{entry}" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A detector or manual call point with a button that toggles it in and out of alarm.
+#[component]
+fn SimulatorInput(
+    unit_store: Signal<UnitStore>,
+    simulation: Signal<Simulation>,
+    id: u64,
+    name: String,
+) -> Element {
+    let triggered = simulation.read().is_triggered(id);
+
+    rsx! {
+        li {
+            class: if triggered { "simulator-input simulator-active" } else { "simulator-input" },
+            span { "{name}" }
+            button {
+                onclick: move |_| {
+                    let store = unit_store.read();
+                    if triggered {
+                        simulation.write().reset(&store, id);
+                    } else {
+                        let _ = simulation.write().trigger(&store, id);
+                    }
+                },
+                if triggered { "Reset" } else { "Trigger" }
+            }
+        }
+    }
+}
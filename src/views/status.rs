@@ -0,0 +1,168 @@
+use crate::api::{access_level, Session};
+use crate::model::{
+    PanelFilter, PanelStatus, Simulation, StateError, UnitKind, UnitState, UnitStore,
+};
+use dioxus::prelude::*;
+
+const STATUS_CSS: Asset = asset!("/assets/styling/status.css");
+
+/// The status page rendered for the `/status` route
+///
+/// Every unit is shown as a tile coloured by its runtime state, grouped by kind. States follow the Simulator and any
+/// updates fed into the shared [`PanelStatus`]. The panel controls acknowledge, silence and reset, and clicking a tile
+/// offers the events its state machine allows, so faults, disablements and tests can be raised by hand. Anyone may look;
+/// operating the panel needs access level 2. When a panel is picked in the navbar, only its units are shown.
+#[component]
+pub fn Status() -> Element {
+    let unit_store = use_context::<Signal<UnitStore>>();
+    let mut panel_status = use_context::<Signal<PanelStatus>>();
+    let mut simulation = use_context::<Signal<Simulation>>();
+    let session = use_context::<Signal<Option<Session>>>();
+    let panel_filter = use_context::<Signal<PanelFilter>>();
+    let mut selected = use_signal(|| None::<u64>);
+    // Why the last operation was refused, if it was
+    let mut feedback = use_signal(|| None::<String>);
+
+    let mut report = move |result: Result<(), StateError>| {
+        feedback.set(result.err().map(|err| err.to_string()));
+    };
+
+    let store = unit_store.read();
+    let status = panel_status.read();
+    let filter = panel_filter();
+    let counts = status.counts_for(&store, filter);
+    let shown = |id: u64| {
+        filter
+            .panel()
+            .is_none_or(|panel| store.panel_of(id) == Some(panel))
+    };
+    let selected_unit = selected().and_then(|id| store.get_unit(id));
+    let can_operate = access_level(&session.read()).can_operate();
+
+    rsx! {
+        document::Link { rel: "stylesheet", href: STATUS_CSS }
+        div { id: "status",
+            h2 { "Panel Status" }
+            div { id: "status-panel",
+                button {
+                    disabled: !can_operate || status.unacknowledged().is_empty(),
+                    onclick: move |_| report(panel_status.write().acknowledge()),
+                    "Acknowledge ({status.unacknowledged().len()})"
+                }
+                button {
+                    disabled: !can_operate || status.is_silenced() || counts.fires() == 0,
+                    onclick: move |_| report(panel_status.write().silence()),
+                    if status.is_silenced() { "Silenced" } else { "Silence" }
+                }
+                button {
+                    class: "delete",
+                    disabled: !can_operate,
+                    onclick: move |_| {
+                        // The simulated inputs are reset too, or they would raise the alarm again
+                        simulation.write().reset_all(&unit_store.read());
+                        report(panel_status.write().reset());
+                    },
+                    "Reset"
+                }
+            }
+            if !can_operate {
+                p { class: "status-hint", "Log in with access level 2 to acknowledge, silence and reset." }
+            }
+            if let Some(message) = feedback() {
+                p { class: "unit-error", "{message}" }
+            }
+            div { id: "status-legend",
+                for state in UnitState::ALL {
+                    span { class: "status-tile {state_class(state)}", "{state} ({counts.get(state)})" }
+                }
+            }
+            if let Some(unit) = selected_unit {
+                div { id: "status-actions",
+                    span { "{unit.name()}: {status.state(unit.id())} " }
+                    for event in status.events_for(&store, unit.id()) {
+                        button {
+                            disabled: !can_operate,
+                            onclick: {
+                                let id = unit.id();
+                                move |_| {
+                                    let result = panel_status.write().handle(&unit_store.read(), id, event);
+                                    report(result.map(|_| ()));
+                                }
+                            },
+                            "{event.label()}"
+                        }
+                    }
+                    button { onclick: move |_| selected.set(None), "Close" }
+                }
+            }
+            if store.count() == 0 {
+                p { "No units configured." }
+            }
+            for kind in UnitKind::CONFIGURABLE {
+                if store.units_of_kind(kind).any(|unit| shown(unit.id())) {
+                    div { class: "status-group",
+                        h3 { "{kind.plural_label()}" }
+                        div { class: "status-grid",
+                            for unit in store.units_of_kind(kind).filter(|unit| shown(unit.id())) {
+                                StatusTile {
+                                    key: "{unit.id()}",
+                                    id: unit.id(),
+                                    name: unit.name().to_string(),
+                                    address: unit.address().map(|address| address.to_string()),
+                                    state: status.state(unit.id()),
+                                    unacknowledged: status.unacknowledged().contains(&unit.id()),
+                                    selected: selected() == Some(unit.id()),
+                                    on_select: move |id| selected.set(Some(id)),
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// One unit of the status grid. Units whose alarm or fault is not yet acknowledged flash.
+#[component]
+fn StatusTile(
+    id: u64,
+    name: String,
+    address: Option<String>,
+    state: UnitState,
+    unacknowledged: bool,
+    selected: bool,
+    on_select: EventHandler<u64>,
+) -> Element {
+    let title = match &address {
+        Some(address) => format!("{} at {}: {}", name, address, state),
+        None => format!("{}: {}", name, state),
+    };
+    let mut class = format!("status-tile {}", state_class(state));
+    if unacknowledged {
+        class.push_str(" unacknowledged");
+    }
+    if selected {
+        class.push_str(" selected");
+    }
+
+    rsx! {
+        div { class: "{class}", title: "{title}", onclick: move |_| on_select.call(id),
+            span { class: "status-name", "{name}" }
+            span { class: "status-state", "{state}" }
+        }
+    }
+}
+
+/// The CSS class that colours a tile in `state`.
+fn state_class(state: UnitState) -> &'static str {
+    match state {
+        UnitState::Normal => "state-normal",
+        UnitState::PreAlarm => "state-pre-alarm",
+        UnitState::Fire => "state-fire",
+        UnitState::Active => "state-active",
+        UnitState::Fault(_) => "state-fault",
+        UnitState::Disabled => "state-disabled",
+        UnitState::InTest => "state-test",
+    }
+}
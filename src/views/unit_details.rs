@@ -0,0 +1,774 @@
+use crate::model::{
+    check_attribute, check_description, check_serial_number, AlarmDeviceType, BulkAction, BulkEdit,
+    Command, DetectorType, History, Location, LoopAddress, UnitKind, UnitProperties, UnitStore,
+    MAX_DESCRIPTION_LENGTH, MAX_OUTPUT_DELAY_SECONDS, MAX_SERIAL_NUMBER_LENGTH,
+};
+use dioxus::prelude::*;
+
+/// The editor shown in the "Selected Unit" panel of the Config page.
+///
+/// Renames, moves, zone assignments, property edits, units added below and deletes go through the shared [`History`] so
+/// they can be undone. Enter applies a rename, Escape discards it, and Delete (outside the text fields) asks to delete
+/// the unit.
+#[component]
+pub fn UnitDetails(unit_id: u64, on_deleted: EventHandler<()>) -> Element {
+    let mut unit_store = use_context::<Signal<UnitStore>>();
+    let mut history = use_context::<Signal<History>>();
+    let initial_name = unit_store
+        .read()
+        .get_unit(unit_id)
+        .map(|unit| unit.name().to_string())
+        .unwrap_or_default();
+    let mut draft_name = use_signal(|| initial_name);
+    let mut confirm_delete = use_signal(|| false);
+    let mut feedback = use_signal(|| None::<String>);
+
+    let store = unit_store.read();
+    let Some(unit) = store.get_unit(unit_id) else {
+        return rsx! {};
+    };
+    let kind = unit.kind();
+    let current_name = unit.name().to_string();
+    let name_check = store.check_name(unit_id, &draft_name());
+    let name_changed = draft_name() != current_name;
+    let delete_prompt = match units_below_note(&store, &[unit_id]) {
+        Some(note) => format!("Delete {current_name} and {note}? "),
+        None => format!("Delete {current_name}? "),
+    };
+
+    let mut rename = move || {
+        let name = draft_name();
+        if unit_store.read().check_name(unit_id, &name).is_err() {
+            return;
+        }
+        history.write().execute(
+            &mut unit_store.write(),
+            Command::SetName { id: unit_id, name },
+        );
+    };
+
+    let mut delete = move || {
+        history
+            .write()
+            .execute(&mut unit_store.write(), Command::RemoveUnit { id: unit_id });
+        on_deleted.call(());
+    };
+
+    let mut set_properties = move |properties: UnitProperties| {
+        let applied = history.write().execute(
+            &mut unit_store.write(),
+            Command::SetProperties {
+                id: unit_id,
+                properties,
+            },
+        );
+        if applied {
+            feedback.set(None);
+        }
+    };
+
+    rsx! {
+        div {
+            id: "unit-details",
+            tabindex: "0",
+            onkeydown: move |evt: KeyboardEvent| {
+                if evt.key() == Key::Delete {
+                    confirm_delete.set(true);
+                }
+            },
+            h4 { "Unit Details:" }
+            p { "Id: {unit_id}" }
+            p { "Kind: {kind}" }
+            ParentSelect { unit_id }
+            div { class: "unit-field",
+                label { "Name: " }
+                input {
+                    value: "{draft_name}",
+                    class: if name_check.is_err() { "invalid" } else { "" },
+                    oninput: move |evt| draft_name.set(evt.value()),
+                    onkeydown: move |evt: KeyboardEvent| {
+                        // Keys typed into the field must not reach the panel's Delete shortcut
+                        evt.stop_propagation();
+                        match evt.key() {
+                            Key::Enter => rename(),
+                            Key::Escape => {
+                                let name = unit_store
+                                    .read()
+                                    .get_unit(unit_id)
+                                    .map(|unit| unit.name().to_string())
+                                    .unwrap_or_default();
+                                draft_name.set(name);
+                            }
+                            _ => {}
+                        }
+                    },
+                }
+                button {
+                    disabled: !name_changed || name_check.is_err(),
+                    onclick: move |_| rename(),
+                    "Rename"
+                }
+            }
+            if let Err(err) = &name_check {
+                p { class: "unit-error", "Name {err}" }
+            }
+            if store.network().panels().len() > 1 {
+                PanelSelect { unit_id }
+            }
+            if kind.zone_kind().is_some() {
+                ZoneSelect { unit_store, member: unit_id }
+            }
+            if kind.is_addressable() {
+                AddressField { unit_id }
+            }
+            match unit.properties() {
+                UnitProperties::Detector { detector_type } => rsx! {
+                    div { class: "unit-field",
+                        label { "Detector type: " }
+                        select {
+                            onchange: move |evt| {
+                                if let Some(detector_type) = DetectorType::ALL
+                                    .into_iter()
+                                    .find(|option| option.to_string() == evt.value())
+                                {
+                                    set_properties(UnitProperties::Detector { detector_type });
+                                }
+                            },
+                            for option in DetectorType::ALL {
+                                option {
+                                    value: "{option}",
+                                    selected: option == detector_type,
+                                    "{option}"
+                                }
+                            }
+                        }
+                    }
+                },
+                UnitProperties::FireAlarmDevice { device_type } => rsx! {
+                    div { class: "unit-field",
+                        label { "Device type: " }
+                        select {
+                            onchange: move |evt| {
+                                if let Some(device_type) = AlarmDeviceType::ALL
+                                    .into_iter()
+                                    .find(|option| option.to_string() == evt.value())
+                                {
+                                    set_properties(UnitProperties::FireAlarmDevice { device_type });
+                                }
+                            },
+                            for option in AlarmDeviceType::ALL {
+                                option {
+                                    value: "{option}",
+                                    selected: option == device_type,
+                                    "{option}"
+                                }
+                            }
+                        }
+                    }
+                },
+                UnitProperties::Output { delay_seconds } => rsx! {
+                    div { class: "unit-field",
+                        label { "Delay (s): " }
+                        input {
+                            r#type: "number",
+                            min: "0",
+                            max: "{MAX_OUTPUT_DELAY_SECONDS}",
+                            value: "{delay_seconds}",
+                            onkeydown: move |evt: KeyboardEvent| evt.stop_propagation(),
+                            onchange: move |evt| {
+                                let Ok(delay_seconds) = evt.value().parse::<u32>() else {
+                                    feedback.set(Some("Delay must be a whole number of seconds".to_string()));
+                                    return;
+                                };
+                                let properties = UnitProperties::Output { delay_seconds };
+                                match properties.validate(kind) {
+                                    Ok(()) => set_properties(properties),
+                                    Err(err) => feedback.set(Some(format!("Delay: {}", err))),
+                                }
+                            },
+                        }
+                    }
+                },
+                UnitProperties::DetectionZone { floor_area_m2, exits } => rsx! {
+                    div { class: "unit-field",
+                        label { "Floor area (m²): " }
+                        input {
+                            r#type: "number",
+                            min: "0",
+                            value: "{floor_area_m2}",
+                            onkeydown: move |evt: KeyboardEvent| evt.stop_propagation(),
+                            onchange: move |evt| match evt.value().parse::<u32>() {
+                                Ok(floor_area_m2) => set_properties(UnitProperties::DetectionZone { floor_area_m2, exits }),
+                                Err(_) => feedback.set(Some("Floor area must be a whole number of square metres".to_string())),
+                            },
+                        }
+                    }
+                    div { class: "unit-field",
+                        label { "Exits: " }
+                        input {
+                            r#type: "number",
+                            min: "0",
+                            value: "{exits}",
+                            onkeydown: move |evt: KeyboardEvent| evt.stop_propagation(),
+                            onchange: move |evt| match evt.value().parse::<u32>() {
+                                Ok(exits) => set_properties(UnitProperties::DetectionZone { floor_area_m2, exits }),
+                                Err(_) => feedback.set(Some("Exits must be a whole number".to_string())),
+                            },
+                        }
+                    }
+                },
+                UnitProperties::None => rsx! {},
+            }
+            CommissioningFields { unit_id }
+            if !kind.is_zone() {
+                AddBelow { parent: unit_id }
+            }
+            if let Some(message) = feedback() {
+                p { class: "unit-error", "{message}" }
+            }
+            div { class: "unit-actions",
+                if confirm_delete() {
+                    span { "{delete_prompt}" }
+                    button { class: "delete", onclick: move |_| delete(), "Delete" }
+                    button { onclick: move |_| confirm_delete.set(false), "Cancel" }
+                } else {
+                    button {
+                        class: "delete",
+                        title: "Delete (Del)",
+                        onclick: move |_| confirm_delete.set(true),
+                        "Delete Unit"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// How many units below `ids` are named in a delete prompt before the rest are only counted.
+const NAMED_BELOW: usize = 5;
+
+/// Describes the units that go with `ids` when they are deleted, such as "the 2 units below it (Base Sounder, Beacon)",
+/// or returns `None` if nothing is below them.
+pub(super) fn units_below_note(store: &UnitStore, ids: &[u64]) -> Option<String> {
+    let below = store.units_below(ids);
+    let mut names: Vec<String> = below
+        .iter()
+        .take(NAMED_BELOW)
+        .filter_map(|id| store.get_unit(*id).map(|unit| unit.name().to_string()))
+        .collect();
+    if below.len() > NAMED_BELOW {
+        names.push(format!("{} more", below.len() - NAMED_BELOW));
+    }
+    let pronoun = if ids.len() == 1 { "it" } else { "them" };
+    match below.len() {
+        0 => None,
+        1 => Some(format!("the unit below {pronoun} ({})", names.join(", "))),
+        count => Some(format!(
+            "the {count} units below {pronoun} ({})",
+            names.join(", ")
+        )),
+    }
+}
+
+/// Lets the user pick which zone a unit belongs to.
+///
+/// Only zones of the kind the unit can join are offered, so the assignment cannot fail on kind.
+#[component]
+fn ZoneSelect(unit_store: Signal<UnitStore>, member: u64) -> Element {
+    let mut history = use_context::<Signal<History>>();
+    let store = unit_store.read();
+    let Some(zone_kind) = store
+        .get_unit(member)
+        .and_then(|unit| unit.kind().zone_kind())
+    else {
+        return rsx! {};
+    };
+    let current_zone = store.zone_of(member);
+
+    rsx! {
+        p {
+            "{zone_kind}: "
+            select {
+                onchange: move |evt| {
+                    let command = match evt.value().parse::<u64>() {
+                        Ok(zone) => Command::AssignZone { member, zone },
+                        Err(_) => Command::UnassignZone { member },
+                    };
+                    history.write().execute(&mut unit_store.write(), command);
+                },
+                option { value: "", selected: current_zone.is_none(), "Unassigned" }
+                for zone in store.units_of_kind(zone_kind) {
+                    option {
+                        value: "{zone.id()}",
+                        selected: current_zone == Some(zone.id()),
+                        "{zone.name()}"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Lets the user move a unit, with its subtree, under the Root or another unit. Zones stay under the Root, so they get no
+/// choice.
+///
+/// Zones and units in the subtree of the unit are not offered, so the move cannot fail. Units on another panel are marked,
+/// since the subtree moves to their panel. Hovering shows the full path.
+#[component]
+fn ParentSelect(unit_id: u64) -> Element {
+    let mut unit_store = use_context::<Signal<UnitStore>>();
+    let mut history = use_context::<Signal<History>>();
+
+    let store = unit_store.read();
+    let Some(unit) = store.get_unit(unit_id) else {
+        return rsx! {};
+    };
+    let (Some(current), false) = (unit.parent(), unit.kind().is_zone()) else {
+        return rsx! {};
+    };
+    let path = store
+        .ancestors(unit_id)
+        .iter()
+        .rev()
+        .filter_map(|id| store.get_unit(*id).map(|unit| unit.name()))
+        .collect::<Vec<_>>()
+        .join(" / ");
+    let subtree = store.descendants(unit_id);
+    let units = store.get_all_units().iter().skip(1).filter(|unit| {
+        let id = unit.id();
+        !unit.kind().is_zone() && id != unit_id && !subtree.contains(&id)
+    });
+    // Units on another panel take the moved subtree to their panel
+    let own_panel = store.panel_of(unit_id);
+    let parents = std::iter::once(store.root_unit())
+        .chain(units)
+        .map(|parent| {
+            let panel = store
+                .panel_of(parent.id())
+                .filter(|panel| Some(*panel) != own_panel);
+            (
+                parent,
+                panel.and_then(|number| store.network().get_panel(number)),
+            )
+        });
+
+    rsx! {
+        p { title: "{path}",
+            "Parent: "
+            select {
+                onchange: move |evt| {
+                    if let Ok(parent) = evt.value().parse() {
+                        history
+                            .write()
+                            .execute(&mut unit_store.write(), Command::MoveUnit { id: unit_id, parent });
+                    }
+                },
+                for (parent, other_panel) in parents {
+                    option {
+                        value: "{parent.id()}",
+                        selected: parent.id() == current,
+                        if let Some(panel) = other_panel {
+                            "{parent.name()} (moves to {panel})"
+                        } else {
+                            "{parent.name()}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Lets the user add a unit below another one, named the way Configure names new units. Zones cannot be added below
+/// units, so they are not offered.
+#[component]
+fn AddBelow(parent: u64) -> Element {
+    let mut unit_store = use_context::<Signal<UnitStore>>();
+    let mut history = use_context::<Signal<History>>();
+    let mut kind = use_signal(|| UnitKind::FireAlarmDevice);
+
+    rsx! {
+        div { class: "unit-field",
+            label { "Add below: " }
+            select {
+                onchange: move |evt| {
+                    if let Some(picked) = UnitKind::CONFIGURABLE
+                        .into_iter()
+                        .find(|option| option.to_string() == evt.value())
+                    {
+                        kind.set(picked);
+                    }
+                },
+                for option in UnitKind::CONFIGURABLE.into_iter().filter(|option| !option.is_zone()) {
+                    option { value: "{option}", selected: option == kind(), "{option}" }
+                }
+            }
+            button {
+                onclick: move |_| {
+                    let name = unit_store.read().free_name(kind());
+                    history
+                        .write()
+                        .execute(
+                            &mut unit_store.write(),
+                            Command::AddUnit {
+                                parent,
+                                kind: kind(),
+                                name,
+                            },
+                        );
+                },
+                "Add"
+            }
+        }
+    }
+}
+
+/// Lets the user move a unit, with its subtree, to another panel. Units mounted below another unit only show the panel
+/// they share with it.
+#[component]
+fn PanelSelect(unit_id: u64) -> Element {
+    let mut unit_store = use_context::<Signal<UnitStore>>();
+    let mut history = use_context::<Signal<History>>();
+
+    let store = unit_store.read();
+    let current = store.panel_of(unit_id);
+    let mounted_below = store
+        .get_unit(unit_id)
+        .and_then(|unit| unit.parent())
+        .filter(|parent| *parent != 0)
+        .and_then(|parent| store.get_unit(parent));
+    if let Some(parent) = mounted_below {
+        let panel = current
+            .and_then(|number| store.network().get_panel(number))
+            .map(|panel| panel.to_string())
+            .unwrap_or_default();
+        return rsx! {
+            p { "Panel: {panel} (with {parent.name()})" }
+        };
+    }
+
+    rsx! {
+        p {
+            "Panel: "
+            select {
+                onchange: move |evt| {
+                    let Ok(panel) = evt.value().parse() else {
+                        return;
+                    };
+                    let edit = BulkEdit::new([unit_id], BulkAction::MoveToPanel { panel });
+                    history.write().execute(&mut unit_store.write(), Command::Bulk { edit });
+                },
+                for panel in store.network().panels() {
+                    option {
+                        value: "{panel.number()}",
+                        selected: current == Some(panel.number()),
+                        "{panel}"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Edits the loop and address of an addressable device.
+///
+/// Addresses that are out of range or used by another device are refused with the reason shown below the field.
+#[component]
+fn AddressField(unit_id: u64) -> Element {
+    let mut unit_store = use_context::<Signal<UnitStore>>();
+    let mut history = use_context::<Signal<History>>();
+    let mut feedback = use_signal(|| None::<String>);
+
+    let store = unit_store.read();
+    let current = store.get_unit(unit_id).and_then(|unit| unit.address());
+    let isolator = store
+        .get_unit(unit_id)
+        .is_some_and(|unit| unit.has_isolator());
+    let loop_numbers: Vec<u16> = store.loops().iter().map(|l| l.number()).collect();
+
+    let mut set_address = move |address: Option<LoopAddress>| {
+        let check = unit_store.read().check_address(unit_id, address);
+        match check {
+            Ok(()) => {
+                history.write().execute(
+                    &mut unit_store.write(),
+                    Command::SetAddress {
+                        id: unit_id,
+                        address,
+                    },
+                );
+                feedback.set(None);
+            }
+            Err(err) => feedback.set(Some(format!("Address: {}", err))),
+        }
+    };
+
+    let auto_address = move |_| {
+        history.write().execute(
+            &mut unit_store.write(),
+            Command::AutoAddress { id: unit_id },
+        );
+        feedback.set(None);
+    };
+
+    rsx! {
+        div { class: "unit-field",
+            label { "Loop: " }
+            select {
+                onchange: move |evt| {
+                    let Ok(number) = evt.value().parse::<u16>() else {
+                        set_address(None);
+                        return;
+                    };
+                    match unit_store.read().first_free_address(number) {
+                        Ok(address) => set_address(Some(address)),
+                        Err(err) => feedback.set(Some(format!("Address: {}", err))),
+                    }
+                },
+                option { value: "", selected: current.is_none(), "Not addressed" }
+                for number in loop_numbers {
+                    option {
+                        value: "{number}",
+                        selected: current.map(|a| a.loop_number) == Some(number),
+                        "Loop {number}"
+                    }
+                }
+            }
+            if let Some(address) = current {
+                label { " Address: " }
+                input {
+                    r#type: "number",
+                    min: "1",
+                    value: "{address.address}",
+                    onkeydown: move |evt: KeyboardEvent| evt.stop_propagation(),
+                    onchange: move |evt| match evt.value().parse::<u16>() {
+                        Ok(number) => set_address(Some(LoopAddress::new(address.loop_number, number))),
+                        Err(_) => feedback.set(Some("Address must be a whole number".to_string())),
+                    },
+                }
+            }
+            if current.is_none() {
+                button { title: "First free address on the first loop with room", onclick: auto_address, "Auto" }
+            }
+        }
+        div { class: "unit-field",
+            label {
+                input {
+                    r#type: "checkbox",
+                    checked: isolator,
+                    onchange: move |evt| {
+                        history.write().execute(
+                            &mut unit_store.write(),
+                            Command::SetIsolator {
+                                id: unit_id,
+                                isolator: evt.checked(),
+                            },
+                        );
+                    },
+                }
+                " Short-circuit isolator"
+            }
+        }
+        if let Some(message) = feedback() {
+            p { class: "unit-error", "{message}" }
+        }
+    }
+}
+
+/// Edits where a unit is installed and the details recorded when it was commissioned.
+///
+/// Each field is applied when it loses focus. Text the model refuses is kept out of the store and the reason is shown
+/// below the fields.
+#[component]
+fn CommissioningFields(unit_id: u64) -> Element {
+    let mut unit_store = use_context::<Signal<UnitStore>>();
+    let mut history = use_context::<Signal<History>>();
+    let mut feedback = use_signal(|| None::<String>);
+    let mut new_key = use_signal(String::new);
+    let mut new_value = use_signal(String::new);
+
+    let store = unit_store.read();
+    let Some(unit) = store.get_unit(unit_id) else {
+        return rsx! {};
+    };
+    let location = unit.location().clone();
+    let description = unit.description().to_string();
+    let install_date = unit
+        .install_date()
+        .map(|date| date.to_string())
+        .unwrap_or_default();
+    let serial_number = unit.serial_number().to_string();
+    let attributes: Vec<(String, String)> = unit
+        .attributes()
+        .iter()
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    let attribute_check = check_attribute(unit.attributes(), new_key.read().trim(), &new_value());
+
+    let mut execute = move |command: Command| {
+        history.write().execute(&mut unit_store.write(), command);
+        feedback.set(None);
+    };
+
+    let mut set_location = move |location: Location| match location.validate() {
+        Ok(()) => execute(Command::SetLocation {
+            id: unit_id,
+            location,
+        }),
+        Err(err) => feedback.set(Some(format!("Location: {}", err))),
+    };
+    let (building, floor, room) = (
+        location.building.clone(),
+        location.floor.clone(),
+        location.room.clone(),
+    );
+
+    let mut add_attribute = move || {
+        let key = new_key.read().trim().to_string();
+        let value = new_value();
+        let check = check_attribute(
+            unit_store
+                .read()
+                .get_unit(unit_id)
+                .map(|unit| unit.attributes())
+                .unwrap_or(&Default::default()),
+            &key,
+            &value,
+        );
+        match check {
+            Ok(()) => {
+                execute(Command::SetAttribute {
+                    id: unit_id,
+                    key,
+                    value: Some(value),
+                });
+                new_key.set(String::new());
+                new_value.set(String::new());
+            }
+            Err(err) => feedback.set(Some(format!("Attribute: {}", err))),
+        }
+    };
+
+    rsx! {
+        div { class: "unit-field location-fields",
+            label { "Location: " }
+            input {
+                placeholder: "Building",
+                value: "{building}",
+                onkeydown: move |evt: KeyboardEvent| evt.stop_propagation(),
+                onchange: {
+                    let location = location.clone();
+                    move |evt: FormEvent| set_location(Location::new(&evt.value(), &location.floor, &location.room))
+                },
+            }
+            input {
+                placeholder: "Floor",
+                value: "{floor}",
+                onkeydown: move |evt: KeyboardEvent| evt.stop_propagation(),
+                onchange: {
+                    let location = location.clone();
+                    move |evt: FormEvent| set_location(Location::new(&location.building, &evt.value(), &location.room))
+                },
+            }
+            input {
+                placeholder: "Room",
+                value: "{room}",
+                onkeydown: move |evt: KeyboardEvent| evt.stop_propagation(),
+                onchange: move |evt: FormEvent| set_location(Location::new(&location.building, &location.floor, &evt.value())),
+            }
+        }
+        div { class: "unit-field",
+            label { "Description: " }
+            textarea {
+                rows: "3",
+                maxlength: "{MAX_DESCRIPTION_LENGTH}",
+                value: "{description}",
+                onkeydown: move |evt: KeyboardEvent| evt.stop_propagation(),
+                onchange: move |evt| {
+                    let description = evt.value().trim_end().to_string();
+                    match check_description(&description) {
+                        Ok(()) => execute(Command::SetDescription { id: unit_id, description }),
+                        Err(err) => feedback.set(Some(format!("Description: {}", err))),
+                    }
+                },
+            }
+        }
+        div { class: "unit-field",
+            label { "Installed: " }
+            input {
+                r#type: "date",
+                value: "{install_date}",
+                onkeydown: move |evt: KeyboardEvent| evt.stop_propagation(),
+                onchange: move |evt| match evt.value().trim() {
+                    "" => execute(Command::SetInstallDate { id: unit_id, install_date: None }),
+                    value => match value.parse() {
+                        Ok(date) => execute(Command::SetInstallDate { id: unit_id, install_date: Some(date) }),
+                        Err(err) => feedback.set(Some(format!("Install date: {}", err))),
+                    },
+                },
+            }
+        }
+        div { class: "unit-field",
+            label { "Serial number: " }
+            input {
+                maxlength: "{MAX_SERIAL_NUMBER_LENGTH}",
+                value: "{serial_number}",
+                onkeydown: move |evt: KeyboardEvent| evt.stop_propagation(),
+                onchange: move |evt| {
+                    let serial_number = evt.value().trim().to_string();
+                    match check_serial_number(&serial_number) {
+                        Ok(()) => execute(Command::SetSerialNumber { id: unit_id, serial_number }),
+                        Err(err) => feedback.set(Some(format!("Serial number: {}", err))),
+                    }
+                },
+            }
+        }
+        div { class: "unit-field",
+            label { "Attributes:" }
+            ul { class: "unit-attributes",
+                for (key, value) in attributes {
+                    li { key: "{key}",
+                        "{key} = {value}"
+                        button {
+                            class: "delete",
+                            title: "Remove {key}",
+                            onclick: {
+                                let key = key.clone();
+                                move |_| execute(Command::SetAttribute { id: unit_id, key: key.clone(), value: None })
+                            },
+                            "✕"
+                        }
+                    }
+                }
+            }
+            input {
+                placeholder: "Key",
+                value: "{new_key}",
+                oninput: move |evt| new_key.set(evt.value()),
+                onkeydown: move |evt: KeyboardEvent| evt.stop_propagation(),
+            }
+            input {
+                placeholder: "Value",
+                value: "{new_value}",
+                oninput: move |evt| new_value.set(evt.value()),
+                onkeydown: move |evt: KeyboardEvent| {
+                    evt.stop_propagation();
+                    if evt.key() == Key::Enter {
+                        add_attribute();
+                    }
+                },
+            }
+            button {
+                disabled: new_key.read().trim().is_empty() || attribute_check.is_err(),
+                onclick: move |_| add_attribute(),
+                "Set"
+            }
+        }
+        if let (false, Err(err)) = (new_key.read().trim().is_empty(), &attribute_check) {
+            p { class: "unit-error", "Attribute: {err}" }
+        }
+        if let Some(message) = feedback() {
+            p { class: "unit-error", "{message}" }
+        }
+    }
+}
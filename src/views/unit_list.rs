@@ -0,0 +1,212 @@
+use super::validation::{severity_class, ValidationResults};
+use crate::components::VirtualList;
+use crate::model::{
+    LoopFilter, PanelFilter, Selection, SortKey, UnitKind, UnitQuery, UnitStore, ZoneFilter,
+};
+use dioxus::prelude::*;
+
+/// Height in pixels of one row of the list; `.unit-row` in config.css must match.
+const ROW_HEIGHT: f64 = 32.0;
+/// Height in pixels of the scrolling part of the list.
+const LIST_HEIGHT: f64 = 300.0;
+
+/// The searchable, sortable list of units on the Config page.
+///
+/// Selection is by unit id, so selected units stay selected while the list is filtered or re-sorted. Ctrl-click (Cmd on
+/// macOS) adds or removes a unit and shift-click selects a range of the list as shown. Units with
+/// findings in the last validation run are coloured by their most serious finding. Only the rows in view are rendered, so configurations
+/// with thousands of units stay responsive.
+#[component]
+pub fn UnitList(selection: Signal<Selection>, validation: Signal<ValidationResults>) -> Element {
+    let unit_store = use_context::<Signal<UnitStore>>();
+    let mut query = use_signal(UnitQuery::default);
+    // The panel picked in the navbar narrows the list like any other filter
+    let panel_filter = use_context::<Signal<PanelFilter>>();
+
+    // Ids of the matching units in display order, recomputed only when the store, the query or the panel changes
+    let rows = use_memo(move || {
+        let query = UnitQuery {
+            panel: panel_filter().panel(),
+            ..query.read().clone()
+        };
+        query
+            .run(&unit_store.read())
+            .iter()
+            .map(|unit| unit.id())
+            .collect::<Vec<u64>>()
+    });
+    let store = unit_store.read();
+    let current = query.read().clone();
+    let kind_counts = match panel_filter().panel() {
+        Some(panel) => store.counts_on_panel(panel),
+        None => store.counts_by_kind(),
+    };
+
+    let sort_marker = |key: SortKey| match (current.sort == key, current.descending) {
+        (true, false) => " ▲",
+        (true, true) => " ▼",
+        (false, _) => "",
+    };
+
+    rsx! {
+        div { id: "unit-list-panel",
+            h3 { style: "text-align: center;", "Units ({rows.read().len()} of {store.count()})" }
+            div { id: "unit-search",
+                input {
+                    r#type: "search",
+                    placeholder: "Search names",
+                    value: "{current.text}",
+                    oninput: move |evt| query.write().text = evt.value(),
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: current.fuzzy,
+                        onchange: move |evt| query.write().fuzzy = evt.checked(),
+                    }
+                    " Fuzzy"
+                }
+            }
+            div { id: "unit-filters",
+                select {
+                    onchange: move |evt| {
+                        let value = evt.value();
+                        query.write().kind = UnitKind::CONFIGURABLE
+                            .into_iter()
+                            .find(|kind| kind.label() == value);
+                    },
+                    option { value: "", selected: current.kind.is_none(), "All kinds" }
+                    for (kind, count) in kind_counts {
+                        option {
+                            value: "{kind.label()}",
+                            selected: current.kind == Some(kind),
+                            "{kind.plural_label()} ({count})"
+                        }
+                    }
+                }
+                select {
+                    onchange: move |evt| {
+                        let value = evt.value();
+                        query.write().zone = match value.as_str() {
+                            "" => None,
+                            "unassigned" => Some(ZoneFilter::Unassigned),
+                            id => id.parse().ok().map(ZoneFilter::Zone),
+                        };
+                    },
+                    option { value: "", selected: current.zone.is_none(), "All zones" }
+                    option {
+                        value: "unassigned",
+                        selected: current.zone == Some(ZoneFilter::Unassigned),
+                        "Not in a zone"
+                    }
+                    for zone in store
+                        .get_all_units()
+                        .iter()
+                        .filter(|unit| unit.kind().is_zone())
+                    {
+                        option {
+                            value: "{zone.id()}",
+                            selected: current.zone == Some(ZoneFilter::Zone(zone.id())),
+                            "{zone.name()}"
+                        }
+                    }
+                }
+                select {
+                    onchange: move |evt| {
+                        let value = evt.value();
+                        query.write().loop_filter = match value.as_str() {
+                            "" => None,
+                            "unaddressed" => Some(LoopFilter::Unaddressed),
+                            number => number.parse().ok().map(LoopFilter::Loop),
+                        };
+                    },
+                    option { value: "", selected: current.loop_filter.is_none(), "All loops" }
+                    option {
+                        value: "unaddressed",
+                        selected: current.loop_filter == Some(LoopFilter::Unaddressed),
+                        "Not addressed"
+                    }
+                    for l in store.loops() {
+                        option {
+                            value: "{l.number()}",
+                            selected: current.loop_filter == Some(LoopFilter::Loop(l.number())),
+                            "Loop {l.number()}"
+                        }
+                    }
+                }
+            }
+            div { id: "unit-selection-buttons",
+                button {
+                    disabled: rows.read().is_empty(),
+                    onclick: move |_| selection.write().select_all(&rows.read()),
+                    "Select All Shown"
+                }
+                button {
+                    disabled: selection.read().is_empty(),
+                    onclick: move |_| selection.write().clear(),
+                    "Select None"
+                }
+            }
+            div { id: "unit-table",
+                div { class: "unit-row unit-header",
+                    for key in SortKey::ALL {
+                        button {
+                            title: "Sort by {key.label()}",
+                            onclick: move |_| query.write().toggle_sort(key),
+                            "{key.label()}{sort_marker(key)}"
+                        }
+                    }
+                }
+                VirtualList {
+                    row_count: rows.read().len(),
+                    row_height: ROW_HEIGHT,
+                    height: LIST_HEIGHT,
+                    render_row: move |index: usize| {
+                        let Some(id) = rows.read().get(index).copied() else {
+                            return rsx! {};
+                        };
+                        let store = unit_store.read();
+                        let Some(unit) = store.get_unit(id) else {
+                            return rsx! {};
+                        };
+                        let zone_name = store
+                            .zone_of(id)
+                            .and_then(|zone| store.get_unit(zone))
+                            .map(|zone| zone.name())
+                            .unwrap_or_default();
+                        let finding_class = validation
+                            .read()
+                            .worst(id)
+                            .map(severity_class)
+                            .unwrap_or_default();
+                        rsx! {
+                            div {
+                                key: "{id}",
+                                class: format!(
+                                    "unit-row {} {}",
+                                    if selection.read().contains(id) { "selected" } else { "" },
+                                    finding_class,
+                                ),
+                                onclick: move |evt: MouseEvent| {
+                                    let modifiers = evt.modifiers();
+                                    if modifiers.shift() {
+                                        selection.write().extend_to(id, &rows.read());
+                                    } else if modifiers.ctrl() || modifiers.meta() {
+                                        selection.write().toggle(id);
+                                    } else {
+                                        selection.write().select_only(id);
+                                    }
+                                },
+                                span { "{id}" }
+                                span { "{unit.name()}" }
+                                span { "{unit.kind()}" }
+                                span { "{zone_name}" }
+                                span { {unit.address().map(|address| address.to_string()).unwrap_or_default()} }
+                            }
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
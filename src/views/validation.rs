@@ -0,0 +1,220 @@
+use std::collections::BTreeMap;
+
+use crate::model::{validate, Command, Finding, History, Severity, UnitStore, ZoningThresholds};
+use dioxus::prelude::*;
+
+/// The findings of the last validation run, shared by [`ValidationPanel`] and the unit list.
+///
+/// Validating a large configuration takes a while, so it runs when asked rather than on every edit.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationResults {
+    /// The findings, errors first, or `None` before the first run.
+    pub findings: Option<Vec<Finding>>,
+    /// The most serious finding for each unit.
+    worst: BTreeMap<u64, Severity>,
+    /// Whether the configuration has changed since the last run.
+    pub stale: bool,
+}
+
+impl ValidationResults {
+    /// Validates `store`.
+    pub fn run(store: &UnitStore) -> Self {
+        let findings = validate(store);
+        let mut worst: BTreeMap<u64, Severity> = BTreeMap::new();
+        for finding in &findings {
+            for &id in &finding.units {
+                worst
+                    .entry(id)
+                    .and_modify(|worst| *worst = (*worst).min(finding.severity))
+                    .or_insert(finding.severity);
+            }
+        }
+        Self {
+            findings: Some(findings),
+            worst,
+            stale: false,
+        }
+    }
+
+    /// The most serious finding about unit `id` in the last run.
+    pub fn worst(&self, id: u64) -> Option<Severity> {
+        self.worst.get(&id).copied()
+    }
+}
+
+/// The Validation section of the Config page.
+///
+/// Lists the findings of the last check, errors first, and marks them out of date once the configuration changes.
+/// Clicking a unit name selects that unit.
+#[component]
+pub fn ValidationPanel(
+    results: Signal<ValidationResults>,
+    on_select: EventHandler<u64>,
+) -> Element {
+    let unit_store = use_context::<Signal<UnitStore>>();
+    let store = unit_store.read();
+    let current = results.read();
+    let findings = current.findings.as_deref().unwrap_or_default();
+    let count_of = |severity: Severity| {
+        findings
+            .iter()
+            .filter(|finding| finding.severity == severity)
+            .count()
+    };
+    let name_of = |id: u64| {
+        store
+            .get_unit(id)
+            .map(|unit| unit.name().to_string())
+            .unwrap_or_else(|| format!("unit {}", id))
+    };
+
+    rsx! {
+        div { id: "validation",
+            h3 {
+                "Validation "
+                if current.findings.is_some() {
+                    span { class: "finding-error", "{count_of(Severity::Error)} errors" }
+                    ", "
+                    span { class: "finding-warning", "{count_of(Severity::Warning)} warnings" }
+                }
+            }
+            button {
+                id: "validate-btn",
+                onclick: move |_| results.set(ValidationResults::run(&unit_store.read())),
+                if current.findings.is_some() { "Check again" } else { "Check" }
+            }
+            if current.findings.is_none() {
+                p { "Not checked yet" }
+            } else if current.stale {
+                p { class: "validation-stale", "The configuration has changed since the last check" }
+            } else if findings.is_empty() {
+                p { class: "validation-ok", "No problems found" }
+            }
+            ThresholdsEditor {}
+            ul { id: "validation-list",
+                for finding in findings.iter() {
+                    li { class: severity_class(finding.severity),
+                        span { class: "finding-code", "{finding.severity} · {finding.code}" }
+                        " {finding.message}"
+                        if let Some(rule) = &finding.rule {
+                            div { class: "finding-rule", "Rule: {rule}" }
+                        }
+                        if !finding.units.is_empty() {
+                            div { class: "finding-units",
+                                // Long lists only show the first few units
+                                for id in finding.units.iter().copied().take(10) {
+                                    button { onclick: move |_| on_select.call(id), "{name_of(id)}" }
+                                }
+                                if finding.units.len() > 10 {
+                                    span { " and {finding.units.len() - 10} more" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The CSS class used to colour anything tagged with `severity`.
+pub fn severity_class(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "finding-error",
+        Severity::Warning => "finding-warning",
+        Severity::Info => "finding-info",
+    }
+}
+
+/// Edits the zoning rule thresholds stored with the project.
+///
+/// Picking a variant replaces every threshold with that variant's values; each value can then be changed on its own.
+#[component]
+fn ThresholdsEditor() -> Element {
+    let mut unit_store = use_context::<Signal<UnitStore>>();
+    let mut history = use_context::<Signal<History>>();
+    let thresholds = unit_store.read().thresholds().clone();
+
+    let mut set_thresholds = move |thresholds: ZoningThresholds| {
+        if *unit_store.read().thresholds() != thresholds {
+            history.write().execute(
+                &mut unit_store.write(),
+                Command::SetThresholds { thresholds },
+            );
+        }
+    };
+
+    // Parses a threshold input, ignoring anything that is not a whole number
+    let number_input = move |value: usize, apply: fn(&mut ZoningThresholds, usize)| {
+        rsx! {
+            input {
+                r#type: "number",
+                min: "0",
+                value: "{value}",
+                onchange: move |evt| {
+                    if let Ok(value) = evt.value().parse::<usize>() {
+                        let mut thresholds = unit_store.read().thresholds().clone();
+                        apply(&mut thresholds, value);
+                        set_thresholds(thresholds);
+                    }
+                },
+            }
+        }
+    };
+
+    rsx! {
+        div { id: "thresholds",
+            h4 { "Zoning rules" }
+            div { class: "unit-field",
+                label { "Variant: " }
+                select {
+                    onchange: move |evt| {
+                        if let Some(preset) = ZoningThresholds::presets()
+                            .into_iter()
+                            .find(|preset| preset.variant == evt.value())
+                        {
+                            set_thresholds(preset);
+                        }
+                    },
+                    for preset in ZoningThresholds::presets() {
+                        option {
+                            value: "{preset.variant}",
+                            selected: preset.variant == thresholds.variant,
+                            "{preset.variant}"
+                        }
+                    }
+                }
+            }
+            table {
+                tbody {
+                    tr {
+                        td { "Devices per detection zone" }
+                        td {
+                            {number_input(thresholds.max_devices_per_zone, |t, v| t.max_devices_per_zone = v)}
+                        }
+                    }
+                    tr {
+                        td { "Detection zone floor area (m²)" }
+                        td {
+                            {number_input(thresholds.max_zone_area_m2 as usize, |t, v| {
+                                t.max_zone_area_m2 = u32::try_from(v).unwrap_or(u32::MAX)
+                            })}
+                        }
+                    }
+                    tr {
+                        td { "Manual call points per exit" }
+                        td {
+                            {number_input(thresholds.call_points_per_exit, |t, v| t.call_points_per_exit = v)}
+                        }
+                    }
+                    tr {
+                        td { "Devices lost to one loop fault" }
+                        td {
+                            {number_input(thresholds.max_devices_per_loop_fault, |t, v| t.max_devices_per_loop_fault = v)}
+                        }
+                    }
+                }
+            }
+        }
+    }
+}